@@ -0,0 +1,28 @@
+// Heat haze distortion of the playfield part of the canvas; see `rendering::HeatHazeMaterial`.
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+// x = intensity from 0 to 1, y = time in seconds
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> params: vec4<f32>;
+// min and max UV of the playfield; nothing outside it is distorted
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var<uniform> playfield_uv: vec4<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var canvas_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var canvas_sampler: sampler;
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let intensity = params.x;
+    let time = params.y;
+    var uv = mesh.uv;
+
+    let in_playfield = all(uv >= playfield_uv.xy) && all(uv <= playfield_uv.zw);
+    if in_playfield && intensity > 0.0 {
+        // Two horizontal waves travelling upward at different rates, like rising hot air.
+        let wave = sin(uv.y * 70.0 + time * 6.0)
+            + 0.5 * sin(uv.y * 151.0 + uv.x * 23.0 + time * 9.0);
+        let offset = vec2(wave * intensity * 0.002, 0.0);
+        uv = clamp(uv + offset, playfield_uv.xy, playfield_uv.zw);
+    }
+
+    return textureSample(canvas_texture, canvas_sampler, uv);
+}
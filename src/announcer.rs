@@ -0,0 +1,165 @@
+use bevy::math::{vec2, vec3};
+use bevy::prelude as b;
+
+use crate::pickup::PickupSpawnType;
+use crate::run_stats::RunEvent;
+use crate::text_effects::{TextEffect, TextEffectStyle};
+use crate::{GameAssets, GameState, SoundId, UI_LAYERS, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Kills which happened close enough together in time to count as one multi-kill.
+#[derive(Debug, Default, b::Resource)]
+pub(crate) struct KillStreak {
+    kills: u32,
+    /// Game time remaining before the streak ends, unless another kill extends it.
+    window: f32,
+}
+
+/// Time after a kill within which another kill continues the [`KillStreak`].
+const STREAK_WINDOW: f32 = 0.5;
+
+/// Escalating announcements of a [`KillStreak`], and of collecting a surge pickup or losing
+/// a life.
+#[derive(Clone, Copy, Debug)]
+enum Callout {
+    Double,
+    Volley,
+    Recombobulated,
+    Surge,
+    LifeLost,
+}
+
+/// Text showing the most recent [`Callout`].
+#[derive(Debug, Default, b::Component)]
+#[require(TextEffect)]
+pub(crate) struct CalloutText {
+    /// Real time remaining before the callout disappears.
+    remaining: f32,
+}
+
+/// Real time for which a [`Callout`] stays on screen.
+const CALLOUT_DURATION: f32 = 1.0;
+
+// -------------------------------------------------------------------------------------------------
+
+impl Callout {
+    /// The callout, if any, for the streak having reached exactly this many kills.
+    fn reached(kills: u32) -> Option<Self> {
+        match kills {
+            2 => Some(Callout::Double),
+            3 => Some(Callout::Volley),
+            5 => Some(Callout::Recombobulated),
+            _ => None,
+        }
+    }
+
+    fn text(self) -> &'static str {
+        match self {
+            Callout::Double => "Double!",
+            Callout::Volley => "Volley!",
+            Callout::Recombobulated => "Recombobulated!",
+            Callout::Surge => "Surge!",
+            Callout::LifeLost => "Ship lost!",
+        }
+    }
+
+    fn style(self) -> TextEffectStyle {
+        match self {
+            Callout::Double | Callout::Volley | Callout::LifeLost => TextEffectStyle::Plain,
+            Callout::Recombobulated | Callout::Surge => TextEffectStyle::Rainbow,
+        }
+    }
+
+    /// Playback speed of the sting sound, which rises with each step up.
+    fn sting_speed(self) -> f32 {
+        match self {
+            Callout::Double => 1.0,
+            Callout::Volley => 1.25,
+            Callout::Recombobulated => 1.5,
+            // deep and distinct from the streak stings
+            Callout::Surge => 0.6,
+            Callout::LifeLost => 0.4,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn callout_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        CalloutText::default(),
+        b::TextFont {
+            font: assets.ui_font.clone(),
+            font_size: 26.0,
+            font_smoothing: bevy::text::FontSmoothing::None,
+            ..b::default()
+        },
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        b::TextLayout::new_with_justify(b::Justify::Center),
+        bevy::sprite::Anchor::CENTER,
+        b::Transform::from_translation(vec3(0.0, 40.0, Zees::UiMiddle.z())),
+        UI_LAYERS,
+    )
+}
+
+/// Groups kills into [`KillStreak`]s and announces streaks as they grow, surges, and lost lives.
+pub(crate) fn kill_streak_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    assets: b::Res<GameAssets>,
+    mut run_events: b::MessageReader<RunEvent>,
+    mut streak: b::ResMut<KillStreak>,
+    text: b::Single<(&mut TextEffect, &mut CalloutText)>,
+) {
+    let (mut effect, mut callout_text) = text.into_inner();
+
+    streak.window -= time.delta_secs();
+    if streak.window <= 0.0 {
+        streak.kills = 0;
+    }
+
+    for event in run_events.read() {
+        let callout = match event {
+            RunEvent::EnemyKilled => {
+                streak.kills += 1;
+                streak.window = STREAK_WINDOW;
+                Callout::reached(streak.kills)
+            }
+            RunEvent::PickupCollected(PickupSpawnType::Surge) => Some(Callout::Surge),
+            RunEvent::LifeLost => Some(Callout::LifeLost),
+            _ => None,
+        };
+
+        if let Some(callout) = callout {
+            effect.set(callout.text(), callout.style());
+            callout_text.remaining = CALLOUT_DURATION;
+            commands.spawn((
+                b::AudioPlayer::new(assets.sound(SoundId::Pickup)),
+                b::PlaybackSettings {
+                    volume: bevy::audio::Volume::Decibels(-6.),
+                    speed: callout.sting_speed(),
+                    ..b::PlaybackSettings::DESPAWN
+                },
+            ));
+        }
+    }
+}
+
+/// Removes the [`Callout`] when its time is up or the run is no longer being played.
+///
+/// Uses real time, like [`TextEffect`]s do.
+pub(crate) fn expire_callout_system(
+    time: b::Res<b::Time<b::Real>>,
+    state: b::Res<b::State<GameState>>,
+    text: b::Single<(&mut TextEffect, &mut CalloutText)>,
+) {
+    let (mut effect, mut callout_text) = text.into_inner();
+    callout_text.remaining -= time.delta_secs();
+    if callout_text.remaining <= 0.0 || *state.get() != GameState::Playing {
+        effect.set("", TextEffectStyle::Plain);
+    }
+}
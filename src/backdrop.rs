@@ -0,0 +1,92 @@
+use avian2d::prelude as p;
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::math::{Vec2, vec2};
+use bevy::prelude as b;
+use rand::RngExt as _;
+use rand::seq::IndexedRandom as _;
+
+use crate::prefab::{BackdropProp, Prefabs};
+use crate::{GameAssets, Lifetime, PLAYFIELD_LAYERS, PLAYFIELD_RECT, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Component attached to a (currently) singleton entity that spawns props from the
+/// [`BackdropPrefab`](crate::prefab::BackdropPrefab) during a run.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct BackdropSpawner {
+    pub cooldown: f32,
+}
+
+/// Distance beyond the top and bottom of the playfield at which props appear and disappear.
+/// Must be at least half the size of the largest prop sprite.
+const PROP_MARGIN: f32 = 40.0;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Spawns backdrop props based on [`BackdropSpawner`] state.
+pub(crate) fn spawn_backdrop_props_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    spawners: b::Query<&mut BackdropSpawner>,
+    prefabs: Prefabs,
+    assets: b::Res<GameAssets>,
+) -> b::Result {
+    let dt = time.delta_secs();
+    // Not the `GameRng`, so that scenery cannot change the course of a seeded run.
+    let rng = &mut rand::rng();
+
+    for mut spawner in spawners {
+        spawner.cooldown -= dt;
+        if spawner.cooldown > 0.0 {
+            continue;
+        }
+        let backdrop = prefabs.backdrop()?;
+        let (min_interval, max_interval) = backdrop.interval;
+        spawner.cooldown += rng.random_range(min_interval..=max_interval);
+
+        let Ok(prop) = backdrop.props.choose_weighted(rng, |prop| prop.weight) else {
+            continue;
+        };
+        commands.spawn(prop_bundle(&assets, rng, prop));
+    }
+    Ok(())
+}
+
+fn prop_bundle(
+    assets: &GameAssets,
+    rng: &mut rand::rngs::ThreadRng,
+    prop: &BackdropProp,
+) -> impl b::Bundle + use<> {
+    let (min_speed, max_speed) = prop.speed;
+    let angle = rng
+        .random_range(-prop.max_angle..=prop.max_angle)
+        .to_radians();
+    let velocity =
+        Vec2::from_angle(angle).rotate(Vec2::NEG_Y) * rng.random_range(min_speed..=max_speed);
+
+    let travel_time = (PLAYFIELD_RECT.height() + PROP_MARGIN * 2.0) / -velocity.y;
+    // Start offset sideways so that props moving at an angle are over the playfield
+    // halfway through their trip, rather than mostly outside it.
+    let x = rng.random_range(PLAYFIELD_RECT.min.x..=PLAYFIELD_RECT.max.x)
+        - velocity.x * travel_time / 2.0;
+    let rotation = if prop.face_heading {
+        Vec2::NEG_Y.angle_to(velocity)
+    } else {
+        0.0
+    };
+
+    (
+        b::Sprite::from_image(assets.sprite(prop.sprite)),
+        b::Transform::from_translation(
+            vec2(x, PLAYFIELD_RECT.max.y + PROP_MARGIN).extend(Zees::Backdrop.z()),
+        )
+        .with_rotation(b::Quat::from_rotation_z(rotation)),
+        PLAYFIELD_LAYERS,
+        // no collider, since props are far away and nothing touches them
+        p::RigidBody::Kinematic,
+        p::LinearVelocity(velocity),
+        p::AngularVelocity(rng.random_range(-prop.max_spin..=prop.max_spin)),
+        Lifetime(travel_time),
+    )
+}
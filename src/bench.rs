@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use avian2d::prelude as p;
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::math::{Vec2, vec2};
+use bevy::platform::time::Instant;
+use bevy::prelude as b;
+use rand::{RngExt as _, SeedableRng as _};
+
+use crate::bullets_and_targets::{Bullet, ColliderFromImage, ColliderShape};
+use crate::difficulty::Difficulty;
+use crate::prefab::Prefabs;
+use crate::quantity::{QuantitiesMut, Quantity};
+use crate::{
+    GameState, Gun, Lifetime, PLAYFIELD_LAYERS, PLAYFIELD_RECT, Player, SpriteId, Team, Zees,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Skips the menu, spawns a worst-case scene, runs it for a fixed number of fixed-update ticks,
+/// prints timing statistics, and exits.
+///
+/// Only enabled if the game is started with the `--bench-scenario` option.
+///
+/// The initial scene is deterministic, but gameplay systems still use unseeded randomness,
+/// so results will vary slightly from run to run.
+pub(crate) struct BenchPlugin {
+    pub enabled: bool,
+}
+
+impl b::Plugin for BenchPlugin {
+    fn build(&self, app: &mut b::App) {
+        if !self.enabled {
+            return;
+        }
+        b::info!("running benchmark scenario");
+
+        app.init_resource::<BenchRecording>()
+            .add_systems(b::OnEnter(GameState::Menu), skip_menu)
+            .add_systems(
+                b::OnTransition {
+                    exited: GameState::Menu,
+                    entered: GameState::Playing,
+                },
+                spawn_scenario.after(crate::start_new_game),
+            )
+            .add_systems(
+                b::FixedUpdate,
+                hold_quantities_system
+                    .after(crate::quantity::apply_quantity_deltas_system)
+                    .before(crate::quantity::quantity_behaviors_system),
+            )
+            .add_systems(
+                b::FixedUpdate,
+                hold_trigger_system
+                    .before(crate::bullets_and_targets::fire_gun_system)
+                    .run_if(b::in_state(GameState::Playing)),
+            )
+            .add_systems(
+                b::FixedFirst,
+                start_tick_system.run_if(b::in_state(GameState::Playing)),
+            )
+            .add_systems(
+                b::FixedLast,
+                finish_tick_system.run_if(b::in_state(GameState::Playing)),
+            );
+    }
+}
+
+const ENEMY_COUNT: usize = 200;
+const BULLET_COUNT: usize = 2000;
+const TICKS: usize = 1000;
+const SEED: u64 = 0x1234_5678;
+
+#[derive(Debug, Default, b::Resource)]
+struct BenchRecording {
+    tick_start: Option<Instant>,
+    tick_durations: Vec<Duration>,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+fn skip_menu(mut next_state: b::ResMut<b::NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn spawn_scenario(
+    mut commands: b::Commands,
+    assets: b::Res<crate::GameAssets>,
+    prefabs: Prefabs,
+    images: b::Res<b::Assets<b::Image>>,
+) -> b::Result {
+    let rng = &mut rand::rngs::SmallRng::seed_from_u64(SEED);
+    let enemy = prefabs.basic_enemy()?;
+    let pickups = prefabs.pickups()?;
+    let spawn_rect = PLAYFIELD_RECT.inflate(-20.0);
+
+    for _ in 0..ENEMY_COUNT {
+        let station = random_point(rng, spawn_rect);
+        commands.spawn(crate::enemy::enemy_bundle(
+            rng,
+            enemy,
+            pickups,
+            Difficulty::Normal,
+            0.0,
+            station + vec2(0.0, PLAYFIELD_RECT.size().y),
+            station,
+        ));
+    }
+
+    let bullet_image = assets.sprite(SpriteId::EnemyBullet);
+    let bullet_size = images
+        .get(&bullet_image)
+        .ok_or_else(|| b::BevyError::from("asset not loaded"))?
+        .size_f32();
+    for _ in 0..BULLET_COUNT {
+        let team = if rng.random_bool(0.5) {
+            Team::Player
+        } else {
+            Team::Enemy
+        };
+        let velocity = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU))
+            * rng.random_range(50.0..=250.0);
+        commands.spawn((
+            Bullet {
+                damage: 1,
+                reach: bullet_size.max_element() / 2.0,
+                inflicts: None,
+            },
+            team,
+            Lifetime(10.0),
+            b::Sprite::from_image(bullet_image.clone()),
+            PLAYFIELD_LAYERS,
+            p::RigidBody::Kinematic,
+            p::LinearVelocity(velocity),
+            ColliderShape::Ellipse.fit(bullet_size),
+            ColliderFromImage(bullet_image.clone(), ColliderShape::Ellipse),
+            b::Transform::from_translation(
+                random_point(rng, PLAYFIELD_RECT).extend(Zees::Bullets.z()),
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn random_point(rng: &mut impl rand::Rng, rect: b::Rect) -> Vec2 {
+    vec2(
+        rng.random_range(rect.min.x..=rect.max.x),
+        rng.random_range(rect.min.y..=rect.max.y),
+    )
+}
+
+/// Keeps coherence and fervor at their most expensive values, and keeps the player from losing.
+fn hold_quantities_system(mut quantities: QuantitiesMut) -> b::Result {
+    let [mut coherence, mut fever, mut fervor] = quantities.all_mut()?;
+    *coherence = Quantity::new(1.0);
+    *fever = Quantity::new(0.0);
+    // as high as possible without winning
+    *fervor = Quantity::new(0.9);
+    Ok(())
+}
+
+fn hold_trigger_system(gun: b::Single<&mut Gun, b::With<Player>>) {
+    gun.into_inner().trigger = true;
+}
+
+fn start_tick_system(mut recording: b::ResMut<BenchRecording>) {
+    recording.tick_start = Some(Instant::now());
+}
+
+fn finish_tick_system(
+    mut recording: b::ResMut<BenchRecording>,
+    entities: b::Query<()>,
+    mut app_exit: b::MessageWriter<b::AppExit>,
+) {
+    let Some(start) = recording.tick_start.take() else {
+        return;
+    };
+    recording.tick_durations.push(start.elapsed());
+
+    if recording.tick_durations.len() == TICKS {
+        let durations = &mut recording.tick_durations;
+        durations.sort_unstable();
+        let percentile = |p: usize| durations[(durations.len() - 1) * p / 100];
+        let mean = durations.iter().sum::<Duration>() / durations.len() as u32;
+
+        println!(
+            "bench-scenario: {ENEMY_COUNT} enemies, {BULLET_COUNT} bullets, \
+                {TICKS} fixed-update ticks, {entities} entities at end",
+            entities = entities.iter().count()
+        );
+        println!(
+            "tick time: mean {mean:?}, min {min:?}, p50 {p50:?}, p95 {p95:?}, p99 {p99:?}, \
+                max {max:?}",
+            min = percentile(0),
+            p50 = percentile(50),
+            p95 = percentile(95),
+            p99 = percentile(99),
+            max = percentile(100),
+        );
+        app_exit.write(b::AppExit::Success);
+    }
+}
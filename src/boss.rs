@@ -0,0 +1,352 @@
+use std::f32::consts::PI;
+
+use avian2d::prelude as p;
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::math::{Vec2, Vec3Swizzles as _, vec2};
+use bevy::prelude as b;
+use rand::RngExt as _;
+use rand_distr::Distribution as _;
+
+use crate::bullets_and_targets::{
+    Attackable, BulletStyle, ColliderShape, Pattern, Targeting, spawn_debris,
+};
+use crate::enemy::Threat;
+use crate::game_speed::{GameSpeed, SpeedEffect};
+use crate::prefab::Prefabs;
+use crate::quantity::{Adjustment, Fervor, Quantities, QuantityDelta, QuantityKind};
+use crate::rendering::CameraZoom;
+use crate::score::ScoreValue;
+use crate::settings::Settings;
+use crate::sound::emitted_sound_bundle;
+use crate::{Gun, PLAYFIELD_LAYERS, PLAYFIELD_RECT, SpriteId, Team, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// A large enemy which appears once per run when Fervor is high, and must be destroyed before
+/// the run can be won.
+///
+/// It goes through the [`PHASES`] in order as its health drops, each with its own attack pattern
+/// and movement.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct Boss {
+    /// Index into [`PHASES`] of the current phase.
+    phase: usize,
+    /// Direction of horizontal movement; 1 is right and -1 is left.
+    sway_direction: f32,
+}
+
+/// The [`Boss`] has been destroyed and is exploding, which it does at length before it is
+/// despawned; see [`boss_death_system`].
+#[derive(Debug, Default, b::Component)]
+pub(crate) struct BossDeath {
+    /// Game time since the boss’s health reached zero.
+    elapsed: f32,
+    /// Game time until the next burst of debris.
+    next_burst: f32,
+}
+
+/// Progress of the boss fight in the current run.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, b::Resource)]
+pub(crate) enum BossEncounter {
+    #[default]
+    NotYet,
+    InProgress,
+    Defeated,
+}
+
+/// One stage of a boss fight.
+struct BossPhase {
+    /// The phase begins when the boss’s health falls to this fraction of [`BOSS_HEALTH`].
+    health_fraction: f32,
+    pattern: Pattern,
+    cooldown: f32,
+    /// Horizontal speed while on station.
+    sway_speed: f32,
+}
+
+/// Fervor at which the boss appears. Less than [`Fervor::WIN_THRESHOLD`], so that the fight
+/// happens before the run can be won.
+const BOSS_FERVOR_THRESHOLD: f32 = 0.85;
+
+const BOSS_HEALTH: u8 = 240;
+const BOSS_SIZE: f32 = 72.0;
+const BOSS_COLLIDER_RADIUS: f32 = 26.0;
+const BOSS_TINT: b::Color = b::Color::srgb(1.0, 0.6, 0.9);
+/// Height at which the boss stops descending and starts swaying.
+const BOSS_STATION_Y: f32 = PLAYFIELD_RECT.max.y - 60.0;
+const BOSS_ENTRY_SPEED: f32 = 40.0;
+/// Counts the boss as this many ordinary enemies, so that the spawner mostly holds off
+/// during the fight.
+const BOSS_THREAT: f32 = 8.0;
+/// Points for destroying the boss, before multipliers.
+const BOSS_SCORE: u32 = 5000;
+/// Temporary fervor granted for destroying the boss.
+const BOSS_DEFEAT_FERVOR: f32 = 0.3;
+
+/// Game time for which a destroyed boss explodes before it is despawned.
+const BOSS_DEATH_TIME: f32 = 1.6;
+/// Game time between bursts of debris while the boss explodes.
+const BOSS_DEATH_BURST_INTERVAL: f32 = 0.15;
+/// Fraction of the playfield by which the camera zooms in on the exploding boss.
+const BOSS_KILL_CAM_ZOOM: f32 = 0.25;
+
+const BOSS_BULLET: BulletStyle = BulletStyle {
+    sprite: SpriteId::EnemyBullet,
+    shape: ColliderShape::Ellipse,
+    stretch: true,
+    muzzle_flash: Some(SpriteId::MuzzleFlash),
+};
+
+const PHASES: [BossPhase; 3] = [
+    // A slowly turning spiral of slow bullets in every direction.
+    BossPhase {
+        health_fraction: 1.0,
+        pattern: Pattern {
+            uses_coherence: false,
+            spread_count: 0,
+            spread_angle: 0.0,
+            coherence_narrowing: 0.0,
+            speed: 120.0,
+            coherence_speed_boost: 0.0,
+            damage: 1,
+            coherence_damage_bonus: 0.0,
+            bullet: BOSS_BULLET,
+            targeting: Targeting::Spiral {
+                angular_velocity: 0.6,
+            },
+            ring_count: 5,
+            inflicts: None,
+        },
+        cooldown: 0.35,
+        sway_speed: 30.0,
+    },
+    // Aimed bursts.
+    BossPhase {
+        health_fraction: 0.66,
+        pattern: Pattern {
+            uses_coherence: false,
+            spread_count: 1,
+            spread_angle: 0.12,
+            coherence_narrowing: 0.0,
+            speed: 200.0,
+            coherence_speed_boost: 0.0,
+            damage: 1,
+            coherence_damage_bonus: 0.0,
+            bullet: BOSS_BULLET,
+            targeting: Targeting::Player,
+            ring_count: 1,
+            inflicts: None,
+        },
+        cooldown: 0.9,
+        sway_speed: 60.0,
+    },
+    // Leading fans, fast movement.
+    BossPhase {
+        health_fraction: 0.33,
+        pattern: Pattern {
+            uses_coherence: false,
+            spread_count: 2,
+            spread_angle: 0.2,
+            coherence_narrowing: 0.0,
+            speed: 230.0,
+            coherence_speed_boost: 0.0,
+            damage: 1,
+            coherence_damage_bonus: 0.0,
+            bullet: BOSS_BULLET,
+            targeting: Targeting::LeadPlayer,
+            ring_count: 1,
+            inflicts: None,
+        },
+        cooldown: 0.7,
+        sway_speed: 100.0,
+    },
+];
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn reset_boss_encounter(mut encounter: b::ResMut<BossEncounter>) {
+    *encounter = BossEncounter::NotYet;
+}
+
+/// Spawns the boss once Fervor is high enough, if it has not yet appeared this run.
+pub(crate) fn spawn_boss_system(
+    mut commands: b::Commands,
+    mut encounter: b::ResMut<BossEncounter>,
+    quantities: Quantities,
+    prefabs: Prefabs,
+) -> b::Result {
+    if *encounter != BossEncounter::NotYet
+        || quantities.get::<Fervor>()?.base() < BOSS_FERVOR_THRESHOLD
+    {
+        return Ok(());
+    }
+    *encounter = BossEncounter::InProgress;
+
+    let enemy = prefabs.basic_enemy()?;
+    let phase = &PHASES[0];
+    commands.spawn((
+        Boss {
+            phase: 0,
+            sway_direction: 1.0,
+        },
+        Team::Enemy,
+        Attackable {
+            health: BOSS_HEALTH,
+            max_health: BOSS_HEALTH,
+            hurt_animation_cooldown: 0.0,
+            hurt_animation_damage: 0,
+            destruction_particle: enemy.destruction_particle.clone(),
+            hurt_sound: enemy.hurt_sound.clone(),
+            // much bigger than the enemy whose sound it borrows
+            hurt_pitch: 0.55,
+            last_hit_by: None,
+        },
+        // No `Pickup::Damage` like small enemies, since touching one of those destroys it.
+        Threat(BOSS_THREAT),
+        ScoreValue(BOSS_SCORE),
+        Gun {
+            cooldown: phase.cooldown,
+            base_cooldown: phase.cooldown,
+            trigger: false,
+            pattern: phase.pattern.clone(),
+            aim: PI,
+            shoot_sound: (enemy.shoot_sound.clone(), enemy.shoot_volume),
+        },
+        b::Sprite {
+            custom_size: Some(Vec2::splat(BOSS_SIZE)),
+            color: BOSS_TINT,
+            ..b::Sprite::from_image(enemy.sprite.clone())
+        },
+        b::Transform::from_xyz(
+            PLAYFIELD_RECT.center().x,
+            PLAYFIELD_RECT.max.y + BOSS_SIZE,
+            Zees::Enemy.z(),
+        ),
+        PLAYFIELD_LAYERS,
+        p::RigidBody::Kinematic,
+        p::LinearVelocity(Vec2::ZERO),
+        p::Collider::circle(BOSS_COLLIDER_RADIUS),
+    ));
+    Ok(())
+}
+
+/// Moves the boss onto its station and then from side to side, firing while on screen.
+pub(crate) fn boss_ai_system(
+    query: b::Query<(&mut Boss, &b::Transform, &mut p::LinearVelocity, &mut Gun)>,
+) {
+    for (mut boss, transform, mut velocity, mut gun) in query {
+        let position = transform.translation.xy();
+        if position.y > BOSS_STATION_Y {
+            velocity.0 = vec2(0.0, -BOSS_ENTRY_SPEED);
+            continue;
+        }
+
+        let margin = BOSS_SIZE / 2.0;
+        if position.x > PLAYFIELD_RECT.max.x - margin {
+            boss.sway_direction = -1.0;
+        } else if position.x < PLAYFIELD_RECT.min.x + margin {
+            boss.sway_direction = 1.0;
+        }
+        velocity.0 = vec2(boss.sway_direction * PHASES[boss.phase].sway_speed, 0.0);
+        gun.trigger = true;
+    }
+}
+
+/// Advances bosses to the phase matching their health, and rewards destroying them.
+pub(crate) fn boss_phase_system(
+    query: b::Query<(&mut Boss, &Attackable, &mut Gun)>,
+    mut encounter: b::ResMut<BossEncounter>,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
+) {
+    for (mut boss, attackable, mut gun) in query {
+        if attackable.health == 0 {
+            if *encounter == BossEncounter::InProgress {
+                *encounter = BossEncounter::Defeated;
+                quantity_deltas.write(QuantityDelta::new(
+                    QuantityKind::Fervor,
+                    Adjustment::TemporaryStacking,
+                    BOSS_DEFEAT_FERVOR,
+                ));
+            }
+            continue;
+        }
+
+        let health_fraction = f32::from(attackable.health) / f32::from(BOSS_HEALTH);
+        let phase = PHASES
+            .iter()
+            .rposition(|phase| health_fraction <= phase.health_fraction)
+            .unwrap_or(0);
+        if phase > boss.phase {
+            boss.phase = phase;
+            let phase = &PHASES[phase];
+            gun.pattern = phase.pattern.clone();
+            gun.base_cooldown = phase.cooldown;
+            gun.cooldown = gun.cooldown.min(phase.cooldown);
+        }
+    }
+}
+
+/// Plays out a destroyed boss’s [`BossDeath`]: the game slows down and the camera closes in while
+/// bursts of debris go off across the hull, and then the boss is despawned in a final blast.
+pub(crate) fn boss_death_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    query: b::Query<(
+        b::Entity,
+        &mut BossDeath,
+        &b::Transform,
+        &mut b::Sprite,
+        &mut p::LinearVelocity,
+    )>,
+    prefabs: Prefabs,
+    settings: b::Res<Settings>,
+    mut game_speed: b::ResMut<GameSpeed>,
+    mut zoom: b::ResMut<CameraZoom>,
+) -> b::Result {
+    let rng = &mut rand::rng();
+    let enemy = prefabs.basic_enemy()?;
+
+    for (entity, mut death, transform, mut sprite, mut velocity) in query {
+        if death.elapsed == 0.0 {
+            game_speed.add_effect(SpeedEffect::KILL_CAM);
+        }
+        death.elapsed += time.delta_secs();
+        death.next_burst -= time.delta_secs();
+        velocity.0 = Vec2::ZERO;
+
+        let position = transform.translation.xy();
+        zoom.kill_cam(position, BOSS_KILL_CAM_ZOOM);
+
+        if death.elapsed >= BOSS_DEATH_TIME {
+            if let Some(particle) = &enemy.destruction_particle {
+                spawn_debris(&mut commands, rng, particle, position, Vec2::ZERO, 120);
+            }
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        // Flicker between the boss’s own tint and white-hot.
+        let hot = ((death.elapsed / BOSS_DEATH_BURST_INTERVAL) as u32).is_multiple_of(2);
+        sprite.color = if hot { b::Color::WHITE } else { BOSS_TINT };
+
+        if death.next_burst <= 0.0 {
+            death.next_burst += BOSS_DEATH_BURST_INTERVAL;
+            let burst_position =
+                position + Vec2::from(rand_distr::UnitDisc.sample(rng)) * BOSS_COLLIDER_RADIUS;
+            if let Some(particle) = &enemy.destruction_particle
+                && !settings.low_spec
+            {
+                spawn_debris(&mut commands, rng, particle, burst_position, Vec2::ZERO, 12);
+            }
+            commands.spawn(emitted_sound_bundle(
+                enemy.hurt_sound.clone(),
+                bevy::audio::Volume::Decibels(-6.0),
+                rng.random_range(0.35..=0.5),
+                burst_position.extend(transform.translation.z),
+                entity,
+            ));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::Mutex;
+
+use bevy::log::tracing::field::{Field, Visit};
+use bevy::log::tracing::{Event, Subscriber};
+use bevy::log::tracing_subscriber::Layer;
+use bevy::log::tracing_subscriber::layer::Context;
+use bevy::math::{vec2, vec3};
+use bevy::prelude as b;
+use bevy::reflect::serde::TypedReflectSerializer;
+use bevy::render::view::screenshot::{Screenshot, save_to_disk};
+use bevy_enhanced_input::prelude as bei;
+
+use crate::bullets_and_targets::{Attackable, Bullet, Part};
+use crate::game_rng::{GameRng, format_seed};
+use crate::persistence::{self, DataDirectory};
+use crate::pickup::Pickup;
+use crate::quantity::Quantities;
+use crate::run_stats::RunStats;
+use crate::score::Score;
+use crate::settings::Settings;
+use crate::{
+    Coherence, Fervor, Fever, GameAssets, GameState, PLAYFIELD_RECT, Team, UI_LAYERS, Zees,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Lets playtesters save a bug report by pressing a key (bound to [`SaveBugReport`]).
+///
+/// Each report is a directory, in the `bug-reports` directory of the
+/// [data directory](DataDirectory), containing:
+///
+/// * `screenshot.png` of the window,
+/// * `report.json` with the seed, settings, quantities, run statistics, and entity counts,
+/// * `log.txt` with the most recent log messages.
+pub(crate) struct BugReportPlugin;
+
+impl b::Plugin for BugReportPlugin {
+    fn build(&self, app: &mut b::App) {
+        app.add_observer(save_bug_report_observer)
+            .add_systems(b::Last, update_bug_report_toast_system);
+    }
+}
+
+/// Saves a bug report; see [`BugReportPlugin`].
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+pub(crate) struct SaveBugReport;
+
+/// Log messages recorded by [`RecentLogLayer`], oldest first.
+///
+/// This has to be a static because the logger is set up before the world exists.
+static RECENT_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Number of log messages kept in [`RECENT_LOG`].
+const RECENT_LOG_LENGTH: usize = 200;
+
+/// Text telling the player where the latest bug report was saved.
+#[derive(Debug, b::Component)]
+struct BugReportToast {
+    /// Real time remaining before the toast disappears.
+    remaining: f32,
+}
+
+/// Real time for which a [`BugReportToast`] stays on screen.
+const TOAST_DURATION: f32 = 4.0;
+
+#[derive(Debug, serde::Serialize)]
+struct BugReport {
+    game_version: &'static str,
+    state: String,
+    seed: String,
+    score: u64,
+    /// Reflected [`Quantity`](crate::quantity::Quantity) components, by name.
+    quantities: BTreeMap<&'static str, serde_json::Value>,
+    /// Reflected [`RunStats`].
+    run_stats: serde_json::Value,
+    entity_counts: BTreeMap<&'static str, usize>,
+    settings: Settings,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// For [`LogPlugin::custom_layer`](bevy::log::LogPlugin::custom_layer):
+/// records log messages in [`RECENT_LOG`] so that they can be included in bug reports.
+pub(crate) fn recent_log_layer(_app: &mut b::App) -> Option<bevy::log::BoxedLayer> {
+    Some(Box::new(RecentLogLayer))
+}
+
+struct RecentLogLayer;
+
+impl<S: Subscriber> Layer<S> for RecentLogLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!("{} {}:", metadata.level(), metadata.target());
+        event.record(&mut LogLineVisitor(&mut line));
+
+        if let Ok(mut recent) = RECENT_LOG.lock() {
+            if recent.len() >= RECENT_LOG_LENGTH {
+                recent.pop_front();
+            }
+            recent.push_back(line);
+        }
+    }
+}
+
+/// Appends the fields of a log event to a line of text.
+struct LogLineVisitor<'a>(&'a mut String);
+
+impl Visit for LogLineVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            _ = write!(self.0, " {value:?}");
+        } else {
+            _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[allow(
+    clippy::too_many_arguments,
+    clippy::type_complexity,
+    reason = "a report gathers state from every part of the game"
+)]
+fn save_bug_report_observer(
+    _event: b::On<bei::Start<SaveBugReport>>,
+    mut commands: b::Commands,
+    assets: Option<b::Res<GameAssets>>,
+    type_registry: b::Res<b::AppTypeRegistry>,
+    state: b::Res<b::State<GameState>>,
+    rng: b::Res<GameRng>,
+    score: b::Res<Score>,
+    settings: b::Res<Settings>,
+    run_stats: b::Res<RunStats>,
+    quantities: Quantities,
+    entities: b::Query<()>,
+    teams: b::Query<(&Team, b::Has<Attackable>, b::Has<Bullet>, b::Has<Part>)>,
+    pickups: b::Query<(), b::With<Pickup>>,
+    toasts: b::Query<b::Entity, b::With<BugReportToast>>,
+    data_directory: b::Res<DataDirectory>,
+) -> b::Result {
+    let Some(data_directory) = &data_directory.0 else {
+        b::warn!("bug reports cannot be saved on this platform");
+        return Ok(());
+    };
+    let directory = persistence::timestamped_path(&data_directory.join("bug-reports"), "", "");
+
+    let registry = type_registry.read();
+    let reflect = |value: &dyn b::PartialReflect| {
+        serde_json::to_value(TypedReflectSerializer::new(value, &registry))
+    };
+    let mut entity_counts = BTreeMap::from([
+        ("entities", entities.iter().count()),
+        ("pickups", pickups.iter().count()),
+    ]);
+    for (&team, attackable, bullet, part) in teams {
+        let key = match (team, attackable, bullet, part) {
+            (_, _, _, true) => "enemy_parts",
+            (Team::Enemy, true, _, _) => "enemies",
+            (Team::Enemy, _, true, _) => "enemy_bullets",
+            (Team::Player, _, true, _) => "player_bullets",
+            _ => continue,
+        };
+        *entity_counts.entry(key).or_default() += 1;
+    }
+    let report = BugReport {
+        game_version: env!("CARGO_PKG_VERSION"),
+        state: format!("{:?}", state.get()),
+        seed: format_seed(rng.seed()),
+        score: score.0,
+        quantities: BTreeMap::from([
+            ("coherence", reflect(quantities.get::<Coherence>()?)?),
+            ("fever", reflect(quantities.get::<Fever>()?)?),
+            ("fervor", reflect(quantities.get::<Fervor>()?)?),
+        ]),
+        run_stats: reflect(&*run_stats)?,
+        entity_counts,
+        settings: settings.clone(),
+    };
+    let report = serde_json::to_string_pretty(&report)?;
+
+    let log = match RECENT_LOG.lock() {
+        Ok(recent) => recent.iter().fold(String::new(), |mut log, line| {
+            log.push_str(line);
+            log.push('\n');
+            log
+        }),
+        Err(_) => String::new(),
+    };
+
+    // A full disk or unwritable data directory should not stop the game, so report the error
+    // the same way as success.
+    let result = std::fs::create_dir_all(&directory)
+        .and_then(|()| std::fs::write(directory.join("report.json"), report))
+        .and_then(|()| std::fs::write(directory.join("log.txt"), log));
+    let message = match result {
+        Ok(()) => {
+            b::info!("saved bug report to {}", directory.display());
+            // The screenshot is saved once it has been rendered, a frame or so from now.
+            commands
+                .spawn(Screenshot::primary_window())
+                .observe(save_to_disk(directory.join("screenshot.png")));
+            format!("Bug report saved to\n{}", directory.display())
+        }
+        Err(error) => {
+            b::error!(
+                "failed to save bug report to {}: {error}",
+                directory.display()
+            );
+            format!("Bug report could not be saved:\n{error}")
+        }
+    };
+
+    if let Some(assets) = assets {
+        for toast in toasts {
+            commands.entity(toast).despawn();
+        }
+        commands.spawn((
+            BugReportToast {
+                remaining: TOAST_DURATION,
+            },
+            b::Text2d::new(message),
+            assets.small_mono_font(),
+            b::TextLayout::new_with_justify(b::Justify::Left),
+            b::TextShadow {
+                offset: vec2(1.0, 1.0),
+                color: b::Color::BLACK,
+            },
+            bevy::text::TextBounds::new_horizontal(PLAYFIELD_RECT.width() - 8.0),
+            bevy::sprite::Anchor::TOP_LEFT,
+            b::Transform::from_translation(vec3(
+                PLAYFIELD_RECT.min.x + 4.0,
+                PLAYFIELD_RECT.max.y - 16.0,
+                Zees::UiFront2.z(),
+            )),
+            UI_LAYERS,
+        ));
+    }
+
+    Ok(())
+}
+
+fn update_bug_report_toast_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time<b::Real>>,
+    toasts: b::Query<(b::Entity, &mut BugReportToast)>,
+) {
+    for (entity, mut toast) in toasts {
+        toast.remaining -= time.delta_secs();
+        if toast.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
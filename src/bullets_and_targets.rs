@@ -1,55 +1,151 @@
-use std::f32::consts::PI;
+use std::collections::{HashSet, VecDeque};
+use std::f32::consts::{PI, TAU};
 
 use avian2d::prelude as p;
+use bevy::color::Alpha as _;
+use bevy::ecs::change_detection::DetectChangesMut as _;
 use bevy::ecs::entity::EntityHashSet;
+use bevy::ecs::query::QueryFilter;
+use bevy::ecs::reflect::ReflectComponent;
 use bevy::math::{Vec2, Vec3Swizzles as _, vec2, vec3};
 use bevy::prelude as b;
 use bevy_enhanced_input::prelude as bei;
 use rand::RngExt;
 use rand_distr::Distribution as _;
 
-use crate::pickup::Pickup;
-use crate::quantity::{QCoherenceMut, QFervorMut, QFeverMut, ReadQuantity, fervor_is_active};
+use crate::boss::{Boss, BossDeath};
+use crate::difficulty::{AdaptiveDifficulty, Difficulty};
+use crate::game_rng::GameRng;
+use crate::lives::{Invulnerable, Lives};
+use crate::pickup::{Carried, Drops, WeaponUpgrade, choose_drop};
+use crate::prefab::Prefabs;
+use crate::quantity::{Adjustment, Quantities, QuantityDelta, QuantityKind, fervor_is_active};
+use crate::run_stats::RunEvent;
+use crate::score::{Combo, Score, ScoreValue, kill_points, points_popup_bundle};
+use crate::settings::Settings;
+use crate::sound::{FollowEmitter, emitted_sound_bundle};
+use crate::status_effects::{StatusEffects, StatusKind};
+use crate::wrap::WrapGhost;
 use crate::{
-    Coherence, Fervor, Fever, Lifetime, PLAYFIELD_LAYERS, PLAYFIELD_RECT, Player, Quantity, Shoot,
-    Team, Zees,
+    Bomb, Coherence, DespawnNextTick, Fervor, Fever, GameAssets, GameState, Lifetime,
+    PLAYFIELD_LAYERS, PLAYFIELD_RECT, Player, PlayerVelocity, Shoot, SoundId, SpriteId,
+    SwitchWeapon, Team, Zees,
 };
 
 // -------------------------------------------------------------------------------------------------
 
 /// Entity is a bullet and does bullet things such as hurting enemies.
-#[derive(Debug, b::Component)]
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 #[require(p::CollidingEntities)]
 pub(crate) struct Bullet {
-    damage: u8,
+    pub damage: u8,
+
+    /// Distance from the bullet’s center to the farthest point of its shape,
+    /// used by [`cull_offscreen_bullets_system`].
+    pub reach: f32,
+
+    /// Status effect inflicted on whatever the bullet hurts.
+    pub inflicts: Option<StatusKind>,
 }
 
-/// Something that dies if shot.
+/// Sprite which moves or looks like a projectile but does not affect gameplay, such as debris,
+/// a muzzle flash, or a [`TrailImage`](crate::rendering::TrailImage).
+///
+/// These are faded out early, oldest first, when there are too many projectiles on screen;
+/// see [`cap_rendered_projectiles_system`].
 #[derive(Debug, b::Component)]
+pub(crate) struct CosmeticProjectile;
+
+/// Marks a former [`CosmeticProjectile`] which is fading out early; see
+/// [`cap_rendered_projectiles_system`].
+#[derive(Debug, b::Component)]
+pub(crate) struct EarlyFade;
+
+/// Counts of live projectiles, kept up to date by observers as they are spawned and despawned,
+/// so that systems which limit projectiles do not each need to count them.
+#[derive(Debug, Default, b::Resource)]
+pub(crate) struct ProjectileLedger {
+    /// Number of live [`Bullet`]s.
+    bullets: usize,
+    /// Number of live [`CosmeticProjectile`]s.
+    cosmetic: usize,
+    /// [`CosmeticProjectile`]s in the order they were spawned, oldest first.
+    /// May also contain entities which have since been despawned or faded.
+    cosmetic_order: VecDeque<b::Entity>,
+}
+
+/// Marks an enemy bullet which is within [`GRAZE_RADIUS`] of the player, and will count as a
+/// near miss for [`fever_pressure_system`] if it leaves without hitting.
+#[derive(Debug, b::Component)]
+pub(crate) struct Grazing;
+
+/// Marks an enemy bullet which has already narrowly missed the player,
+/// so that [`fever_pressure_system`] counts it only once.
+#[derive(Debug, b::Component)]
+pub(crate) struct Grazed;
+
+/// The entity’s [`p::Collider`] is this shape fitted to this image.
+/// Used to update the collider when the image is hot-reloaded.
+#[derive(Debug, b::Component)]
+pub(crate) struct ColliderFromImage(pub b::Handle<b::Image>, pub ColliderShape);
+
+/// Something that dies if shot.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) struct Attackable {
     /// Reduced by bullets, and when zero, this is despawned.
     pub health: u8,
 
+    /// [`Self::health`] when undamaged.
+    pub max_health: u8,
+
     /// Set to 1.0 when damage occurs, and decays to 0.0.
     pub hurt_animation_cooldown: f32,
+    /// Largest damage taken during the current hurt animation, which determines its color.
+    pub hurt_animation_damage: u8,
 
     pub hurt_sound: b::Handle<b::AudioSource>,
 
+    /// Playback speed, and so pitch, of [`Self::hurt_sound`] when undamaged.
+    /// It rises as health falls; see [`hurt_sound_speed()`].
+    pub hurt_pitch: f32,
+
     pub destruction_particle: Option<b::Handle<b::Image>>,
 
     /// What team last hit it, to attribute the kill.
     pub last_hit_by: Option<Team>,
 }
 
+/// A separately destructible piece of a composite enemy, such as a turret or wing.
+/// It is a child of the enemy’s core entity, and is [`Attackable`] on its own.
+///
+/// While a core has any critical parts, it cannot be hurt directly; it is destroyed when the
+/// last critical part is.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct Part {
+    pub critical: bool,
+}
+
 /// This entity has a gun! It might be the player ship or an enemy ship.
-#[derive(Debug, b::Component)]
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) struct Gun {
     /// Gun will shoot next time [`fire_gun_system`] runs, if possible.
     pub trigger: bool,
 
     pub pattern: Pattern,
 
+    /// Direction the gun fires in, as an angle in radians counterclockwise from straight up.
+    ///
+    /// This is independent of the rotation of the entity’s [`b::Transform`].
+    pub aim: f32,
+
     /// If positive, gun may not shoot yet.
+    ///
+    /// May be slightly negative on the tick the gun becomes ready, so that the time it was
+    /// ready *within* the tick is not lost and the rate of fire does not depend on the tick rate.
     pub cooldown: f32,
 
     /// Value `cooldown` is reset to after firing.
@@ -58,17 +154,264 @@ pub(crate) struct Gun {
     pub shoot_sound: (b::Handle<b::AudioSource>, bevy::audio::Volume),
 }
 
-#[derive(Debug)]
-pub(crate) enum Pattern {
-    /// Fire a single, slow bullet.
-    Single,
-    /// Shotgun-to-laser depending on [`Coherence`].
+/// Time between shots of the [`Player`]’s [`Gun`] with [`PlayerWeapon::Coherent`],
+/// before [`WeaponUpgrades`].
+pub(crate) const PLAYER_GUN_COOLDOWN: f32 = 0.25;
+
+/// Time between shots of the [`Player`]’s [`Gun`] with [`PlayerWeapon::Homing`],
+/// before [`WeaponUpgrades`].
+const HOMING_GUN_COOLDOWN: f32 = 0.4;
+
+/// Time after switching weapons before the [`Player`]’s [`Gun`] can fire,
+/// so that switching cannot be used to skip a cooldown.
+const WEAPON_SWITCH_DELAY: f32 = 0.15;
+
+/// Which weapon the [`Player`]’s [`Gun`] currently fires, as chosen by [`SwitchWeapon`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) enum PlayerWeapon {
+    /// [`Pattern::COHERENT`].
+    #[default]
     Coherent,
+    /// [`Pattern::HOMING`].
+    Homing,
+}
+
+/// Weapon upgrades the [`Player`] has collected this run, which modify their [`Gun`].
+///
+/// Each is a level from 0 to [`WeaponUpgrades::MAX_LEVEL`].
+#[derive(Clone, Debug, Default, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct WeaponUpgrades {
+    /// Each level adds a bullet on each side of the spread.
+    pub wide: u8,
+    /// Each level shortens the time between shots by [`RAPID_COOLDOWN_FACTOR`].
+    pub rapid: u8,
+    /// Each level adds 1 damage to each bullet.
+    pub heavy: u8,
 }
 
+/// Factor by which each level of [`WeaponUpgrades::rapid`] multiplies the time between shots.
+const RAPID_COOLDOWN_FACTOR: f32 = 0.85;
+
+/// Parameters of how a [`Gun`] fires its bullets.
+///
+/// Quantities named `coherence_*` are scaled by the [`Coherence`] of the gun.
+#[derive(Clone, Debug, b::Reflect, serde::Deserialize)]
+pub(crate) struct Pattern {
+    /// Whether the gun’s shots depend on [`Coherence`] at all.
+    /// If false, the gun fires as if coherence were zero.
+    pub uses_coherence: bool,
+
+    /// Number of bullets fired on each side of the center bullet.
+    pub spread_count: u8,
+
+    /// Angle in radians between adjacent bullets, at zero coherence.
+    pub spread_angle: f32,
+
+    /// Fraction by which full coherence narrows [`Self::spread_angle`].
+    pub coherence_narrowing: f32,
+
+    /// Speed of bullets, at zero coherence.
+    /// Each bullet is randomly slower by up to 25%.
+    pub speed: f32,
+
+    /// Speed added to [`Self::speed`], multiplied by the square of coherence.
+    pub coherence_speed_boost: f32,
+
+    /// Damage dealt by each bullet, at zero coherence.
+    pub damage: u8,
+
+    /// Damage added to [`Self::damage`], multiplied by coherence and rounded down.
+    pub coherence_damage_bonus: f32,
+
+    /// Appearance and shape of the bullets.
+    pub bullet: BulletStyle,
+
+    /// Which way the center bullet is fired.
+    pub targeting: Targeting,
+
+    /// Number of copies of the spread fired at once, evenly spaced around a full circle
+    /// starting from the [`Self::targeting`] direction. 1 (or 0) fires just the one spread.
+    pub ring_count: u8,
+
+    /// Status effect inflicted by each bullet on whatever it hurts.
+    pub inflicts: Option<StatusKind>,
+}
+
+/// Which way a [`Pattern`] is fired.
+#[derive(Clone, Copy, Debug, PartialEq, b::Reflect, serde::Deserialize)]
+pub(crate) enum Targeting {
+    /// In the direction of [`Gun::aim`].
+    Fixed,
+    /// At the [`Player`]’s current position.
+    Player,
+    /// At where the [`Player`] will be when the bullet arrives, if they keep moving the same way.
+    LeadPlayer,
+    /// In the direction of [`Gun::aim`], which is then turned counterclockwise by this many
+    /// radians per second of [`Gun::base_cooldown`], so that successive shots form a spiral.
+    Spiral { angular_velocity: f32 },
+    /// In the direction of [`Gun::aim`], after which each bullet turns toward the nearest
+    /// opponent by up to this many radians per second; see [`Homing`].
+    Homing { turn_rate: f32 },
+}
+
+/// Bullet which turns toward the nearest [`Attackable`] of the other [`Team`];
+/// fired by [`Targeting::Homing`].
+#[derive(Debug, b::Component)]
+pub(crate) struct Homing {
+    /// Maximum turn, in radians per second.
+    turn_rate: f32,
+}
+
+/// Appearance and shape of the bullets fired by a [`Pattern`].
+#[derive(Clone, Debug, b::Reflect, serde::Deserialize)]
+pub(crate) struct BulletStyle {
+    pub sprite: SpriteId,
+
+    /// Shape of the collider, which is fitted to the bounds of the sprite.
+    pub shape: ColliderShape,
+
+    /// Whether the bullet is stretched along its direction of travel, enough that it overlaps
+    /// itself from tick to tick. This is good for both reliable collisions and good visuals of
+    /// fast bullets.
+    pub stretch: bool,
+
+    /// Sprite briefly shown where each bullet is fired, transformed like the bullet.
+    pub muzzle_flash: Option<SpriteId>,
+}
+
+/// A shape which can be fitted to a rectangular size, such as the size of a sprite.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, b::Reflect, serde::Deserialize)]
+pub(crate) enum ColliderShape {
+    Ellipse,
+    Rectangle,
+}
+
+impl ColliderShape {
+    pub fn fit(self, size: Vec2) -> p::Collider {
+        match self {
+            ColliderShape::Ellipse => p::Collider::ellipse(size.x / 2., size.y / 2.),
+            ColliderShape::Rectangle => p::Collider::rectangle(size.x, size.y),
+        }
+    }
+}
+
+impl Pattern {
+    /// Shotgun-to-laser depending on [`Coherence`].
+    pub const COHERENT: Self = Self {
+        uses_coherence: true,
+        spread_count: 3,
+        spread_angle: 5f32.to_radians(),
+        coherence_narrowing: 0.9,
+        speed: 400.0,
+        coherence_speed_boost: 20000.0,
+        damage: 1,
+        coherence_damage_bonus: 2.9,
+        bullet: BulletStyle {
+            sprite: SpriteId::PlayerBullet,
+            shape: ColliderShape::Ellipse,
+            stretch: true,
+            muzzle_flash: Some(SpriteId::MuzzleFlash),
+        },
+        targeting: Targeting::Fixed,
+        ring_count: 1,
+        // but see `SURGE_INFLICTS`
+        inflicts: None,
+    };
+
+    /// Slower shots which seek out enemies, for when aiming is hard.
+    pub const HOMING: Self = Self {
+        uses_coherence: true,
+        spread_count: 1,
+        spread_angle: 25f32.to_radians(),
+        coherence_narrowing: 0.5,
+        speed: 220.0,
+        // not much, or the bullets could not turn in time
+        coherence_speed_boost: 120.0,
+        damage: 1,
+        coherence_damage_bonus: 1.9,
+        bullet: BulletStyle {
+            sprite: SpriteId::PlayerBullet,
+            shape: ColliderShape::Ellipse,
+            // a stretched bullet would look wrong when turning
+            stretch: false,
+            muzzle_flash: Some(SpriteId::MuzzleFlash),
+        },
+        targeting: Targeting::Homing { turn_rate: 5.0 },
+        ring_count: 1,
+        inflicts: None,
+    };
+}
+
+impl PlayerWeapon {
+    /// The weapon which [`SwitchWeapon`] switches to from this one.
+    pub fn next(self) -> Self {
+        match self {
+            PlayerWeapon::Coherent => PlayerWeapon::Homing,
+            PlayerWeapon::Homing => PlayerWeapon::Coherent,
+        }
+    }
+
+    fn base_pattern(self) -> Pattern {
+        match self {
+            PlayerWeapon::Coherent => Pattern::COHERENT,
+            PlayerWeapon::Homing => Pattern::HOMING,
+        }
+    }
+
+    fn base_cooldown(self) -> f32 {
+        match self {
+            PlayerWeapon::Coherent => PLAYER_GUN_COOLDOWN,
+            PlayerWeapon::Homing => HOMING_GUN_COOLDOWN,
+        }
+    }
+}
+
+impl WeaponUpgrades {
+    pub const MAX_LEVEL: u8 = 3;
+
+    /// The [`Player`]’s gun pattern for `weapon` with these upgrades.
+    pub fn pattern(&self, weapon: PlayerWeapon) -> Pattern {
+        let base = weapon.base_pattern();
+        Pattern {
+            spread_count: base.spread_count + self.wide,
+            damage: base.damage + self.heavy,
+            ..base
+        }
+    }
+
+    /// The [`Player`]’s gun cooldown for `weapon` with these upgrades.
+    pub fn cooldown(&self, weapon: PlayerWeapon) -> f32 {
+        weapon.base_cooldown() * RAPID_COOLDOWN_FACTOR.powi(i32::from(self.rapid))
+    }
+
+    /// Raises the level of `upgrade`, and returns whether it was not already at the maximum.
+    pub fn add(&mut self, upgrade: WeaponUpgrade) -> bool {
+        let level = match upgrade {
+            WeaponUpgrade::Wide => &mut self.wide,
+            WeaponUpgrade::Rapid => &mut self.rapid,
+            WeaponUpgrade::Heavy => &mut self.heavy,
+        };
+        if *level >= Self::MAX_LEVEL {
+            return false;
+        }
+        *level += 1;
+        true
+    }
+}
+
+/// Marks the sound of a [`Gun`] firing, so that [`fire_gun_system`] can count how many are playing.
+#[derive(Debug, b::Component)]
+pub(crate) struct ShotSound(Team);
+
 /// Event triggered whenever an [`Attackable`] takes damage, by the system making the health change.
 #[derive(Debug, b::Event)]
-pub(crate) struct Hurt(pub b::Entity);
+pub(crate) struct Hurt {
+    pub target: b::Entity,
+    /// Amount the target’s health was reduced by, before clamping to zero.
+    pub damage: u8,
+}
 
 // -------------------------------------------------------------------------------------------------
 
@@ -89,83 +432,288 @@ pub(crate) fn player_input_fire_gun(
     }
 }
 
+/// Fervor the player must have to use a bomb, and which using it spends.
+const BOMB_FERVOR_COST: f32 = 0.25;
+
+/// Damage dealt by a bomb to every enemy in the playfield.
+const BOMB_DAMAGE: u8 = 12;
+
+/// Time for which the playfield flashes when a bomb goes off.
+const BOMB_FLASH_DURATION: f32 = 0.12;
+
+/// Note that this is an input observer, not a system function.
+///
+/// If the player has at least [`BOMB_FERVOR_COST`] Fervor, spends it to clear all enemy bullets
+/// and deal [`BOMB_DAMAGE`] to every enemy in the playfield.
+#[allow(
+    clippy::too_many_arguments,
+    clippy::type_complexity,
+    reason = "a bomb affects the player, every enemy and bullet, and Fervor"
+)]
+pub(crate) fn player_input_bomb(
+    _bomb: b::On<bei::Start<Bomb>>,
+    mut commands: b::Commands,
+    state: b::Res<b::State<GameState>>,
+    settings: b::Res<Settings>,
+    quantities: Quantities,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
+    bullets: b::Query<(b::Entity, &Team), b::With<Bullet>>,
+    targets: b::Query<(
+        b::Entity,
+        &Team,
+        &mut Attackable,
+        &b::Transform,
+        Option<&b::ChildOf>,
+        Option<&b::Children>,
+    )>,
+    transforms: b::Query<&b::Transform>,
+    parts: b::Query<&Part>,
+) -> b::Result {
+    if *state.get() != GameState::Playing
+        || quantities.get::<Fervor>()?.effective_value() < BOMB_FERVOR_COST
+    {
+        return Ok(());
+    }
+    quantity_deltas.write(QuantityDelta::new(
+        QuantityKind::Fervor,
+        Adjustment::PermanentIncludingTemporary,
+        -BOMB_FERVOR_COST,
+    ));
+
+    for (bullet_entity, &team) in bullets {
+        if team == Team::Enemy {
+            commands.entity(bullet_entity).insert(DespawnNextTick);
+        }
+    }
+
+    let none_destroyed = EntityHashSet::new();
+    for (entity, &team, mut attackable, transform, parent, children) in targets {
+        if team != Team::Enemy
+            || attackable.health == 0
+            || !PLAYFIELD_RECT.contains(playfield_position(transform, parent, &transforms))
+            || children.is_some_and(|children| has_critical_part(children, &parts, &none_destroyed))
+        {
+            continue;
+        }
+        attackable.health = attackable.health.saturating_sub(BOMB_DAMAGE);
+        attackable.last_hit_by = Some(Team::Player);
+        commands.trigger(Hurt {
+            target: entity,
+            damage: BOMB_DAMAGE,
+        });
+    }
+
+    if !settings.reduced_motion {
+        commands.spawn((
+            Lifetime(BOMB_FLASH_DURATION),
+            b::Sprite {
+                color: b::Color::srgba(1.0, 0.95, 0.85, 0.5),
+                custom_size: Some(PLAYFIELD_RECT.size()),
+                ..b::default()
+            },
+            b::Transform::from_translation(PLAYFIELD_RECT.center().extend(Zees::AbovePlayer.z())),
+            PLAYFIELD_LAYERS,
+        ));
+    }
+    Ok(())
+}
+
+/// Note that this is an input observer, not a system function.
+///
+/// Switches the [`Player`]’s [`Gun`] to their next [`PlayerWeapon`].
+pub(crate) fn player_input_switch_weapon(
+    _switch: b::On<bei::Start<SwitchWeapon>>,
+    state: b::Res<b::State<GameState>>,
+    player: b::Query<(&mut Gun, &mut PlayerWeapon, &WeaponUpgrades), b::With<Player>>,
+) {
+    if *state.get() != GameState::Playing {
+        return;
+    }
+    for (mut gun, mut weapon, upgrades) in player {
+        *weapon = weapon.next();
+        gun.pattern = upgrades.pattern(*weapon);
+        gun.base_cooldown = upgrades.cooldown(*weapon);
+        gun.cooldown = gun.cooldown.max(WEAPON_SWITCH_DELAY);
+    }
+}
+
+/// Volume of the hurt sound of a 1-damage hit.
+const HURT_VOLUME_DB: f32 = -12.0;
+/// Increase in the volume of the hurt sound per point of damage above 1.
+const HURT_VOLUME_DB_PER_DAMAGE: f32 = 1.5;
+/// Damage above which hurt sounds get no louder.
+const HURT_VOLUME_MAX_DAMAGE: u8 = 5;
+/// Fraction by which the pitch of a hurt sound rises from full health to nearly none.
+const HURT_PITCH_RISE: f32 = 0.5;
+
+/// Playback speed of the hurt sound of an enemy whose armor stopped a bullet, which is high and
+/// short enough to sound like a clink rather than a hit.
+const ARMOR_CLINK_SPEED: f32 = 2.6;
+const ARMOR_CLINK_VOLUME_DB: f32 = -16.0;
+
+/// Status effect inflicted by the player’s bullets while a surge pickup holds
+/// [`Coherence`] at its maximum.
+const SURGE_INFLICTS: StatusKind = StatusKind::Burn;
+
+/// Maximum number of bullets which may exist at once when [`Settings::low_spec`] is set.
+const LOW_SPEC_MAX_BULLETS: usize = 300;
+
+/// Time over which a [`CosmeticProjectile`] fades out when cut short by
+/// [`cap_rendered_projectiles_system`].
+const EARLY_FADE_TIME: f32 = 0.1;
+
 /// Spawn bullets if [`Gun::trigger`] is true.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "firing depends on the gun, its owner, difficulty, and sound limits"
+)]
 pub(crate) fn fire_gun_system(
     mut commands: b::Commands,
-    gun_query: b::Query<(&b::Transform, &mut Gun, &Team, b::Has<Player>)>,
-    mut coherence_query: QCoherenceMut,
-    mut fever_query: QFeverMut,
-    assets: b::Res<crate::MyAssets>,
+    time: b::Res<b::Time>,
+    gun_query: b::Query<(b::Entity, &b::Transform, &mut Gun, &Team, b::Has<Player>)>,
+    quantities: Quantities,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
+    mut run_events: b::MessageWriter<RunEvent>,
+    assets: b::Res<crate::GameAssets>,
     images: b::Res<b::Assets<b::Image>>,
+    mut game_rng: b::ResMut<GameRng>,
+    shot_sounds: b::Query<&ShotSound>,
+    ledger: b::Res<ProjectileLedger>,
+    target_player: b::Query<(&b::Transform, &PlayerVelocity), b::With<Player>>,
+    settings: b::Res<Settings>,
+    difficulty: b::Res<Difficulty>,
+    adaptive: b::Res<AdaptiveDifficulty>,
 ) -> b::Result {
-    for (gun_transform, mut gun, &team, is_player) in gun_query {
-        if !gun.trigger || gun.cooldown != 0.0 {
+    // Bullets which may still be spawned, if limited.
+    let mut bullet_budget = settings
+        .low_spec
+        .then(|| LOW_SPEC_MAX_BULLETS.saturating_sub(ledger.bullets));
+
+    let mut enemy_shot_sounds = shot_sounds
+        .iter()
+        .filter(|&&ShotSound(team)| team == Team::Enemy)
+        .count();
+
+    for (gun_entity, gun_transform, mut gun, &team, is_player) in gun_query {
+        if !gun.trigger || gun.cooldown > 0.0 {
             // Gun is not commanded to fire or is not ready to fire
             continue;
         }
         gun.trigger = false;
 
-        let mut origin_of_bullets_transform: b::Transform = *gun_transform;
-        origin_of_bullets_transform.translation.z = Zees::Bullets.z();
+        let origin_of_bullets_transform = b::Transform::from_translation(
+            gun_transform.translation.xy().extend(Zees::Bullets.z()),
+        );
 
-        let (base_shooting_angle, unmodified_bullet_speed) = match team {
-            Team::Player => (0.0, 400.0),
-            Team::Enemy => (PI, 210.0),
+        let pattern = &gun.pattern;
+        let coherence = if pattern.uses_coherence {
+            quantities.get::<Coherence>()?.effective_value()
+        } else {
+            0.0
         };
 
-        // 1 + 2 * spread_count is the number of bullets
-        let (coherence, spread_count): (f32, i32) = match gun.pattern {
-            Pattern::Single => (0.0, 0),
-            Pattern::Coherent => (coherence_query.effective_value(), 3),
+        let difficulty_speed = if team == Team::Enemy {
+            difficulty.enemy_bullet_speed()
+        } else {
+            1.0
         };
+        let bullet_speed_with_boost =
+            (pattern.speed + coherence.powi(2) * pattern.coherence_speed_boost) * difficulty_speed;
 
-        let bullet_speed_with_boost = unmodified_bullet_speed + coherence.powi(2) * 20000.0;
-        let bullet_angle_step_rad = (1.0 - coherence * 0.9) * 5f32.to_radians();
-        // bullets scaled so that they overlap themselves from frame to frame,
-        // for both reliable collisions and for good visuals.
-        let bullet_scale = vec2(1.0, (bullet_speed_with_boost * 0.003).max(1.0));
+        let target = match pattern.targeting {
+            Targeting::Fixed | Targeting::Spiral { .. } | Targeting::Homing { .. } => None,
+            Targeting::Player | Targeting::LeadPlayer => target_player
+                .single()
+                .ok()
+                .map(|(transform, velocity)| (transform.translation.xy(), velocity.0)),
+        };
+        let base_shooting_angle = match (pattern.targeting, target) {
+            (Targeting::Player, Some((position, _))) => {
+                Vec2::Y.angle_to(position - gun_transform.translation.xy())
+            }
+            (Targeting::LeadPlayer, Some((position, velocity))) => {
+                Vec2::Y.angle_to(intercept_direction(
+                    gun_transform.translation.xy(),
+                    position,
+                    velocity,
+                    // average of the random speed variation below
+                    bullet_speed_with_boost * 0.875,
+                ))
+            }
+            _ => gun.aim,
+        };
+        let bullet_angle_step_rad =
+            (1.0 - coherence * pattern.coherence_narrowing) * pattern.spread_angle;
+        let damage = pattern.damage + (coherence * pattern.coherence_damage_bonus).floor() as u8;
+        let inflicts = if is_player && quantities.get::<Coherence>()?.is_maxed() {
+            Some(SURGE_INFLICTS)
+        } else {
+            pattern.inflicts
+        };
+        // (1 + 2 * spread_count) * ring_count is the number of bullets
+        let spread_count = i32::from(pattern.spread_count);
+        let ring_count = pattern.ring_count.max(1);
+        let ring_angle_step_rad = TAU / f32::from(ring_count);
+        let bullet_angles = (0..ring_count).flat_map(|ring_index| {
+            let center_angle_rad =
+                base_shooting_angle + f32::from(ring_index) * ring_angle_step_rad;
+            (-spread_count..=spread_count).map(move |bullet_angle_index| {
+                center_angle_rad + bullet_angle_index as f32 * bullet_angle_step_rad
+            })
+        });
 
+        let bullet_image = assets.sprite(pattern.bullet.sprite);
         let sprite_size = images
-            .get(&assets.player_bullet_sprite)
+            .get(&bullet_image)
             .ok_or_else(|| b::BevyError::from("asset not loaded"))?
             .size_f32();
+        let bullet_scale = if pattern.bullet.stretch {
+            // This must use the tick length so that it stays reliable if the tick rate is changed.
+            let travel_per_tick = bullet_speed_with_boost * time.delta_secs();
+            vec2(1.0, (travel_per_tick * 2.3 / sprite_size.y).max(1.0))
+        } else {
+            Vec2::ONE
+        };
         let bullet_box_size = sprite_size * bullet_scale;
 
-        for bullet_angle_index in -spread_count..=spread_count {
-            let bullet_angle_rad =
-                base_shooting_angle + bullet_angle_index as f32 * bullet_angle_step_rad;
-            let single_speed = rand::rng().random_range(0.75..=1.0) * bullet_speed_with_boost;
+        for bullet_angle_rad in bullet_angles {
+            let single_speed = game_rng.random_range(0.75..=1.0) * bullet_speed_with_boost;
             let bullet_transform = origin_of_bullets_transform
                 * b::Transform::from_rotation(b::Quat::from_rotation_z(bullet_angle_rad))
                 * b::Transform::from_translation(vec3(0.0, bullet_box_size.y / 2., 0.0))
                 * b::Transform::from_scale(bullet_scale.extend(1.0));
+            let reach = bullet_box_size.max_element() / 2.0;
 
-            commands.spawn((
+            // Don't spawn bullets that could only hit things the player can't see.
+            if !could_be_in_playfield(bullet_transform.translation.xy(), reach) {
+                continue;
+            }
+            if let Some(budget) = &mut bullet_budget {
+                if *budget == 0 {
+                    continue;
+                }
+                *budget -= 1;
+            }
+
+            let mut bullet = commands.spawn((
                 Bullet {
-                    damage: match gun.pattern {
-                        Pattern::Single => 1,
-                        // if coherence is high, add bonus damage
-                        Pattern::Coherent => 1 + (coherence * 2.9).floor() as u8,
-                    },
+                    damage,
+                    reach,
+                    inflicts,
                 },
                 team,
                 Lifetime(match team {
                     Team::Player => 2.0,
                     Team::Enemy => 10.0, // can cross the whole screen
                 }),
-                b::Sprite::from_image(
-                    match team {
-                        Team::Player => &assets.player_bullet_sprite,
-                        Team::Enemy => &assets.enemy_bullet_sprite,
-                    }
-                    .clone(),
-                ),
+                b::Sprite::from_image(bullet_image.clone()),
                 PLAYFIELD_LAYERS,
                 p::RigidBody::Kinematic,
                 p::LinearVelocity(
                     Vec2::from_angle(bullet_angle_rad).rotate(vec2(0.0, single_speed)),
                 ),
-                p::Collider::ellipse(sprite_size.x / 2., sprite_size.y / 2.),
+                pattern.bullet.shape.fit(sprite_size),
+                ColliderFromImage(bullet_image.clone(), pattern.bullet.shape),
                 p::CollidingEntities::default(), // for dealing damage
                 bullet_transform
                     * if bullet_angle_rad.cos() < 0.0 {
@@ -175,19 +723,37 @@ pub(crate) fn fire_gun_system(
                         b::Transform::IDENTITY
                     },
             ));
+            if let Targeting::Homing { turn_rate } = pattern.targeting {
+                bullet.insert(Homing { turn_rate });
+            }
 
             // Muzzle flash sprite is transformed exactly like the bullet, but does not move forward.
             // This helps avoid fast bullets look disconnected.
-            commands.spawn((
-                Lifetime(0.04),
-                b::Sprite::from_image(assets.muzzle_flash_sprite.clone()),
-                PLAYFIELD_LAYERS,
-                bullet_transform,
-            ));
+            if let Some(muzzle_flash) = pattern.bullet.muzzle_flash {
+                commands.spawn((
+                    CosmeticProjectile,
+                    Lifetime(0.04),
+                    b::Sprite::from_image(assets.sprite(muzzle_flash)),
+                    PLAYFIELD_LAYERS,
+                    bullet_transform,
+                ));
+            }
         }
 
         let (ref shoot_sound, volume) = gun.shoot_sound;
+        let volume = if team == Team::Enemy {
+            // Keep dense enemy fire from drowning out everything else.
+            let mut reduction_db = (enemy_shot_sounds as f32 * 1.5).min(12.0);
+            if !PLAYFIELD_RECT.contains(gun_transform.translation.xy()) {
+                reduction_db += 12.0;
+            }
+            enemy_shot_sounds += 1;
+            bevy::audio::Volume::Decibels(volume.to_decibels() - reduction_db)
+        } else {
+            volume
+        };
         commands.spawn((
+            ShotSound(team),
             b::AudioPlayer::new(shoot_sound.clone()),
             b::PlaybackSettings {
                 spatial: true,
@@ -196,68 +762,436 @@ pub(crate) fn fire_gun_system(
                 ..b::PlaybackSettings::DESPAWN
             },
             origin_of_bullets_transform,
+            FollowEmitter(gun_entity),
         ));
 
         // Side effects of firing besides a bullet.
-        gun.cooldown = gun.base_cooldown;
+        run_events.write(RunEvent::ShotFired(team));
+        if let Targeting::Spiral { angular_velocity } = gun.pattern.targeting {
+            gun.aim = (gun.aim + angular_velocity * gun.base_cooldown).rem_euclid(TAU);
+        }
+        gun.cooldown += if team == Team::Enemy {
+            gun.base_cooldown * adaptive.cooldown_scale()
+        } else {
+            gun.base_cooldown
+        };
         if is_player {
             // Shooting with high coherence adds temporary fever, which must be mitigated by not
             // shooting too frequently
-            fever_query.adjust_temporary_and_commit_previous_temporary(0.1 * coherence);
+            quantity_deltas.write(QuantityDelta::new(
+                QuantityKind::Fever,
+                Adjustment::TemporaryCommittingPrevious,
+                0.1 * coherence,
+            ));
 
             // Shooting decreases coherence, which must be mitigated by not missing
-            coherence_query.adjust_temporary_stacking_with_previous(-0.1);
+            quantity_deltas.write(QuantityDelta::new(
+                QuantityKind::Coherence,
+                Adjustment::TemporaryStacking,
+                -0.1,
+            ));
         }
     }
 
     Ok(())
 }
 
+impl ProjectileLedger {
+    /// Whether there are as many projectiles as [`Settings::projectile_render_cap`] allows,
+    /// so that no more [`CosmeticProjectile`]s should be spawned.
+    pub fn is_full(&self, settings: &Settings) -> bool {
+        self.bullets + self.cosmetic >= settings.projectile_render_cap
+    }
+}
+
+pub(crate) fn ledger_add_bullet_observer(
+    _add: b::On<b::Add, Bullet>,
+    mut ledger: b::ResMut<ProjectileLedger>,
+) {
+    ledger.bullets += 1;
+}
+
+pub(crate) fn ledger_remove_bullet_observer(
+    _remove: b::On<b::Remove, Bullet>,
+    mut ledger: b::ResMut<ProjectileLedger>,
+) {
+    ledger.bullets -= 1;
+}
+
+pub(crate) fn ledger_add_cosmetic_observer(
+    add: b::On<b::Add, CosmeticProjectile>,
+    mut ledger: b::ResMut<ProjectileLedger>,
+) {
+    ledger.cosmetic += 1;
+    ledger.cosmetic_order.push_back(add.entity);
+}
+
+pub(crate) fn ledger_remove_cosmetic_observer(
+    _remove: b::On<b::Remove, CosmeticProjectile>,
+    mut ledger: b::ResMut<ProjectileLedger>,
+) {
+    ledger.cosmetic -= 1;
+}
+
+/// When there are more projectiles than [`Settings::projectile_render_cap`], fades out the
+/// oldest [`CosmeticProjectile`]s early to make up the difference, to protect the frame rate
+/// when a fight gets out of hand.
+///
+/// [`Bullet`]s themselves are never removed, since they matter to gameplay.
+pub(crate) fn cap_rendered_projectiles_system(
+    mut commands: b::Commands,
+    settings: b::Res<Settings>,
+    mut ledger: b::ResMut<ProjectileLedger>,
+    mut lifetimes: b::Query<&mut Lifetime, b::With<CosmeticProjectile>>,
+) {
+    let mut excess =
+        (ledger.bullets + ledger.cosmetic).saturating_sub(settings.projectile_render_cap);
+    while excess > 0
+        && let Some(entity) = ledger.cosmetic_order.pop_front()
+    {
+        let Ok(mut lifetime) = lifetimes.get_mut(entity) else {
+            // already gone
+            continue;
+        };
+        lifetime.0 = lifetime.0.min(EARLY_FADE_TIME);
+        // No longer counted, since it is on its way out.
+        commands
+            .entity(entity)
+            .remove::<CosmeticProjectile>()
+            .insert(EarlyFade);
+        excess -= 1;
+    }
+
+    // Drop despawned entities from the front, so that the queue does not grow without bound
+    // while under the cap.
+    while let Some(&entity) = ledger.cosmetic_order.front()
+        && !lifetimes.contains(entity)
+    {
+        ledger.cosmetic_order.pop_front();
+    }
+}
+
+/// Fades [`EarlyFade`] sprites out over the rest of their [`Lifetime`].
+///
+/// Must run after other systems which set the alpha of those sprites.
+pub(crate) fn early_fade_system(query: b::Query<(&mut b::Sprite, &Lifetime), b::With<EarlyFade>>) {
+    for (mut sprite, &Lifetime(remaining)) in query {
+        let alpha = sprite
+            .color
+            .alpha()
+            .min((remaining / EARLY_FADE_TIME).clamp(0.0, 1.0));
+        sprite.color.set_alpha(alpha);
+    }
+}
+
+/// Direction in which to fire a bullet at `speed` from `origin` so that it hits a target which
+/// is at `target` and moving with `target_velocity`.
+///
+/// If the bullet cannot catch up with the target, returns the direction of the target’s current
+/// position instead.
+fn intercept_direction(origin: Vec2, target: Vec2, target_velocity: Vec2, speed: f32) -> Vec2 {
+    // Solve |offset + target_velocity * t| = speed * t for the earliest positive time t.
+    let offset = target - origin;
+    let qa = target_velocity.length_squared() - speed * speed;
+    let qb = 2.0 * offset.dot(target_velocity);
+    let qc = offset.length_squared();
+    let time = if qa.abs() < 1e-6 {
+        // equal speeds; the equation is linear
+        (qb < 0.0).then(|| -qc / qb)
+    } else {
+        let discriminant = qb * qb - 4.0 * qa * qc;
+        (discriminant >= 0.0)
+            .then(|| {
+                let root = discriminant.sqrt();
+                [(-qb - root) / (2.0 * qa), (-qb + root) / (2.0 * qa)]
+                    .into_iter()
+                    .filter(|&t| t > 0.0)
+                    .reduce(f32::min)
+            })
+            .flatten()
+    };
+    match time {
+        Some(time) => offset + target_velocity * time,
+        None => offset,
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 pub(crate) fn gun_cooldown(time: b::Res<b::Time>, query: b::Query<&mut Gun>) {
     let delta = time.delta_secs();
     for mut gun in query {
-        let new_cooldown = (gun.cooldown - delta).max(0.0);
+        // If the cooldown was already finished last tick, don't accumulate any more credit
+        // toward the next shot.
+        let new_cooldown = if gun.cooldown > 0.0 {
+            gun.cooldown - delta
+        } else {
+            0.0
+        };
         if new_cooldown != gun.cooldown {
             gun.cooldown = new_cooldown;
         }
     }
 }
 
-pub(crate) fn bullet_hit_system(
+/// Coherence at and above which the player’s bullets are drawn as a single [`Beam`].
+const BEAM_COHERENCE: f32 = 0.9;
+
+/// Sprite drawn in place of the player’s bullets when they are coherent enough to form a
+/// continuous stream, so that the shot reads as a laser. See [`beam_system`].
+#[derive(Debug, b::Component)]
+pub(crate) struct Beam;
+
+/// At high coherence, hides the player’s bullets which are in line with the gun and draws a
+/// [`Beam`] from the gun to the first of them to hit something (or the farthest of them).
+///
+/// This is purely visual; the bullets still do all the hitting.
+#[allow(
+    clippy::type_complexity,
+    reason = "the bullet and beam queries must be disjoint"
+)]
+pub(crate) fn beam_system(
     mut commands: b::Commands,
-    bullet_query: b::Query<(&Bullet, &Team, &p::CollidingEntities, &mut Lifetime)>,
-    mut target_query: b::Query<
+    assets: Option<b::Res<crate::GameAssets>>,
+    images: b::Res<b::Assets<b::Image>>,
+    quantities: Quantities,
+    player: b::Query<(&b::Transform, &Gun), b::With<Player>>,
+    bullets: b::Query<
         (
-            // Note: Beware requiring components here!
-            // Every required component becomes a condition for attackability!
-            &Team,
-            &mut Attackable,
             &b::Transform,
+            &Team,
+            &mut b::Visibility,
+            b::Has<DespawnNextTick>,
         ),
-        b::Without<b::ChildOf>,
+        (b::With<Bullet>, b::Without<Beam>),
     >,
-    mut coherence_query: b::Single<
-        &mut Quantity,
-        (b::With<Coherence>, b::Without<Fever>, b::Without<Fervor>),
+    mut beams: b::Query<
+        (&mut b::Sprite, &mut b::Transform, &mut b::Visibility),
+        (b::With<Beam>, b::Without<Bullet>, b::Without<Player>),
     >,
+) -> b::Result {
+    let coherence = quantities.get::<Coherence>()?;
+
+    // How far a bullet’s center may be from the line of the beam to be part of it.
+    const TOLERANCE: f32 = 4.0;
+
+    let Some(assets) = assets else {
+        return Ok(());
+    };
+    let Ok((mut beam_sprite, mut beam_transform, mut beam_visibility)) = beams.single_mut() else {
+        commands.spawn((
+            Beam,
+            b::Sprite::from_image(assets.sprite(SpriteId::PlayerBullet)),
+            b::Visibility::Hidden,
+            PLAYFIELD_LAYERS,
+        ));
+        return Ok(());
+    };
+
+    // Origin and direction of the beam, if there is one.
+    let beam_line = player
+        .single()
+        .ok()
+        .filter(|_| coherence.effective_value() >= BEAM_COHERENCE)
+        .map(|(transform, gun)| {
+            (
+                transform.translation.xy(),
+                Vec2::from_angle(gun.aim).rotate(Vec2::Y),
+            )
+        });
+
+    let mut farthest: Option<f32> = None;
+    let mut nearest_hit: Option<f32> = None;
+    for (transform, &team, mut visibility, hit) in bullets {
+        if team != Team::Player {
+            continue;
+        }
+        let distance_along = beam_line.and_then(|(origin, direction)| {
+            let offset = transform.translation.xy() - origin;
+            let along = offset.dot(direction);
+            (along >= 0.0 && offset.perp_dot(direction).abs() <= TOLERANCE).then_some(along)
+        });
+        visibility.set_if_neq(if distance_along.is_some() {
+            b::Visibility::Hidden
+        } else {
+            b::Visibility::Inherited
+        });
+        if let Some(along) = distance_along {
+            farthest = Some(farthest.map_or(along, |f| f.max(along)));
+            if hit {
+                nearest_hit = Some(nearest_hit.map_or(along, |n| n.min(along)));
+            }
+        }
+    }
+
+    let (Some((origin, direction)), Some(length)) = (beam_line, nearest_hit.or(farthest)) else {
+        beam_visibility.set_if_neq(b::Visibility::Hidden);
+        return Ok(());
+    };
+    let width = images
+        .get(&beam_sprite.image)
+        .map_or(1.0, |image| image.size_f32().x);
+    beam_sprite.custom_size = Some(vec2(width, length));
+    *beam_transform = b::Transform::from_translation(
+        (origin + direction * (length / 2.0)).extend(Zees::Bullets.z()),
+    )
+    .with_rotation(b::Quat::from_rotation_z(Vec2::Y.angle_to(direction)));
+    beam_visibility.set_if_neq(b::Visibility::Inherited);
+    Ok(())
+}
+
+/// Despawns bullets which have left the playfield, so that they cannot hit anything that is
+/// not visible (such as enemies waiting to fly in).
+pub(crate) fn cull_offscreen_bullets_system(
+    mut commands: b::Commands,
+    bullets: b::Query<(b::Entity, &Bullet, &b::Transform)>,
+) {
+    for (entity, bullet, transform) in bullets {
+        if !could_be_in_playfield(transform.translation.xy(), bullet.reach) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Turns [`Homing`] bullets toward the nearest living [`Attackable`] of the other [`Team`]
+/// which is in the playfield.
+pub(crate) fn homing_bullets_system(
+    time: b::Res<b::Time>,
+    bullets: b::Query<(&Homing, &Team, &mut b::Transform, &mut p::LinearVelocity)>,
+    targets: b::Query<(&Team, &Attackable, &b::Transform, Option<&b::ChildOf>), b::Without<Homing>>,
+    transforms: b::Query<&b::Transform, b::Without<Homing>>,
+) {
+    let target_positions: Vec<(Team, Vec2)> = targets
+        .iter()
+        .filter(|(_, attackable, _, _)| attackable.health > 0)
+        .map(|(&team, _, transform, parent)| {
+            (team, playfield_position(transform, parent, &transforms))
+        })
+        .filter(|&(_, position)| PLAYFIELD_RECT.contains(position))
+        .collect();
+
+    for (homing, &team, mut transform, mut velocity) in bullets {
+        let position = transform.translation.xy();
+        let Some(target) = target_positions
+            .iter()
+            .filter(|&&(target_team, _)| target_team != team)
+            .map(|&(_, target)| target)
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            })
+        else {
+            continue;
+        };
+        let max_turn = homing.turn_rate * time.delta_secs();
+        let turn = velocity
+            .0
+            .angle_to(target - position)
+            .clamp(-max_turn, max_turn);
+        if turn.is_finite() {
+            velocity.0 = Vec2::from_angle(turn).rotate(velocity.0);
+            transform.rotate_z(turn);
+        }
+    }
+}
+
+/// Refits [`ColliderFromImage`] colliders, and bullet reaches, to images which have changed.
+/// This only matters when assets are being hot-reloaded during development.
+pub(crate) fn refit_colliders_to_images_system(
+    mut image_events: b::MessageReader<b::AssetEvent<b::Image>>,
+    images: b::Res<b::Assets<b::Image>>,
+    colliders: b::Query<(
+        &ColliderFromImage,
+        &mut p::Collider,
+        Option<&mut Bullet>,
+        &b::Transform,
+    )>,
+) {
+    let modified: HashSet<b::AssetId<b::Image>> = image_events
+        .read()
+        .filter_map(|event| match *event {
+            b::AssetEvent::Modified { id } => Some(id),
+            _ => None,
+        })
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+
+    for (&ColliderFromImage(ref image, shape), mut collider, bullet, transform) in colliders {
+        if !modified.contains(&image.id()) {
+            continue;
+        }
+        let Some(size) = images.get(image).map(b::Image::size_f32) else {
+            continue;
+        };
+        *collider = shape.fit(size);
+        if let Some(mut bullet) = bullet {
+            bullet.reach = (size * transform.scale.xy()).max_element() / 2.0;
+        }
+    }
+}
+
+/// Conservatively checks whether a shape with the given center and reach overlaps the playfield.
+fn could_be_in_playfield(center: Vec2, reach: f32) -> bool {
+    PLAYFIELD_RECT.inflate(reach).contains(center)
+}
+
+/// Coherence refunded for each kill beyond the first made by a single player bullet.
+const MULTI_KILL_REFUND_PER_EXTRA_KILL: f32 = 0.04;
+/// Upper limit on coherence refunded for one bullet; equal to the cost of firing it.
+const MULTI_KILL_REFUND_MAX: f32 = 0.1;
+
+#[allow(
+    clippy::type_complexity,
+    reason = "the target query must stay as written; see the note in it"
+)]
+pub(crate) fn bullet_hit_system(
+    mut commands: b::Commands,
+    bullet_query: b::Query<(b::Entity, &Bullet, &Team, &p::CollidingEntities)>,
+    mut target_query: b::Query<(
+        // Note: Beware requiring components here!
+        // Every required component becomes a condition for attackability!
+        &Team,
+        &mut Attackable,
+        &b::Transform,
+        Option<&b::ChildOf>,
+        Option<&b::Children>,
+        b::Has<Invulnerable>,
+    )>,
+    transforms: b::Query<&b::Transform>,
+    parts: b::Query<&Part>,
+    wrap_ghosts: b::Query<&WrapGhost>,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
 ) -> b::Result {
     let mut killed = EntityHashSet::new();
-    for (bullet, &bullet_team, collisions, mut bullet_lifetime) in bullet_query {
+    // so that a burst of bullets against armor makes one clink, not a roar
+    let mut clinked = EntityHashSet::new();
+    for (bullet_entity, bullet, &bullet_team, collisions) in bullet_query {
         // Note that a bullet may hit multiple targets and kill them if its collider
         // is large enough. This is on purpose to make high Coherence shots more effective.
+        let mut kills_by_this_bullet: u32 = 0;
 
         'colliding: for &colliding_entity in &collisions.0 {
-            let Ok((&target_team, mut target_attackable, target_transform)) =
-                target_query.get_mut(colliding_entity)
+            // hitting a ghost hits its ship
+            let colliding_entity = wrap_ghosts
+                .get(colliding_entity)
+                .map_or(colliding_entity, |ghost| ghost.of);
+            let Ok((
+                &target_team,
+                mut target_attackable,
+                target_transform,
+                target_parent,
+                target_children,
+                target_invulnerable,
+            )) = target_query.get_mut(colliding_entity)
             else {
                 // collided but is not attackable
                 // b::warn!("collided with {colliding_entity} but is not attackable");
                 continue 'colliding;
             };
 
-            if !bullet_team.should_hurt(target_team) {
+            if !bullet_team.should_hurt(target_team) || target_invulnerable {
                 continue 'colliding;
             }
 
@@ -266,35 +1200,86 @@ pub(crate) fn bullet_hit_system(
                 continue 'colliding;
             }
 
-            if !PLAYFIELD_RECT.contains(target_transform.translation.xy()) {
+            let target_position = playfield_position(target_transform, target_parent, &transforms);
+            if !PLAYFIELD_RECT.contains(target_position) {
                 // don't allow shooting things that are off the screen
                 // (this is a kludge for the sake of enemies flying on screen)
                 continue 'colliding;
             }
 
+            if target_children.is_some_and(|children| has_critical_part(children, &parts, &killed))
+            {
+                // armored by its parts; the bullet stops but does no damage
+                commands.entity(bullet_entity).insert(DespawnNextTick);
+                if clinked.insert(colliding_entity) {
+                    commands.spawn(emitted_sound_bundle(
+                        target_attackable.hurt_sound.clone(),
+                        bevy::audio::Volume::Decibels(ARMOR_CLINK_VOLUME_DB),
+                        ARMOR_CLINK_SPEED,
+                        target_position.extend(target_transform.translation.z),
+                        colliding_entity,
+                    ));
+                }
+                continue 'colliding;
+            }
+
             let new_health = target_attackable.health.saturating_sub(bullet.damage);
             let is_killed = new_health == 0;
 
             target_attackable.last_hit_by = Some(bullet_team);
             target_attackable.health = new_health;
-            commands.trigger(Hurt(colliding_entity));
+            if let Some(kind) = bullet.inflicts
+                && !is_killed
+            {
+                commands
+                    .entity(colliding_entity)
+                    .entry::<StatusEffects>()
+                    .or_default()
+                    .and_modify(move |mut statuses| statuses.inflict(kind, bullet_team));
+            }
+            commands.trigger(Hurt {
+                target: colliding_entity,
+                damage: bullet.damage,
+            });
 
             if is_killed {
                 killed.insert(colliding_entity);
+                kills_by_this_bullet += 1;
             }
 
-            bullet_lifetime.0 = 0.0; // cause bullet to die on the next frame
+            commands.entity(bullet_entity).insert(DespawnNextTick);
 
             // Player successfully hitting *something* cancels coherence loss.
             if bullet_team == Team::Player {
-                coherence_query.adjust_permanent_clearing_temporary(0.0);
+                quantity_deltas.write(QuantityDelta::new(
+                    QuantityKind::Coherence,
+                    Adjustment::PermanentClearingTemporary,
+                    0.0,
+                ));
             }
         }
+
+        // One player bullet killing several enemies gives back some of the coherence spent on
+        // firing, so that well-placed big shots can sustain themselves.
+        if bullet_team == Team::Player && kills_by_this_bullet >= 2 {
+            let refund = (MULTI_KILL_REFUND_PER_EXTRA_KILL * (kills_by_this_bullet - 1) as f32)
+                .min(MULTI_KILL_REFUND_MAX);
+            quantity_deltas.write(QuantityDelta::new(
+                QuantityKind::Coherence,
+                Adjustment::PermanentKeepingTemporary,
+                refund,
+            ));
+        }
     }
     Ok(())
 }
 
 /// Despawns [`Attackable`]s with health of 0, and produces side effects such as drops.
+#[allow(
+    clippy::too_many_arguments,
+    clippy::type_complexity,
+    reason = "a death affects score, drops, effects, and run statistics"
+)]
 pub(crate) fn death_system(
     mut commands: b::Commands,
     attackable_query: b::Query<
@@ -305,165 +1290,600 @@ pub(crate) fn death_system(
             &mut Attackable,
             &b::Transform,
             Option<&p::LinearVelocity>,
+            Option<&Drops>,
             Option<&b::Children>,
+            Option<&b::ChildOf>,
+            Option<&Part>,
+            Option<&ScoreValue>,
+            b::Has<Boss>,
         ),
-        (b::Changed<Attackable>, b::Without<b::ChildOf>),
-    >,
-    fever_query: ReadQuantity<Fever>,
-    coherence_query: ReadQuantity<Coherence>,
-    mut fervor_query: QFervorMut,
-    mut children_to_drop_query: b::Query<
-        (&b::GlobalTransform, &mut b::Transform, &Pickup),
-        (
-            b::With<b::ChildOf>,
-            b::Without<Bullet>,
-            b::Without<Quantity>,
-        ),
+        b::Changed<Attackable>,
     >,
-) {
+    transforms: b::Query<&b::Transform>,
+    carried_pickups: b::Query<&b::GlobalTransform, b::With<Carried>>,
+    mut cores: b::Query<(&mut Attackable, &b::Children), b::Without<Part>>,
+    parts: b::Query<&Part>,
+    quantities: Quantities,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
+    mut run_events: b::MessageWriter<RunEvent>,
+    prefabs: Prefabs,
+    settings: b::Res<Settings>,
+    mut game_rng: b::ResMut<GameRng>,
+    mut score: b::ResMut<Score>,
+    mut combo: b::ResMut<Combo>,
+    assets: b::Res<GameAssets>,
+    state: b::Res<b::State<GameState>>,
+) -> b::Result {
+    // Kills after the run has ended are only for show, and do not score.
+    let run_in_progress = *state.get() == GameState::Playing;
+
+    let fever = quantities.get::<Fever>()?;
+    let coherence = quantities.get::<Coherence>()?;
+    let fervor = quantities.get::<Fervor>()?;
+
     let rng = &mut rand::rng();
 
-    for (dying_entity, dying_attackable, &dying_transform, dying_velocity, children_of_dying) in
-        attackable_query
+    // Deltas are not applied until later, so keep our own running total for the
+    // sake of multiple kills in one frame.
+    let mut fervor_temporary_stack = fervor.temporary_stack();
+
+    // Parts dying now, which no longer protect their cores.
+    let mut dying_parts = EntityHashSet::new();
+    // Cores which may have just lost their last critical part, with who destroyed it.
+    let mut cores_to_check = Vec::new();
+
+    for (
+        dying_entity,
+        dying_attackable,
+        dying_transform,
+        dying_velocity,
+        drops,
+        children_of_dying,
+        dying_parent,
+        dying_part,
+        score_value,
+        is_boss,
+    ) in attackable_query
     {
         if dying_attackable.health > 0 {
             // not dying
             continue;
         }
 
+        let dying_position = playfield_position(dying_transform, dying_parent, &transforms);
         let dying_velocity = dying_velocity.map_or(Vec2::ZERO, |&p::LinearVelocity(v)| v);
 
-        // Reparent children that are pickups.
-        // (In the future we might want to have a different condition)
+        // Drop the carried pickup, if any.
         for &child in children_of_dying.into_iter().flatten() {
-            if let Ok((global_transform, mut local_transform, pickup)) =
-                children_to_drop_query.get_mut(child)
+            let Ok(global_transform) = carried_pickups.get(child) else {
+                // parts die with their core
+                continue;
+            };
+            // De-parent the pickup so it will survive the target being despawned,
+            // preserve its global position, and give it its own physics.
+            let mut child_cmd = commands.entity(child);
+            child_cmd.remove::<(b::ChildOf, Carried)>();
+            child_cmd.insert((
+                global_transform.compute_transform(),
+                crate::pickup::after_drop_bundle(dying_velocity, &mut **game_rng),
+            ));
+            // The kind of pickup depends on how the player is doing now.
+            if let Some(drops) = drops
+                && let Some(kind) = choose_drop(drops, fever, coherence, &mut **game_rng)
             {
-                // De-parent the pickup so it will survive the target being despawned,
-                // preserve its global position, and give it its own physics.
-                *local_transform = global_transform.compute_transform();
-
-                let mut child_cmd = commands.entity(child);
-                child_cmd.remove::<b::ChildOf>();
-                child_cmd.insert(crate::pickup::after_drop_bundle(pickup));
-            } else {
-                b::warn!("attacked entity has child {child:?} which is not a pickup");
+                child_cmd.insert(kind.appearance_and_effect(prefabs.pickups()?));
             }
         }
 
         // Spawn debris
-        if let Some(particle) = dying_attackable.destruction_particle.as_ref() {
+        if let Some(particle) = dying_attackable.destruction_particle.as_ref()
+            && !settings.low_spec
+        {
             let particle_count = rng.random_range(20u32..40);
-            for _ in 0..particle_count {
-                let particle_direction_1 = Vec2::from(rand_distr::UnitDisc.sample(rng));
-                let particle_direction_2 = Vec2::from(rand_distr::UnitDisc.sample(rng));
-                let particle_position =
-                    dying_transform.translation.xy() + particle_direction_1 * 15.0;
-                let particle_velocity =
-                    dying_velocity + particle_direction_1 * 50.0 + particle_direction_2 * 50.0;
-                commands.spawn((
-                    b::Sprite::from_image(particle.clone()),
-                    b::Transform::from_translation(particle_position.extend(Zees::Pickup.z()))
-                        .with_rotation(b::Quat::from_rotation_z(
-                            rng.random_range(0.0f32..=PI * 2.0),
-                        )),
-                    PLAYFIELD_LAYERS,
-                    p::RigidBody::Kinematic,
-                    p::Collider::circle(1.0), // TODO: use a simple movement system w/o physics so as not to exercise collision
-                    p::LinearVelocity(particle_velocity),
-                    Lifetime(0.5), // TODO: would be more efficient to detect when the sprite is off the screen
+            spawn_debris(
+                &mut commands,
+                rng,
+                particle,
+                dying_position,
+                dying_velocity,
+                particle_count,
+            );
+        }
+
+        if let Some(part) = dying_part {
+            // Destroying a part is not a kill in itself; only the core counts.
+            dying_parts.insert(dying_entity);
+            if part.critical
+                && let Some(&b::ChildOf(core)) = dying_parent
+            {
+                cores_to_check.push((core, dying_attackable.last_hit_by));
+            }
+        } else if dying_attackable.last_hit_by == Some(Team::Player) && run_in_progress {
+            run_events.write(RunEvent::EnemyKilled);
+            combo.add_kill();
+
+            if let Some(&ScoreValue(value)) = score_value {
+                let points = kill_points(value, coherence, fervor, &combo);
+                score.0 += u64::from(points);
+                commands.spawn(points_popup_bundle(&assets, dying_position, points));
+            }
+
+            if fervor_is_active(fever, coherence) {
+                // Increase fervor if the player made this kill.
+                // By adding some of the previous value we make it easier to get big boosts
+                // with combo kills.
+                let added_fervor =
+                    (0.0301 + 0.03 * fervor_temporary_stack.max(0.4)) * combo.multiplier();
+                fervor_temporary_stack += added_fervor;
+                quantity_deltas.write(QuantityDelta::new(
+                    QuantityKind::Fervor,
+                    Adjustment::TemporaryStacking,
+                    added_fervor,
                 ));
             }
         }
 
-        if dying_attackable.last_hit_by == Some(Team::Player)
-            && fervor_is_active(&fever_query, &coherence_query)
-        {
-            // Increase fervor if the player made this kill.
-            // By adding some of the previous value we make it easier to get big boosts
-            // with combo kills.
-            let added_fervor = 0.0301 + 0.03 * fervor_query.temporary_stack().max(0.4);
-            fervor_query.adjust_temporary_stacking_with_previous(added_fervor);
+        if is_boss {
+            // The boss is not despawned yet, but explodes at length; see `boss_death_system()`.
+            commands
+                .entity(dying_entity)
+                .remove::<(Attackable, p::Collider, Gun)>()
+                .insert(BossDeath::default());
+        } else {
+            commands.entity(dying_entity).despawn();
+        }
+    }
+
+    // A core with no critical parts left is destroyed. It dies on the next tick, when this
+    // system sees its health change.
+    for (core, last_hit_by) in cores_to_check {
+        let Ok((mut core_attackable, children)) = cores.get_mut(core) else {
+            continue;
+        };
+        if core_attackable.health > 0 && !has_critical_part(children, &parts, &dying_parts) {
+            core_attackable.health = 0;
+            core_attackable.last_hit_by = last_hit_by;
         }
+    }
+    Ok(())
+}
 
-        commands.entity(dying_entity).despawn();
+/// Spawns `count` pieces of debris flying outward from `position`.
+pub(crate) fn spawn_debris(
+    commands: &mut b::Commands,
+    rng: &mut impl rand::Rng,
+    particle: &b::Handle<b::Image>,
+    position: Vec2,
+    velocity: Vec2,
+    count: u32,
+) {
+    for _ in 0..count {
+        let particle_direction_1 = Vec2::from(rand_distr::UnitDisc.sample(rng));
+        let particle_direction_2 = Vec2::from(rand_distr::UnitDisc.sample(rng));
+        let particle_position = position + particle_direction_1 * 15.0;
+        let particle_velocity =
+            velocity + particle_direction_1 * 50.0 + particle_direction_2 * 50.0;
+        commands.spawn((
+            CosmeticProjectile,
+            b::Sprite::from_image(particle.clone()),
+            b::Transform::from_translation(particle_position.extend(Zees::Pickup.z()))
+                .with_rotation(b::Quat::from_rotation_z(
+                    rng.random_range(0.0f32..=PI * 2.0),
+                )),
+            PLAYFIELD_LAYERS,
+            p::RigidBody::Kinematic,
+            p::Collider::circle(1.0), // TODO: use a simple movement system w/o physics so as not to exercise collision
+            p::LinearVelocity(particle_velocity),
+            Lifetime(0.5), // TODO: would be more efficient to detect when the sprite is off the screen
+        ));
     }
 }
 
+/// Position in the playfield of an entity which is either top-level or a [`Part`] of one.
+fn playfield_position<F: QueryFilter>(
+    transform: &b::Transform,
+    parent: Option<&b::ChildOf>,
+    transforms: &b::Query<&b::Transform, F>,
+) -> Vec2 {
+    let parent_transform = parent
+        .and_then(|&b::ChildOf(parent)| transforms.get(parent).ok())
+        .copied()
+        .unwrap_or(b::Transform::IDENTITY);
+    parent_transform.transform_point(transform.translation).xy()
+}
+
+/// Whether any of `children` is a critical [`Part`] which is not in `destroyed`.
+fn has_critical_part(
+    children: &b::Children,
+    parts: &b::Query<&Part>,
+    destroyed: &EntityHashSet,
+) -> bool {
+    children.iter().any(|&child| {
+        !destroyed.contains(&child) && parts.get(child).is_ok_and(|part| part.critical)
+    })
+}
+
 pub(crate) fn hurt_side_effects_observer(
     hurt: b::On<Hurt>,
     mut commands: b::Commands,
-    assets: b::Res<crate::MyAssets>,
-    mut hurt_entity_query: b::Query<(&mut Attackable, &b::Transform)>,
+    assets: b::Res<crate::GameAssets>,
+    mut game_speed: b::ResMut<crate::GameSpeed>,
+    settings: b::Res<Settings>,
+    mut hurt_entity_query: b::Query<(
+        &mut Attackable,
+        &b::Transform,
+        Option<&b::ChildOf>,
+        b::Has<Player>,
+    )>,
+    transforms: b::Query<&b::Transform>,
 ) -> b::Result {
-    let (mut attackable, &transform) = hurt_entity_query.get_mut(hurt.0)?;
+    let &Hurt { target, damage } = hurt.event();
+    let (mut attackable, transform, parent, is_player) = hurt_entity_query.get_mut(target)?;
+    let position = playfield_position(transform, parent, &transforms);
     let is_killed = attackable.health == 0;
 
     if attackable.hurt_animation_cooldown == 0.0 {
         attackable.hurt_animation_cooldown = 0.1;
+        attackable.hurt_animation_damage = damage;
+    } else {
+        attackable.hurt_animation_damage = attackable.hurt_animation_damage.max(damage);
+    }
+
+    if !settings.low_spec {
+        spawn_hit_sparks(&mut commands, &assets, position, damage);
+    }
+
+    if is_player {
+        game_speed.add_effect(crate::SpeedEffect::HIT_STOP);
     }
 
     // Play death or hurt sound
     // TODO: move death sound to death system for consistency in the presence of fever updates
-    commands.spawn((
-        b::AudioPlayer::new(
-            // TODO: separate player kill sound
-            if is_killed {
-                &assets.enemy_kill_sound
-            } else {
-                &attackable.hurt_sound
-            }
-            .clone(),
-        ),
-        b::PlaybackSettings {
-            spatial: true,
-            volume: bevy::audio::Volume::Decibels(-10.),
-            ..b::PlaybackSettings::DESPAWN
-        },
-        transform,
+    // TODO: separate player kill sound
+    let (sound, volume_db, speed) = if is_killed {
+        (assets.sound(SoundId::EnemyKill), -10.0, 1.0)
+    } else {
+        (
+            attackable.hurt_sound.clone(),
+            hurt_volume_db(damage),
+            hurt_sound_speed(&attackable),
+        )
+    };
+    commands.spawn(emitted_sound_bundle(
+        sound,
+        bevy::audio::Volume::Decibels(volume_db),
+        speed,
+        position.extend(transform.translation.z),
+        // a part’s transform is relative to its core, so follow the core instead
+        parent.map_or(target, |&b::ChildOf(core)| core),
     ));
 
     Ok(())
 }
 
-pub(crate) fn hurt_animation_system(
-    time: b::Res<b::Time>,
-    query: b::Query<(&mut b::Sprite, &mut Attackable)>,
-) {
-    // arguably this should be 2 systems, one for cooldown and one for display
-    for (mut sprite, mut attackable) in query {
-        let luminance = if attackable.hurt_animation_cooldown > 0.0 {
+/// Volume of the hurt sound of a hit for the given amount of damage: louder for heavier hits,
+/// up to [`HURT_VOLUME_MAX_DAMAGE`].
+fn hurt_volume_db(damage: u8) -> f32 {
+    HURT_VOLUME_DB
+        + HURT_VOLUME_DB_PER_DAMAGE * f32::from(damage.clamp(1, HURT_VOLUME_MAX_DAMAGE) - 1)
+}
+
+/// Playback speed of the hurt sound of `attackable`, which rises by up to [`HURT_PITCH_RISE`]
+/// as its health runs out, so that the player can hear when something is nearly destroyed.
+fn hurt_sound_speed(attackable: &Attackable) -> f32 {
+    let damaged_fraction = if attackable.max_health == 0 {
+        0.0
+    } else {
+        1.0 - f32::from(attackable.health) / f32::from(attackable.max_health)
+    };
+    attackable.hurt_pitch * (1.0 + HURT_PITCH_RISE * damaged_fraction)
+}
+
+/// Color of hit feedback for the given amount of damage: white for 1-damage grazes, shading to
+/// orange for the heaviest coherent shots, so that the coherence damage bonus can be felt.
+///
+/// The color is linear and has 0 or 1 in each channel except one; see [`hurt_flash_system`] for
+/// why.
+fn hit_color(damage: u8) -> b::LinearRgba {
+    match damage {
+        0..=1 => b::LinearRgba::rgb(1.0, 1.0, 1.0),
+        2 => b::LinearRgba::rgb(1.0, 1.0, 0.0002),
+        _ => b::LinearRgba::rgb(1.0, 0.0003, 0.0),
+    }
+}
+
+/// Spawns a few short-lived sparks flying away from a hit, more and faster for more damage.
+fn spawn_hit_sparks(commands: &mut b::Commands, assets: &GameAssets, position: Vec2, damage: u8) {
+    // Scale the tiny channels up to ordinary sprite color values.
+    let tint = hit_color(damage) * 1000.0;
+    let color = b::Color::linear_rgb(tint.red.min(1.0), tint.green.min(1.0), tint.blue.min(1.0));
+
+    let rng = &mut rand::rng();
+    for _ in 0..(1 + damage.min(4)) {
+        let direction = Vec2::from(rand_distr::UnitCircle.sample(rng));
+        let speed = rng.random_range(60.0..=100.0) * (1.0 + f32::from(damage.min(4)) * 0.25);
+        commands.spawn((
+            CosmeticProjectile,
+            b::Sprite {
+                image: assets.sprite(SpriteId::Star),
+                color,
+                ..b::default()
+            },
+            b::Transform::from_translation(position.extend(Zees::AbovePlayer.z()))
+                .with_rotation(b::Quat::from_rotation_z(Vec2::NEG_Y.angle_to(direction))),
+            PLAYFIELD_LAYERS,
+            p::RigidBody::Kinematic,
+            p::LinearVelocity(direction * speed),
+            Lifetime(rng.random_range(0.08..=0.16)),
+        ));
+    }
+}
+
+/// Present while an [`Attackable`]’s sprite is flashing from being hurt.
+#[derive(Debug, b::Component)]
+pub(crate) struct HurtFlash {
+    /// Color of the sprite before the flash, to be restored afterward.
+    original_color: b::Color,
+}
+
+pub(crate) fn hurt_cooldown_system(time: b::Res<b::Time>, query: b::Query<&mut Attackable>) {
+    for mut attackable in query {
+        if attackable.hurt_animation_cooldown > 0.0 {
             attackable.hurt_animation_cooldown =
                 (attackable.hurt_animation_cooldown - time.delta_secs()).max(0.0);
-            1000.0
-        } else {
-            1.0
-        };
-        sprite.color = b::Color::linear_rgb(luminance, luminance, luminance)
+        }
     }
 }
 
+/// Brightens the sprites of [`Attackable`]s whose hurt animation is active,
+/// and restores their color when it ends.
+///
+/// The flash is added to the sprite’s color with such intensity that any nonzero channel of
+/// [`hit_color()`] saturates, so the hue comes from its tiny channels and its zero channels,
+/// which leave the sprite’s own color.
+pub(crate) fn hurt_flash_system(
+    mut commands: b::Commands,
+    query: b::Query<(b::Entity, &mut b::Sprite, &Attackable, Option<&HurtFlash>)>,
+) {
+    const FLASH_BRIGHTNESS: f32 = 1000.0;
+
+    for (entity, mut sprite, attackable, flash) in query {
+        let hurting = attackable.hurt_animation_cooldown > 0.0;
+        match (hurting, flash) {
+            (true, None) => {
+                let original_color = sprite.color;
+                let c = original_color.to_linear();
+                let flash = hit_color(attackable.hurt_animation_damage) * FLASH_BRIGHTNESS;
+                sprite.color = b::Color::LinearRgba(b::LinearRgba::new(
+                    c.red + flash.red,
+                    c.green + flash.green,
+                    c.blue + flash.blue,
+                    c.alpha,
+                ));
+                commands.entity(entity).insert(HurtFlash { original_color });
+            }
+            (false, Some(&HurtFlash { original_color })) => {
+                sprite.color = original_color;
+                commands.entity(entity).remove::<HurtFlash>();
+            }
+            (true, Some(_)) | (false, None) => {}
+        }
+    }
+}
+
+/// Distance from the player ship’s center within which an enemy bullet counts as a near miss
+/// for [`fever_pressure_system`].
+const GRAZE_RADIUS: f32 = 24.0;
+/// Temporary Fever added by a near miss when Fever is at its maximum; scaled down by the square
+/// of Fever below that, so that the pressure is negligible until Fever is already high.
+const GRAZE_FEVER: f32 = 0.02;
+
+/// If [`Settings::fever_pressure`] is set, enemy bullets which pass close to the player without
+/// hitting add temporary Fever, once they have passed by.
+#[allow(
+    clippy::type_complexity,
+    reason = "bullets are queried along with their graze state"
+)]
+pub(crate) fn fever_pressure_system(
+    mut commands: b::Commands,
+    settings: b::Res<Settings>,
+    player_query: b::Query<(b::Entity, &b::Transform), b::With<Player>>,
+    bullet_query: b::Query<
+        (
+            b::Entity,
+            &Team,
+            &b::Transform,
+            &p::CollidingEntities,
+            b::Has<Grazing>,
+        ),
+        (
+            b::With<Bullet>,
+            b::Without<Grazed>,
+            b::Without<DespawnNextTick>,
+        ),
+    >,
+    quantities: Quantities,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
+) -> b::Result {
+    if !settings.fever_pressure {
+        return Ok(());
+    }
+    let fever = quantities.get::<Fever>()?.effective_value();
+
+    for (player_entity, player_transform) in player_query {
+        let player_position = player_transform.translation.xy();
+        for (bullet_entity, &team, bullet_transform, collisions, grazing) in bullet_query {
+            // A bullet which hits is not a near miss; it is despawned by `bullet_hit_system`.
+            if team != Team::Enemy || collisions.contains(&player_entity) {
+                continue;
+            }
+            let near = bullet_transform.translation.xy().distance(player_position) <= GRAZE_RADIUS;
+            if near && !grazing {
+                commands.entity(bullet_entity).insert(Grazing);
+            } else if !near && grazing {
+                // Left without hitting, so it was a near miss.
+                commands
+                    .entity(bullet_entity)
+                    .remove::<Grazing>()
+                    .insert(Grazed);
+                quantity_deltas.write(QuantityDelta::new(
+                    QuantityKind::Fever,
+                    Adjustment::TemporaryStacking,
+                    GRAZE_FEVER * fever.powi(2),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts damage to the player into Fever; see [`player_overheat_system`] for how it is lethal.
 pub(crate) fn player_health_is_fever_system(
     // Note that this query matches `Player` and not everything on `Team::Player`.
     // This doesn't matter now but we could imagine having drones or something.
     player_query: b::Query<&mut Attackable, b::With<Player>>,
-    mut fever_query: QFeverMut,
-    mut fervor_query: QFervorMut,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
+    mut run_events: b::MessageWriter<RunEvent>,
 ) {
     for mut attackable in player_query {
         let damage = u8::MAX - attackable.health;
         if damage > 0 {
-            fever_query.adjust_permanent_including_temporary(damage as f32 * 0.1);
+            run_events.write(RunEvent::PlayerDamaged);
 
-            if fever_query.effective_value() == 1.0 {
-                // cause death
-                attackable.health = 0;
-            } else {
-                attackable.health = u8::MAX;
-            }
+            quantity_deltas.write(QuantityDelta::new(
+                QuantityKind::Fever,
+                Adjustment::PermanentIncludingTemporary,
+                damage as f32 * 0.1,
+            ));
+            attackable.health = u8::MAX;
 
             // Taking any damage also resets fervor
-            fervor_query.adjust_permanent_keeping_temporary(-0.2);
+            quantity_deltas.write(QuantityDelta::new(
+                QuantityKind::Fervor,
+                Adjustment::PermanentKeepingTemporary,
+                -0.2,
+            ));
+        }
+    }
+}
+
+/// Destroys the player’s ship when Fever has reached [`Fever::LOSE_THRESHOLD`], from any source,
+/// and there are no lives left. [`death_system`] then handles it on the next tick.
+///
+/// Must run after this tick’s quantity changes have been applied, and after
+/// [`lose_life_system`](crate::lives::lose_life_system), which cools the ship instead
+/// while there is a life to spare.
+pub(crate) fn player_overheat_system(
+    player_query: b::Query<&mut Attackable, b::With<Player>>,
+    quantities: Quantities,
+    lives: b::Res<Lives>,
+) -> b::Result {
+    if quantities.get::<Fever>()?.effective_value() < Fever::LOSE_THRESHOLD || lives.0 > 0 {
+        return Ok(());
+    }
+    for mut attackable in player_query {
+        attackable.health = 0;
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.distance(expected) < 1e-3,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn intercept_stationary_target() {
+        let origin = Vec2::new(10.0, 10.0);
+        let target = Vec2::new(40.0, 50.0);
+        assert_near(
+            intercept_direction(origin, target, Vec2::ZERO, 100.0),
+            target - origin,
+        );
+    }
+
+    #[test]
+    fn intercept_uncatchable_target() {
+        // The target is moving directly away, faster than the bullet.
+        let target = Vec2::new(0.0, 10.0);
+        assert_near(
+            intercept_direction(Vec2::ZERO, target, Vec2::new(0.0, 20.0), 10.0),
+            target,
+        );
+    }
+
+    #[test]
+    fn intercept_earlier_of_two_times() {
+        // The target is faster than the bullet but coming closer, so the bullet could meet it
+        // at two times, and should aim for the earlier.
+        let target = Vec2::new(30.0, 100.0);
+        let target_velocity = Vec2::new(0.0, -20.0);
+        let speed = 10.0;
+        let direction = intercept_direction(Vec2::ZERO, target, target_velocity, speed);
+
+        let time = direction.length() / speed;
+        assert_near(direction, target + target_velocity * time);
+        // the later root is about 9.51
+        assert!((time - 3.8187).abs() < 1e-3, "time {time}");
+    }
+
+    #[test]
+    fn upgrades_stop_at_max_level() {
+        let mut upgrades = WeaponUpgrades::default();
+        for _ in 0..WeaponUpgrades::MAX_LEVEL {
+            assert!(upgrades.add(WeaponUpgrade::Wide));
+        }
+        assert!(!upgrades.add(WeaponUpgrade::Wide));
+        assert_eq!(upgrades.wide, WeaponUpgrades::MAX_LEVEL);
+        // other upgrades are unaffected
+        assert_eq!((upgrades.rapid, upgrades.heavy), (0, 0));
+        assert!(upgrades.add(WeaponUpgrade::Heavy));
+    }
+
+    #[test]
+    fn upgrades_change_pattern() {
+        for weapon in [PlayerWeapon::Coherent, PlayerWeapon::Homing] {
+            let base = weapon.base_pattern();
+            let upgrades = WeaponUpgrades {
+                wide: 2,
+                rapid: 0,
+                heavy: 1,
+            };
+            let pattern = upgrades.pattern(weapon);
+            assert_eq!(pattern.spread_count, base.spread_count + 2);
+            assert_eq!(pattern.damage, base.damage + 1);
+            assert_eq!(pattern.ring_count, base.ring_count);
+
+            let unupgraded = WeaponUpgrades::default().pattern(weapon);
+            assert_eq!(unupgraded.spread_count, base.spread_count);
+            assert_eq!(unupgraded.damage, base.damage);
+        }
+    }
+
+    #[test]
+    fn upgrades_shorten_cooldown() {
+        for weapon in [PlayerWeapon::Coherent, PlayerWeapon::Homing] {
+            let base = weapon.base_cooldown();
+            assert_eq!(WeaponUpgrades::default().cooldown(weapon), base);
+            let upgrades = WeaponUpgrades {
+                wide: 0,
+                rapid: 2,
+                heavy: 0,
+            };
+            let cooldown = upgrades.cooldown(weapon);
+            assert!(
+                (cooldown - base * RAPID_COOLDOWN_FACTOR * RAPID_COOLDOWN_FACTOR).abs() < 1e-6,
+                "{weapon:?}: {cooldown}"
+            );
         }
     }
 }
@@ -0,0 +1,241 @@
+use core::fmt::Write as _;
+use std::borrow::Cow;
+
+use bevy::ecs::change_detection::DetectChangesMut as _;
+use bevy::input::gamepad::{GamepadAxisChangedEvent, GamepadButtonChangedEvent};
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::prelude as b;
+use bevy_enhanced_input::prelude as bei;
+use itertools::Itertools as _;
+
+use crate::settings::Settings;
+use crate::{
+    Aim, Bomb, Escape, GameState, Move, Restart, Shoot, SwitchWeapon, TogglePause, VolumeDown,
+    VolumeUp,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Text listing the current bindings of the player’s actions,
+/// updated by [`update_controls_text_system`].
+#[derive(Debug, b::Component)]
+pub(crate) struct ControlsText;
+
+/// Input context holding a copy of the player’s actions while on the menu, where there is no
+/// player ship, so that the [`ControlsText`] can list their bindings. Never active.
+#[derive(Debug, b::Component)]
+pub(crate) struct ControlsPreview;
+
+/// Which kind of device the player most recently used, so that control hints can show the
+/// bindings for that device.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, b::Resource)]
+pub(crate) enum LastInputDevice {
+    #[default]
+    KeyboardAndMouse,
+    Gamepad,
+}
+
+/// How far a gamepad axis must move to count as using the gamepad, so that stick drift does not.
+const AXIS_USE_THRESHOLD: f32 = 0.5;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Updates [`LastInputDevice`] from input events.
+pub(crate) fn track_input_device_system(
+    mut keys: b::MessageReader<KeyboardInput>,
+    mut mouse_buttons: b::MessageReader<MouseButtonInput>,
+    mut gamepad_buttons: b::MessageReader<GamepadButtonChangedEvent>,
+    mut gamepad_axes: b::MessageReader<GamepadAxisChangedEvent>,
+    mut device: b::ResMut<LastInputDevice>,
+) {
+    let used_gamepad = gamepad_buttons.read().count() > 0
+        || gamepad_axes
+            .read()
+            .any(|event| event.value.abs() >= AXIS_USE_THRESHOLD);
+    let used_keyboard_or_mouse = keys.read().count() > 0 || mouse_buttons.read().count() > 0;
+
+    if used_gamepad {
+        device.set_if_neq(LastInputDevice::Gamepad);
+    } else if used_keyboard_or_mouse {
+        device.set_if_neq(LastInputDevice::KeyboardAndMouse);
+    }
+}
+
+/// Spawns the [`ControlsPreview`], with the bindings the player’s ship will have.
+pub(crate) fn spawn_controls_preview(mut commands: b::Commands, settings: b::Res<Settings>) {
+    let mut preview = commands.spawn((
+        ControlsPreview,
+        bei::ContextActivity::<ControlsPreview>::INACTIVE,
+    ));
+    crate::insert_player_actions::<ControlsPreview>(&mut preview, &settings);
+}
+
+pub(crate) fn despawn_controls_preview(
+    mut commands: b::Commands,
+    previews: b::Query<b::Entity, b::With<ControlsPreview>>,
+) {
+    for preview in previews {
+        commands.entity(preview).despawn();
+    }
+}
+
+/// Rewrites the [`ControlsText`] from the bindings of the actions that currently exist,
+/// showing those for the [`LastInputDevice`] when the action has any.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "each action’s bindings are a separate query"
+)]
+pub(crate) fn update_controls_text_system(
+    device: b::Res<LastInputDevice>,
+    move_actions: b::Query<&bei::Bindings, b::With<bei::Action<Move>>>,
+    aim_actions: b::Query<&bei::Bindings, b::With<bei::Action<Aim>>>,
+    shoot_actions: b::Query<&bei::Bindings, b::With<bei::Action<Shoot>>>,
+    bomb_actions: b::Query<&bei::Bindings, b::With<bei::Action<Bomb>>>,
+    switch_weapon_actions: b::Query<&bei::Bindings, b::With<bei::Action<SwitchWeapon>>>,
+    pause_actions: b::Query<&bei::Bindings, b::With<bei::Action<TogglePause>>>,
+    escape_actions: b::Query<&bei::Bindings, b::With<bei::Action<Escape>>>,
+    restart_actions: b::Query<&bei::Bindings, b::With<bei::Action<Restart>>>,
+    volume_up_actions: b::Query<&bei::Bindings, b::With<bei::Action<VolumeUp>>>,
+    volume_down_actions: b::Query<&bei::Bindings, b::With<bei::Action<VolumeDown>>>,
+    bindings: b::Query<&bei::Binding>,
+    state: b::Res<b::State<GameState>>,
+    text: b::Single<(&mut b::Text2d, &mut b::Visibility), b::With<ControlsText>>,
+) {
+    let (mut text, mut visibility) = text.into_inner();
+    let visible = matches!(state.get(), GameState::Menu | GameState::Paused);
+    visibility.set_if_neq(if visible {
+        b::Visibility::Inherited
+    } else {
+        b::Visibility::Hidden
+    });
+    if !visible {
+        return;
+    }
+
+    let mut new_text = String::from("Controls:\n");
+    for (name, actions) in [
+        ("Move", move_actions.iter().collect::<Vec<_>>()),
+        ("Aim", aim_actions.iter().collect()),
+        ("Shoot", shoot_actions.iter().collect()),
+        ("Bomb", bomb_actions.iter().collect()),
+        ("Switch weapon", switch_weapon_actions.iter().collect()),
+        ("Pause", pause_actions.iter().collect()),
+        ("Back", escape_actions.iter().collect()),
+        ("Restart", restart_actions.iter().collect()),
+        ("Volume up", volume_up_actions.iter().collect()),
+        ("Volume down", volume_down_actions.iter().collect()),
+    ] {
+        let action_bindings: Vec<&bei::Binding> = actions
+            .into_iter()
+            .flat_map(|action_bindings| bindings.iter_many(action_bindings.iter()))
+            .collect();
+        if action_bindings.is_empty() {
+            continue;
+        }
+
+        let keys = action_bindings
+            .iter()
+            .filter_map(|binding| match binding {
+                bei::Binding::Keyboard { key, mod_keys } if mod_keys.is_empty() => {
+                    Some(key_label(*key))
+                }
+                bei::Binding::MouseButton { button, .. } => {
+                    Some(Cow::Owned(format!("Mouse {button:?}")))
+                }
+                _ => None,
+            })
+            .unique()
+            .join(" ");
+        let gamepad = action_bindings
+            .iter()
+            .filter_map(|binding| match binding {
+                bei::Binding::GamepadButton(button) => Some(gamepad_button_label(*button)),
+                bei::Binding::GamepadAxis(axis) => Some(gamepad_axis_label(*axis)),
+                _ => None,
+            })
+            .unique()
+            .join(", ");
+
+        let (preferred, other) = match *device {
+            LastInputDevice::KeyboardAndMouse => (keys, gamepad),
+            LastInputDevice::Gamepad => (gamepad, keys),
+        };
+        let line = if preferred.is_empty() {
+            other
+        } else {
+            preferred
+        };
+        _ = writeln!(new_text, "\n{name}");
+        if !line.is_empty() {
+            _ = writeln!(new_text, "{line}");
+        }
+    }
+
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
+
+fn key_label(key: b::KeyCode) -> Cow<'static, str> {
+    use b::KeyCode as K;
+    Cow::Borrowed(match key {
+        K::ArrowUp => "Up",
+        K::ArrowDown => "Down",
+        K::ArrowLeft => "Left",
+        K::ArrowRight => "Right",
+        K::Escape => "Esc",
+        K::ShiftLeft => "LShift",
+        K::ShiftRight => "RShift",
+        K::ControlLeft => "LCtrl",
+        K::ControlRight => "RCtrl",
+        K::AltLeft => "LAlt",
+        K::AltRight => "RAlt",
+        K::Backquote => "`",
+        K::Equal => "=",
+        K::Minus => "-",
+        K::NumpadAdd => "Num+",
+        K::NumpadSubtract => "Num-",
+        _ => {
+            // Debug names are like `KeyW` and `Digit1`; shorten them.
+            let name = format!("{key:?}");
+            return Cow::Owned(
+                name.strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name)
+                    .to_owned(),
+            );
+        }
+    })
+}
+
+fn gamepad_button_label(button: b::GamepadButton) -> Cow<'static, str> {
+    use b::GamepadButton as G;
+    Cow::Borrowed(match button {
+        G::South => "A",
+        G::East => "B",
+        G::West => "X",
+        G::North => "Y",
+        G::LeftTrigger => "LB",
+        G::LeftTrigger2 => "LT",
+        G::RightTrigger => "RB",
+        G::RightTrigger2 => "RT",
+        G::LeftThumb => "L3",
+        G::RightThumb => "R3",
+        G::DPadUp | G::DPadDown | G::DPadLeft | G::DPadRight => "D-pad",
+        G::Start => "Start",
+        G::Select => "Select",
+        other => return Cow::Owned(format!("{other:?}")),
+    })
+}
+
+fn gamepad_axis_label(axis: b::GamepadAxis) -> Cow<'static, str> {
+    use b::GamepadAxis as A;
+    Cow::Borrowed(match axis {
+        A::LeftStickX | A::LeftStickY => "Left stick",
+        A::RightStickX | A::RightStickY => "Right stick",
+        A::LeftZ => "LT",
+        A::RightZ => "RT",
+        other => return Cow::Owned(format!("{other:?}")),
+    })
+}
@@ -0,0 +1,200 @@
+use std::f32::consts::PI;
+
+use avian2d::prelude as p;
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::math::{Vec2, Vec3Swizzles as _, vec2};
+use bevy::prelude as b;
+use rand::RngExt as _;
+use rand_distr::Distribution as _;
+
+use crate::settings::Settings;
+use crate::{
+    GameAssets, GameState, Lifetime, PLAYFIELD_LAYERS, PLAYFIELD_RECT, Player, SpriteId, Star,
+    Team, Zees,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// A scripted sequence in progress. While this resource exists, the cues of its timeline are
+/// performed in order, and the end-of-game UI is hidden.
+///
+/// Cutscenes are driven from `Update` using virtual time, so they run even though gameplay
+/// systems are not running.
+#[derive(Debug, b::Resource)]
+pub(crate) struct Cutscene {
+    /// Cues and the time, in seconds from the start, at which each happens.
+    timeline: &'static [(f32, Cue)],
+    elapsed: f32,
+    /// Index in `timeline` of the next cue to perform.
+    next_cue: usize,
+
+    /// Current multiplier of star velocity.
+    starfield_warp: f32,
+    /// Multiplier of star velocity which `starfield_warp` is easing towards.
+    starfield_warp_target: f32,
+}
+
+/// Something that happens at a point in a [`Cutscene`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Cue {
+    /// Every entity on [`Team::Enemy`] is despawned, leaving sparkles behind.
+    SparkleEnemies,
+    /// Stars’ velocities are smoothly changed to this multiple of their normal velocity.
+    StarfieldWarp(f32),
+    /// The player ship accelerates upward until it is off the playfield.
+    FlyPlayerOff,
+    /// The cutscene is over.
+    End,
+}
+
+/// Component for an entity being moved by a [`Cutscene`] instead of by gameplay.
+#[derive(Debug, b::Component)]
+struct FlyingOff {
+    velocity: Vec2,
+}
+
+pub(crate) const WIN_SEQUENCE: &[(f32, Cue)] = &[
+    (0.0, Cue::SparkleEnemies),
+    (0.4, Cue::StarfieldWarp(12.0)),
+    (0.9, Cue::FlyPlayerOff),
+    (2.6, Cue::StarfieldWarp(1.0)),
+    (3.6, Cue::End),
+];
+
+pub(crate) struct CutscenePlugin;
+
+impl b::Plugin for CutscenePlugin {
+    fn build(&self, app: &mut b::App) {
+        app.add_systems(
+            b::Update,
+            (run_cutscene_system, fly_off_system)
+                .chain()
+                .run_if(b::resource_exists::<Cutscene>),
+        )
+        // in case the player skips to the menu
+        .add_systems(b::OnExit(GameState::WinOrGameOver), end_cutscene);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl Cutscene {
+    pub fn new(timeline: &'static [(f32, Cue)]) -> Self {
+        Self {
+            timeline,
+            elapsed: 0.0,
+            next_cue: 0,
+            starfield_warp: 1.0,
+            starfield_warp_target: 1.0,
+        }
+    }
+}
+
+fn run_cutscene_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    mut cutscene: b::ResMut<Cutscene>,
+    assets: b::Res<GameAssets>,
+    settings: b::Res<Settings>,
+    team_query: b::Query<(b::Entity, &Team, &b::Transform, b::Has<Player>)>,
+    stars: b::Query<(&Star, &mut p::LinearVelocity)>,
+) {
+    let dt = time.delta_secs();
+    cutscene.elapsed += dt;
+
+    while let Some(&(cue_time, cue)) = cutscene.timeline.get(cutscene.next_cue)
+        && cue_time <= cutscene.elapsed
+    {
+        cutscene.next_cue += 1;
+        match cue {
+            Cue::SparkleEnemies => {
+                // sparkles are only for show, so they do not use `GameRng`
+                let rng = &mut rand::rng();
+                for (entity, &team, transform, _) in team_query {
+                    if team == Team::Enemy {
+                        if !settings.low_spec {
+                            spawn_sparkles(&mut commands, rng, &assets, transform.translation.xy());
+                        }
+                        commands.entity(entity).despawn();
+                    }
+                }
+            }
+            Cue::StarfieldWarp(warp) => cutscene.starfield_warp_target = warp,
+            Cue::FlyPlayerOff => {
+                for (entity, _, _, is_player) in team_query {
+                    if is_player {
+                        commands.entity(entity).insert(FlyingOff {
+                            velocity: Vec2::ZERO,
+                        });
+                    }
+                }
+            }
+            Cue::End => {
+                cutscene.starfield_warp = cutscene.starfield_warp_target;
+                commands.remove_resource::<Cutscene>();
+            }
+        }
+    }
+
+    // Ease starfield speed.
+    // This is applied to every star every frame so that newly spawned stars keep up.
+    let warp_difference = cutscene.starfield_warp_target - cutscene.starfield_warp;
+    if warp_difference.abs() < 0.01 {
+        cutscene.starfield_warp = cutscene.starfield_warp_target;
+    } else {
+        cutscene.starfield_warp += warp_difference * (1.0 - 0.02f32.powf(dt));
+    }
+    for (star, mut velocity) in stars {
+        velocity.0 = star.base_velocity * cutscene.starfield_warp;
+    }
+}
+
+/// Spawns purely decorative sparkles, which fly apart from `position` and then vanish.
+fn spawn_sparkles(
+    commands: &mut b::Commands,
+    rng: &mut impl rand::Rng,
+    assets: &GameAssets,
+    position: Vec2,
+) {
+    for _ in 0..rng.random_range(4u32..8) {
+        let direction = Vec2::from(rand_distr::UnitDisc.sample(rng));
+        commands.spawn((
+            b::Sprite {
+                image: assets.sprite(SpriteId::Star),
+                color: b::Color::srgb(1.0, 1.0, 0.6),
+                ..b::default()
+            },
+            b::Transform::from_translation((position + direction * 6.0).extend(Zees::Pickup.z()))
+                .with_rotation(b::Quat::from_rotation_z(
+                    rng.random_range(0.0f32..=PI * 2.0),
+                )),
+            PLAYFIELD_LAYERS,
+            // no collider, so that they do not take part in collisions
+            p::RigidBody::Kinematic,
+            p::LinearVelocity(direction * 40.0 + vec2(0.0, 20.0)),
+            Lifetime(rng.random_range(0.4..=0.8)),
+        ));
+    }
+}
+
+fn fly_off_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    query: b::Query<(b::Entity, &mut FlyingOff, &mut b::Transform)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut flying, mut transform) in query {
+        flying.velocity.y += 900.0 * dt;
+        transform.translation += (flying.velocity * dt).extend(0.0);
+        if transform.translation.y > PLAYFIELD_RECT.max.y + 40.0 {
+            commands
+                .entity(entity)
+                .remove::<FlyingOff>()
+                .insert(b::Visibility::Hidden);
+        }
+    }
+}
+
+fn end_cutscene(mut commands: b::Commands) {
+    commands.remove_resource::<Cutscene>();
+}
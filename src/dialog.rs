@@ -0,0 +1,167 @@
+use bevy::math::vec2;
+use bevy::prelude as b;
+use bevy::utils::default;
+
+use crate::game_rng::{self, ChosenSeed};
+use crate::text_input::{SubmitText, TextInput};
+use crate::{ButtonAction, GameAssets, GameState, button_bundle};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Root entity of a modal dialog, which covers the rest of the UI and blocks interaction with it
+/// until the dialog is closed.
+#[derive(Debug, b::Component)]
+pub(crate) struct ModalDialog;
+
+/// Event which opens a dialog asking whether to abandon the current run.
+#[derive(Debug, b::Event)]
+pub(crate) struct AskAbandonRun;
+
+/// Event which opens a dialog asking for a seed to start a run with.
+#[derive(Debug, b::Event)]
+pub(crate) struct AskSeed;
+
+/// The [`TextInput`] in the dialog opened by [`AskSeed`].
+#[derive(Debug, b::Component)]
+pub(crate) struct SeedInput;
+
+/// Event which closes all [`ModalDialog`]s.
+#[derive(Debug, b::Event)]
+pub(crate) struct CloseDialogs;
+
+/// Event which ends the current run without a win or loss and returns to the menu.
+/// The game entities are despawned by [`crate::despawn_game()`] on the state transition.
+#[derive(Debug, b::Event)]
+pub(crate) struct QuitToMenu;
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn ask_abandon_run_observer(
+    _event: b::On<AskAbandonRun>,
+    mut commands: b::Commands,
+    assets: b::Res<GameAssets>,
+    existing_dialogs: b::Query<(), b::With<ModalDialog>>,
+) {
+    if !existing_dialogs.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        dialog_root_bundle(),
+        b::children![
+            dialog_text_bundle(&assets, "Abandon run?"),
+            (
+                b::Node {
+                    flex_direction: b::FlexDirection::Row,
+                    column_gap: b::px(10),
+                    ..default()
+                },
+                b::children![
+                    button_bundle(&assets, "Yes", ButtonAction::QuitToMenu),
+                    button_bundle(&assets, "No", ButtonAction::CloseDialog),
+                ],
+            ),
+        ],
+    ));
+}
+
+pub(crate) fn ask_seed_observer(
+    _event: b::On<AskSeed>,
+    mut commands: b::Commands,
+    assets: b::Res<GameAssets>,
+    existing_dialogs: b::Query<(), b::With<ModalDialog>>,
+) {
+    if !existing_dialogs.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        dialog_root_bundle(),
+        b::children![
+            dialog_text_bundle(&assets, "Seed to play:"),
+            (
+                SeedInput,
+                TextInput::new(game_rng::SEED_DIGITS, |ch| ch.is_ascii_hexdigit()),
+                dialog_text_bundle(&assets, ""),
+            ),
+            button_bundle(&assets, "Cancel", ButtonAction::CloseDialog),
+        ],
+    ));
+}
+
+/// Starts a run with the seed entered in the [`SeedInput`], if it is valid.
+pub(crate) fn play_seed_observer(
+    event: b::On<SubmitText>,
+    mut commands: b::Commands,
+    seed_inputs: b::Query<(), b::With<SeedInput>>,
+    mut next_state: b::ResMut<b::NextState<GameState>>,
+) {
+    if !seed_inputs.contains(event.input) {
+        return;
+    }
+    let Some(seed) = game_rng::parse_seed(&event.value) else {
+        return;
+    };
+    commands.insert_resource(ChosenSeed(seed));
+    commands.trigger(CloseDialogs);
+    next_state.set(GameState::Playing);
+}
+
+/// Full-window backdrop and layout for the contents of a [`ModalDialog`].
+fn dialog_root_bundle() -> impl b::Bundle {
+    (
+        ModalDialog,
+        b::Node {
+            width: b::percent(100),
+            height: b::percent(100),
+            flex_direction: b::FlexDirection::Column,
+            align_items: b::AlignItems::Center,
+            justify_content: b::JustifyContent::Center,
+            row_gap: b::px(20),
+            ..default()
+        },
+        b::BackgroundColor(b::Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        b::GlobalZIndex(10),
+        bevy::ui::FocusPolicy::Block,
+    )
+}
+
+fn dialog_text_bundle(assets: &GameAssets, text: &str) -> impl b::Bundle {
+    (
+        b::Text::new(text),
+        b::TextFont {
+            font: assets.ui_font.clone(),
+            font_size: 27.0,
+            ..default()
+        },
+        b::TextColor(b::Color::srgb(0.9, 0.9, 0.9)),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+    )
+}
+
+pub(crate) fn close_dialogs_observer(
+    _event: b::On<CloseDialogs>,
+    mut commands: b::Commands,
+    dialogs: b::Query<b::Entity, b::With<ModalDialog>>,
+) {
+    for dialog in dialogs {
+        commands.entity(dialog).despawn();
+    }
+}
+
+pub(crate) fn quit_to_menu_observer(
+    _event: b::On<QuitToMenu>,
+    mut commands: b::Commands,
+    mut next_state: b::ResMut<b::NextState<GameState>>,
+) {
+    commands.trigger(CloseDialogs);
+    next_state.set(GameState::Menu);
+}
+
+/// Closes dialogs which are left open when the state changes.
+pub(crate) fn close_dialogs(mut commands: b::Commands) {
+    commands.trigger(CloseDialogs);
+}
@@ -0,0 +1,197 @@
+use bevy::ecs::change_detection::DetectChangesMut as _;
+use bevy::prelude as b;
+
+use crate::ButtonAction;
+use crate::quantity::{QuantityDelta, QuantityKind};
+use crate::run_stats::RunEvent;
+
+// -------------------------------------------------------------------------------------------------
+
+/// How hard the game is, chosen on the menu before starting a run.
+///
+/// The game’s balance constants are tuned for [`Difficulty::Normal`];
+/// the other levels scale a few of them up or down.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, b::Resource)]
+pub(crate) enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/// Adjustment of the game to how well the player is doing in the current run, on top of the
+/// chosen [`Difficulty`], so that players who are struggling get some relief and players who
+/// are cruising get more to do.
+///
+/// Tracks recent kills and damage taken by [`adaptive_difficulty_system`], and scales the time
+/// between enemy formations and between enemy shots by [`Self::cooldown_scale()`].
+#[derive(Debug, b::Resource)]
+pub(crate) struct AdaptiveDifficulty {
+    /// Recent kills per second, smoothed over [`ADAPTIVE_WINDOW`].
+    kill_rate: f32,
+    /// Recent damage taken per second, smoothed over [`ADAPTIVE_WINDOW`].
+    damage_rate: f32,
+    /// How well the player is doing, from -1 (badly) to 1 (well).
+    performance: f32,
+}
+
+/// Game time over which [`AdaptiveDifficulty`] averages the player’s performance.
+const ADAPTIVE_WINDOW: f32 = 20.0;
+/// Kills per second of a player for whom the game is balanced as it is.
+/// Twice this counts as doing well.
+const PAR_KILL_RATE: f32 = 0.5;
+/// Damage taken per second which counts as doing badly.
+const PAR_DAMAGE_RATE: f32 = 0.15;
+/// Damage a lost life counts as.
+const LIFE_LOST_DAMAGE: f32 = 5.0;
+/// Rate at which [`AdaptiveDifficulty::performance`] follows changes, per second.
+/// Slow, so that the player does not notice the game changing under them.
+const ADAPTIVE_RESPONSE: f32 = 0.1;
+/// Most by which [`AdaptiveDifficulty`] shortens or lengthens cooldowns, as a fraction.
+const ADAPTIVE_RANGE: f32 = 0.25;
+
+/// Border color of the menu button for the [`Difficulty`] which is not selected.
+const UNSELECTED_BORDER: b::Color = b::Color::srgb(0.35, 0.35, 0.35);
+
+// -------------------------------------------------------------------------------------------------
+
+impl Difficulty {
+    pub const ALL: [Self; 3] = [Self::Easy, Self::Normal, Self::Hard];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// Multiplier for the health of enemies.
+    pub fn enemy_health(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.4,
+        }
+    }
+
+    /// Multiplier for the time between enemy formations.
+    pub fn spawn_interval(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.4,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.7,
+        }
+    }
+
+    /// Multiplier for the speed of enemy bullets.
+    pub fn enemy_bullet_speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.2,
+        }
+    }
+
+    /// Multiplier for increases in Fever.
+    pub fn fever_gain(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    /// Scales the health of an enemy or enemy part, which stays at least 1.
+    pub fn scale_enemy_health(self, health: u8) -> u8 {
+        (f32::from(health) * self.enemy_health())
+            .round()
+            .clamp(1.0, f32::from(u8::MAX)) as u8
+    }
+
+    /// Scales a [`QuantityDelta`] as it will actually be applied, which is by
+    /// [`Self::fever_gain()`] if it increases Fever.
+    pub fn scale_delta(self, delta: QuantityDelta) -> QuantityDelta {
+        if delta.quantity == QuantityKind::Fever && delta.amount > 0.0 {
+            QuantityDelta {
+                amount: delta.amount * self.fever_gain(),
+                ..delta
+            }
+        } else {
+            delta
+        }
+    }
+}
+
+impl Default for AdaptiveDifficulty {
+    /// Assumes the player is doing as expected until they show otherwise.
+    fn default() -> Self {
+        Self {
+            kill_rate: PAR_KILL_RATE,
+            damage_rate: 0.0,
+            performance: 0.0,
+        }
+    }
+}
+
+impl AdaptiveDifficulty {
+    /// Multiplier for the time between enemy formations and between enemy shots.
+    pub fn cooldown_scale(&self) -> f32 {
+        1.0 - self.performance * ADAPTIVE_RANGE
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn reset_adaptive_difficulty(mut adaptive: b::ResMut<AdaptiveDifficulty>) {
+    *adaptive = AdaptiveDifficulty::default();
+}
+
+/// Updates [`AdaptiveDifficulty`] from this tick’s kills and damage.
+pub(crate) fn adaptive_difficulty_system(
+    time: b::Res<b::Time>,
+    mut run_events: b::MessageReader<RunEvent>,
+    mut adaptive: b::ResMut<AdaptiveDifficulty>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    let mut kills = 0.0;
+    let mut damage = 0.0;
+    for event in run_events.read() {
+        match event {
+            RunEvent::EnemyKilled => kills += 1.0,
+            RunEvent::PlayerDamaged => damage += 1.0,
+            RunEvent::LifeLost => damage += LIFE_LOST_DAMAGE,
+            RunEvent::ShotFired(_) | RunEvent::PickupCollected(_) => {}
+        }
+    }
+
+    // Exponential moving averages of the rates.
+    let decay = (dt / ADAPTIVE_WINDOW).min(1.0);
+    adaptive.kill_rate += (kills / dt - adaptive.kill_rate) * decay;
+    adaptive.damage_rate += (damage / dt - adaptive.damage_rate) * decay;
+
+    let target =
+        (adaptive.kill_rate / PAR_KILL_RATE - 1.0 - adaptive.damage_rate / PAR_DAMAGE_RATE)
+            .clamp(-1.0, 1.0);
+    adaptive.performance += (target - adaptive.performance) * (dt * ADAPTIVE_RESPONSE).min(1.0);
+}
+
+/// Outlines the menu button for the current [`Difficulty`] and dims the others.
+pub(crate) fn difficulty_button_system(
+    difficulty: b::Res<Difficulty>,
+    buttons: b::Query<(&ButtonAction, &mut b::BorderColor)>,
+) {
+    for (action, mut border) in buttons {
+        if let &ButtonAction::SetDifficulty(button_difficulty) = action {
+            border.set_if_neq(b::BorderColor::all(if button_difficulty == *difficulty {
+                b::Color::WHITE
+            } else {
+                UNSELECTED_BORDER
+            }));
+        }
+    }
+}
@@ -1,247 +1,769 @@
+use std::f32::consts::PI;
+
 use avian2d::prelude::{self as p};
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::ecs::spawn::{SpawnIter, SpawnRelated as _, SpawnableList};
 use bevy::math::{Vec2, Vec3Swizzles as _, vec2};
 use bevy::prelude as b;
 use rand::RngExt as _;
 use rand::seq::IndexedRandom;
 use rand_distr::Distribution;
 
-use crate::bullets_and_targets::Pattern;
-use crate::pickup::PickupSpawnType;
-use crate::quantity::{Coherence, Fervor, Quantity};
+use crate::difficulty::{AdaptiveDifficulty, Difficulty};
+use crate::game_mode::GameMode;
+use crate::game_rng::GameRng;
+use crate::pickup::Drops;
+use crate::prefab::{EnemyPrefab, PartPrefab, PickupPrefabs, Prefabs};
+use crate::quantity::{Coherence, Fervor, Quantities};
+use crate::run_stats::RunStats;
+use crate::score::ScoreValue;
 use crate::{
-    Gun, Lifetime, MyAssets, PLAYFIELD_LAYERS, PLAYFIELD_RECT, Pickup, Team, Zees,
-    bullets_and_targets::Attackable,
+    Gun, Lifetime, PLAYFIELD_LAYERS, PLAYFIELD_RECT, Pickup, Team, Zees,
+    bullets_and_targets::{Attackable, Part},
 };
 
 // -------------------------------------------------------------------------------------------------
 
 /// Component attached to a (currently) singleton entity that spawns enemies in a pattern.
-#[derive(Debug, b::Component)]
+///
+/// If the spawner is [`Cosmetic`], so are the enemies it spawns.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) struct EnemySpawner {
     pub cooldown: f32,
 }
 
+/// How much an enemy contributes to the on-screen threat which the gameplay [`EnemySpawner`]
+/// keeps within its budget.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct Threat(pub f32);
+
+/// Total [`Threat`] the gameplay spawner aims for at zero coherence and fervor.
+const BASE_THREAT_BUDGET: f32 = 6.0;
+/// Threat budget added at full fervor.
+const FERVOR_THREAT_BUDGET: f32 = 10.0;
+/// Threat budget added at full coherence.
+const COHERENCE_THREAT_BUDGET: f32 = 4.0;
+/// Shortest time between formations from the gameplay spawner, so that a formation is counted
+/// before the next is chosen and formations do not arrive on top of each other.
+const MIN_SPAWN_INTERVAL: f32 = 2.0;
+/// Time between formations from a [`Cosmetic`] spawner.
+const COSMETIC_SPAWN_INTERVAL: f32 = 7.0;
+
+/// Marks entities which are decoration for the menu background and do not participate in
+/// gameplay: cosmetic enemies don’t shoot and can’t be shot.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct Cosmetic;
+
+/// The kinds of ordinary enemy, each with its own [`EnemyPrefab`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum EnemyKind {
+    /// The basic enemy.
+    Grunt,
+    /// Slow, tough, and dangerous.
+    Tank,
+    /// Small and fragile, and comes in numbers.
+    Swarmer,
+    /// Aims from a distance and does not dive.
+    Sniper,
+}
+
+/// What an enemy ship does after its time on station; chosen by [`EnemyPrefab::ai`].
+#[derive(Clone, Copy, Debug, b::Reflect, serde::Deserialize)]
+pub(crate) enum AiStyle {
+    /// Fly straight down, firing.
+    Dive,
+    /// Fly down in a zigzag, firing.
+    Weave,
+    /// Fly back up and out of the playfield, without firing.
+    Retreat,
+}
+
 /// Component adding enemy ship behaviors.
-#[derive(Debug, b::Component)]
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) struct EnemyShipAi {
     /// What to do next.
     state: AiState,
+    /// What to do after leaving the station.
+    style: AiStyle,
     /// Where the ship moves to after being spawned.
     station: Vec2,
     /// Remaining time the ship stays on station before moving.
     time_on_station: f32,
+    /// Speed of the movement after leaving the station.
+    dive_speed: f32,
+    /// Spring constant of the approach to the station; see [`EnemyPrefab::approach_stiffness`].
+    approach_stiffness: f32,
+    /// Damping of the approach to the station; see [`EnemyPrefab::approach_damping`].
+    approach_damping: f32,
+    /// Total time the ship has spent inside the playfield, for [`FIRE_GRACE_PERIOD`].
+    time_on_screen: f32,
+}
+
+/// Time an enemy must have been visible before it may fire,
+/// so that bullets never come from a ship the player hasn’t had a chance to see.
+const FIRE_GRACE_PERIOD: f32 = 1.0;
+
+/// Distance outside the playfield at which an enemy which has left it is despawned.
+const DEPARTURE_MARGIN: f32 = 32.0;
+
+/// Time for one side-to-side cycle of [`AiState::Weave`].
+const WEAVE_PERIOD: f32 = 1.2;
+/// Greatest sideways speed of [`AiState::Weave`], relative to its downward speed.
+const WEAVE_WIDTH: f32 = 1.0;
+
+/// A curve which an enemy ship follows from where it was spawned to its station, instead of
+/// accelerating straight there in [`AiState::GoToStation`].
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct Path {
+    /// Control points of a cubic Bézier curve from the spawn position to the station.
+    points: [Vec2; 4],
+    /// Greatest distance of the side-to-side wiggle added to the curve.
+    wiggle_amplitude: f32,
+    /// Number of half-waves of the wiggle over the whole path; whole, so that it ends centered.
+    wiggle_half_waves: f32,
+    /// Time taken to fly the whole path.
+    duration: f32,
+    /// Time spent flying it so far.
+    elapsed: f32,
 }
 
+/// The shape of the entry [`Path`]s of a formation.
+///
+/// Every ship in a formation is given the same shape, so they fly in along parallel curves,
+/// one after another as their [`AiState::InitialWait`]s end.
 #[derive(Clone, Copy, Debug)]
+enum PathShape {
+    /// No [`Path`]; accelerate straight to the station.
+    Direct,
+    /// Dive past the station, swing out to one side (-1 is left and 1 is right), and rise
+    /// into place.
+    Swoop { side: f32 },
+    /// Fly straight to the station while weaving from side to side.
+    Snake { amplitude: f32, half_waves: f32 },
+}
+
+/// Height of the lowest point of a [`PathShape::Swoop`], as a fraction of the playfield height
+/// above its bottom.
+const SWOOP_BOTTOM: f32 = 0.4;
+
+#[derive(Clone, Copy, Debug, b::Reflect)]
 enum AiState {
     InitialWait(f32),
     GoToStation,
     WaitAtStation,
     Dive,
+    /// Diving in a zigzag, for this long so far.
+    Weave(f32),
+    Retreat,
 }
 
 // -------------------------------------------------------------------------------------------------
 
+/// A formation of enemies, drawn with a letter for each enemy and space for no enemy:
+/// `X` for [`EnemyKind::Grunt`], `T` for [`EnemyKind::Tank`], `S` for [`EnemyKind::Swarmer`],
+/// and `N` for [`EnemyKind::Sniper`].
+/// The top row is the top of the formation.
+pub(crate) type SpawnPattern = [[u8; PATTERN_WIDTH]; PATTERN_HEIGHT];
+pub(crate) const PATTERN_WIDTH: usize = 10;
+pub(crate) const PATTERN_HEIGHT: usize = 4;
+
+/// All the [`SpawnPattern`]s the [`EnemySpawner`] chooses from:
+/// the built-in ones plus any loaded from mods.
+#[derive(Debug, b::Resource)]
+pub(crate) struct SpawnPatterns(pub Vec<SpawnPattern>);
+
+impl Default for SpawnPatterns {
+    fn default() -> Self {
+        Self(SPAWN_PATTERNS.to_vec())
+    }
+}
+
+impl EnemyKind {
+    /// The kind of enemy drawn as `ch` in a [`SpawnPattern`], or [`None`] if it is not an enemy.
+    pub fn from_pattern_char(ch: u8) -> Option<Self> {
+        match ch {
+            b'X' => Some(EnemyKind::Grunt),
+            b'T' => Some(EnemyKind::Tank),
+            b'S' => Some(EnemyKind::Swarmer),
+            b'N' => Some(EnemyKind::Sniper),
+            _ => None,
+        }
+    }
+}
+
+const SPAWN_PATTERNS: [SpawnPattern; 11] = [
+    [
+        *b" XX  XX   ",
+        *b"   XX  XX ",
+        *b"          ",
+        *b"          ",
+    ],
+    [
+        *b"          ",
+        *b" XXXXXXXX ",
+        *b"          ",
+        *b"          ",
+    ],
+    [
+        *b"X        X",
+        *b" X      X ",
+        *b"  X    X  ",
+        *b"   X  X   ",
+    ],
+    [
+        *b" X X  X X ",
+        *b"  X    X  ",
+        *b" X X  X X ",
+        *b"  X    X  ",
+    ],
+    [
+        *b" XX       ",
+        *b"X  X      ",
+        *b"X  X      ",
+        *b" XX       ",
+    ],
+    [
+        *b"    XX    ",
+        *b"   XXXX   ",
+        *b"   XXXX   ",
+        *b"    XX    ",
+    ],
+    [
+        *b"       XX ",
+        *b"      X  X",
+        *b"      X  X",
+        *b"       XX ",
+    ],
+    [
+        *b"          ",
+        *b"  X T  X  ",
+        *b"          ",
+        *b"          ",
+    ],
+    [
+        *b"SS      SS",
+        *b" SS    SS ",
+        *b"  SS  SS  ",
+        *b"          ",
+    ],
+    [
+        *b" N      N ",
+        *b"          ",
+        *b"   XXXX   ",
+        *b"          ",
+    ],
+    [
+        *b"    NN    ",
+        *b"  S    S  ",
+        *b" S  TT  S ",
+        *b"  S    S  ",
+    ],
+];
+
 /// Spawns enemies based on [`EnemySpawner`] state.
+///
+/// A gameplay spawner acts as a director: it spawns a new formation whenever the total [`Threat`]
+/// of live enemies is below a budget, which grows with coherence and fervor, so that the pace
+/// keeps up with how fast the player destroys enemies.
+/// A [`Cosmetic`] spawner simply spawns on a fixed cadence.
+#[allow(
+    clippy::too_many_arguments,
+    clippy::type_complexity,
+    reason = "the threat budget depends on the quantities, mode, and live enemies"
+)]
 pub(crate) fn spawn_enemies_system(
     mut commands: b::Commands,
     time: b::Res<b::Time>,
-    spawners: b::Query<&mut EnemySpawner>,
-    coherence: b::Single<&Quantity, b::With<Coherence>>,
-    fervor: b::Single<&Quantity, b::With<Fervor>>,
-    assets: b::Res<crate::MyAssets>,
-) {
-    const SPAWN_PATTERNS: [[[u8; 10]; 4]; 7] = [
-        [
-            *b" XX  XX   ",
-            *b"   XX  XX ",
-            *b"          ",
-            *b"          ",
-        ],
-        [
-            *b"          ",
-            *b" XXXXXXXX ",
-            *b"          ",
-            *b"          ",
-        ],
-        [
-            *b"X        X",
-            *b" X      X ",
-            *b"  X    X  ",
-            *b"   X  X   ",
-        ],
-        [
-            *b" X X  X X ",
-            *b"  X    X  ",
-            *b" X X  X X ",
-            *b"  X    X  ",
-        ],
-        [
-            *b" XX       ",
-            *b"X  X      ",
-            *b"X  X      ",
-            *b" XX       ",
-        ],
-        [
-            *b"    XX    ",
-            *b"   XXXX   ",
-            *b"   XXXX   ",
-            *b"    XX    ",
-        ],
-        [
-            *b"       XX ",
-            *b"      X  X",
-            *b"      X  X",
-            *b"       XX ",
-        ],
-    ];
+    spawners: b::Query<(&mut EnemySpawner, b::Has<Cosmetic>)>,
+    live_enemies: b::Query<(&Team, &Threat), (b::With<Attackable>, b::Without<Cosmetic>)>,
+    quantities: Quantities,
+    prefabs: Prefabs,
+    patterns: b::Res<SpawnPatterns>,
+    mut game_rng: b::ResMut<GameRng>,
+    difficulty: b::Res<Difficulty>,
+    adaptive: b::Res<AdaptiveDifficulty>,
+    game_mode: b::Res<GameMode>,
+    stats: b::Res<RunStats>,
+) -> b::Result {
+    let coherence = quantities.get::<Coherence>()?;
+    let fervor = quantities.get::<Fervor>()?;
 
     let dt = time.delta_secs();
-    let rng = &mut rand::rng();
-    let spawn_range_rect = PLAYFIELD_RECT.inflate(-20.0);
+    let rng = &mut **game_rng;
+
+    let live_threat: f32 = live_enemies
+        .iter()
+        .filter(|&(&team, _)| team == Team::Enemy)
+        .map(|(_, threat)| threat.0)
+        .sum();
+    let threat_budget = BASE_THREAT_BUDGET
+        + fervor.effective_value() * FERVOR_THREAT_BUDGET
+        + coherence.effective_value() * COHERENCE_THREAT_BUDGET
+        + game_mode.extra_threat_budget(stats.elapsed);
 
-    for mut spawner in spawners {
+    for (mut spawner, cosmetic) in spawners {
         let EnemySpawner { cooldown }: &mut EnemySpawner = &mut *spawner;
+        *cooldown -= dt;
+        if !cosmetic && live_threat == 0.0 {
+            // The field is empty, whether because the player destroyed everything or because
+            // the enemies left; don’t leave the player waiting.
+            *cooldown = (*cooldown).min(0.0);
+        }
         if *cooldown > 0.0 {
-            // cooldown faster, i.e. spawn more often, when coherence & fervor is high
-            let delta =
-                (1.0 + fervor.effective_value() * 1.25 + coherence.effective_value() * 0.5) * dt;
-            *cooldown = (*cooldown - delta).max(0.0);
+            continue;
+        }
+        if cosmetic {
+            // Add rather than set, so the overshoot is subtracted from the next wait
+            // and the spawn rate does not depend on the tick rate.
+            *cooldown += COSMETIC_SPAWN_INTERVAL;
+        } else if live_threat < threat_budget {
+            *cooldown = MIN_SPAWN_INTERVAL
+                * difficulty.spawn_interval()
+                * adaptive.cooldown_scale()
+                * game_mode.spawn_interval(stats.elapsed);
         } else {
-            *cooldown = 7.0;
-
-            let mut offscreen_direction = Vec2::from(rand_distr::UnitCircle.sample(rng));
-            // limit to upper half-circle
-            offscreen_direction.y = offscreen_direction.y.abs();
-            let offscreen_offset = offscreen_direction * PLAYFIELD_RECT.size().x * 0.2
-                + vec2(0.0, PLAYFIELD_RECT.max.y);
-
-            let pattern_to_spawn: &[[u8; _]; _] = SPAWN_PATTERNS.choose(rng).unwrap();
-
-            // scales `i` below down to a 0-1 range, inclusive
-            let index_scale_factors = vec2(
-                ((pattern_to_spawn[0].len() - 1) as f32).recip(),
-                ((pattern_to_spawn.len() - 1) as f32).recip(),
-            );
-
-            let pattern_spacing = spawn_range_rect.size().x * index_scale_factors.x;
-
-            // Choose how much [`AiState::InitialWait`] time is used depending on the x and y index
-            let wait_time_scale = vec2(rng.random_range(-3.0..=3.0), rng.random_range(-3.0..=0.0));
-            let wait_time_offset = vec2(
-                offset_from_signed_scale(wait_time_scale.x),
-                offset_from_signed_scale(wait_time_scale.y),
-            );
-
-            for (yi, row) in pattern_to_spawn.iter().enumerate() {
-                for (xi, &ch) in row.iter().enumerate() {
-                    let x = spawn_range_rect.min.x + xi as f32 * pattern_spacing;
-                    let y = spawn_range_rect.max.y - yi as f32 * pattern_spacing;
-
-                    let wait_times = wait_time_offset
-                        + vec2(xi as f32, yi as f32) * wait_time_scale * index_scale_factors;
-                    let wait_time = wait_times.x + wait_times.y;
-
-                    match ch {
-                        b' ' => {}
-                        b'X' => {
-                            commands.spawn(enemy_bundle(
-                                &assets,
+            // wait for the player to destroy some enemies
+            *cooldown = 0.0;
+            continue;
+        }
+
+        let Some(chosen_pattern) = patterns.0.choose(rng) else {
+            continue;
+        };
+        let pattern_to_spawn = vary_pattern(rng, chosen_pattern);
+        spawn_pattern(
+            &mut commands,
+            rng,
+            &prefabs,
+            &pattern_to_spawn,
+            cosmetic,
+            *difficulty,
+        )?;
+    }
+    Ok(())
+}
+
+/// Position in the playfield of the station of an enemy at `(xi, yi)` in a [`SpawnPattern`].
+pub(crate) fn pattern_cell_position(xi: usize, yi: usize) -> Vec2 {
+    let spawn_range_rect = PLAYFIELD_RECT.inflate(-20.0);
+    let pattern_spacing = spawn_range_rect.size().x / (PATTERN_WIDTH - 1) as f32;
+    vec2(
+        spawn_range_rect.min.x + xi as f32 * pattern_spacing,
+        spawn_range_rect.max.y - yi as f32 * pattern_spacing,
+    )
+}
+
+/// Spawns the enemies of one formation, which fly in from a random direction above the playfield.
+pub(crate) fn spawn_pattern(
+    commands: &mut b::Commands,
+    rng: &mut impl rand::Rng,
+    prefabs: &Prefabs,
+    pattern_to_spawn: &SpawnPattern,
+    cosmetic: bool,
+    difficulty: Difficulty,
+) -> b::Result {
+    let pickups = prefabs.pickups()?;
+
+    let mut offscreen_direction = Vec2::from(rand_distr::UnitCircle.sample(rng));
+    // limit to upper half-circle
+    offscreen_direction.y = offscreen_direction.y.abs();
+    let offscreen_offset =
+        offscreen_direction * PLAYFIELD_RECT.size().x * 0.2 + vec2(0.0, PLAYFIELD_RECT.max.y);
+
+    // scales `i` below down to a 0-1 range, inclusive
+    let index_scale_factors = vec2(
+        ((PATTERN_WIDTH - 1) as f32).recip(),
+        ((PATTERN_HEIGHT - 1) as f32).recip(),
+    );
+
+    // Choose how much [`AiState::InitialWait`] time is used depending on the x and y index.
+    // Usually the bottom rows arrive first, but sometimes the top.
+    let wait_time_scale = vec2(
+        rng.random_range(-3.0..=3.0),
+        rng.random_range(-3.0..=0.0) * if rng.random_bool(0.25) { -1.0 } else { 1.0 },
+    );
+    let wait_time_offset = vec2(
+        offset_from_signed_scale(wait_time_scale.x),
+        offset_from_signed_scale(wait_time_scale.y),
+    );
+    let path_shape = PathShape::random(rng);
+
+    for (yi, row) in pattern_to_spawn.iter().enumerate() {
+        for (xi, &ch) in row.iter().enumerate() {
+            let wait_times = wait_time_offset
+                + vec2(xi as f32, yi as f32) * wait_time_scale * index_scale_factors;
+            let wait_time = wait_times.x + wait_times.y;
+
+            match EnemyKind::from_pattern_char(ch) {
+                None => {}
+                Some(kind) => {
+                    let enemy = prefabs.enemy(kind)?;
+                    let station_position = pattern_cell_position(xi, yi);
+                    let spawn_position = station_position + offscreen_offset;
+                    let mut entity = if cosmetic {
+                        commands.spawn((
+                            Cosmetic,
+                            enemy_ship_bundle(
+                                enemy,
                                 wait_time,
-                                vec2(x, y) + offscreen_offset,
-                                vec2(x, y),
-                            ));
-                        }
-                        _ => unreachable!(),
+                                spawn_position,
+                                station_position,
+                                part_sprite_bundle,
+                                (),
+                            ),
+                        ))
+                    } else {
+                        commands.spawn(enemy_bundle(
+                            rng,
+                            enemy,
+                            pickups,
+                            difficulty,
+                            wait_time,
+                            spawn_position,
+                            station_position,
+                        ))
+                    };
+                    if let Some(path) =
+                        path_shape.path(spawn_position, station_position, enemy.entry_speed)
+                    {
+                        entity.insert(path);
                     }
                 }
             }
         }
     }
+    Ok(())
+}
+
+/// Returns a copy of a spawn pattern that is randomly mirrored horizontally and shifted
+/// sideways, so that a few patterns produce more varied formations.
+///
+/// Shifting never moves an `X` past the edge of the pattern.
+fn vary_pattern(rng: &mut impl rand::Rng, pattern: &SpawnPattern) -> SpawnPattern {
+    let mut pattern = *pattern;
+
+    if rng.random_bool(0.5) {
+        for row in &mut pattern {
+            row.reverse();
+        }
+    }
+
+    let column_is_empty = |xi: usize| pattern.iter().all(|row| row[xi] == b' ');
+    let empty_left = (0..PATTERN_WIDTH)
+        .take_while(|&xi| column_is_empty(xi))
+        .count();
+    let empty_right = (0..PATTERN_WIDTH)
+        .rev()
+        .take_while(|&xi| column_is_empty(xi))
+        .count();
+    if empty_left < PATTERN_WIDTH {
+        let shift = rng.random_range(0..=empty_left + empty_right);
+        for row in &mut pattern {
+            // rotation only moves empty columns from one side to the other
+            row.rotate_left(empty_left);
+            row.rotate_right(shift);
+        }
+    }
+
+    pattern
+}
+
+impl PathShape {
+    fn random(rng: &mut impl rand::Rng) -> Self {
+        match rng.random_range(0..3) {
+            0 => PathShape::Direct,
+            1 => PathShape::Swoop {
+                side: if rng.random_bool(0.5) { -1.0 } else { 1.0 },
+            },
+            _ => PathShape::Snake {
+                amplitude: rng.random_range(10.0..=30.0),
+                half_waves: rng.random_range(2..=4) as f32,
+            },
+        }
+    }
+
+    /// The path of this shape from `start` to `end`, flown at an average of `speed`,
+    /// or [`None`] if there is no path.
+    fn path(self, start: Vec2, end: Vec2, speed: f32) -> Option<Path> {
+        let (points, wiggle_amplitude, wiggle_half_waves) = match self {
+            PathShape::Direct => return None,
+            PathShape::Swoop { side } => {
+                let bottom = PLAYFIELD_RECT.min.y + PLAYFIELD_RECT.size().y * SWOOP_BOTTOM;
+                let inside = PLAYFIELD_RECT.inflate(-20.0);
+                let swing = vec2(start.x, bottom).clamp(inside.min, inside.max);
+                let rise = vec2(end.x + side * PLAYFIELD_RECT.size().x * 0.4, bottom)
+                    .clamp(inside.min, inside.max);
+                ([start, swing, rise, end], 0.0, 0.0)
+            }
+            PathShape::Snake {
+                amplitude,
+                half_waves,
+            } => (
+                [
+                    start,
+                    start.lerp(end, 1. / 3.),
+                    start.lerp(end, 2. / 3.),
+                    end,
+                ],
+                amplitude,
+                half_waves,
+            ),
+        };
+        let mut path = Path {
+            points,
+            wiggle_amplitude,
+            wiggle_half_waves,
+            duration: 0.0,
+            elapsed: 0.0,
+        };
+        path.duration = path.approximate_length() / speed;
+        Some(path)
+    }
+}
+
+impl Path {
+    /// Position at `t`, which runs from 0 at the start to 1 at the end.
+    fn position(&self, t: f32) -> Vec2 {
+        let [p0, p1, p2, p3] = self.points;
+        let s = 1.0 - t;
+        let on_curve =
+            p0 * (s * s * s) + p1 * (3.0 * s * s * t) + p2 * (3.0 * s * t * t) + p3 * (t * t * t);
+        let sideways = (p3 - p0).normalize_or_zero().perp();
+        on_curve + sideways * (t * self.wiggle_half_waves * PI).sin() * self.wiggle_amplitude
+    }
+
+    fn approximate_length(&self) -> f32 {
+        const SEGMENTS: u16 = 16;
+        (1..=SEGMENTS)
+            .map(|i| {
+                let t0 = f32::from(i - 1) / f32::from(SEGMENTS);
+                let t1 = f32::from(i) / f32::from(SEGMENTS);
+                self.position(t0).distance(self.position(t1))
+            })
+            .sum()
+    }
+
+    /// Advances along the path by `dt`, and returns the new position and whether it is the end.
+    ///
+    /// The ship slows down as it approaches the end, so that it settles into its station.
+    fn advance(&mut self, dt: f32) -> (Vec2, bool) {
+        self.elapsed += dt;
+        let linear = if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0)
+        } else {
+            1.0
+        };
+        let eased = 1.0 - (1.0 - linear).powi(2);
+        (self.position(eased), linear >= 1.0)
+    }
 }
 
 fn offset_from_signed_scale(scale: f32) -> f32 {
     if scale >= 0.0 { 0.0 } else { -scale }
 }
 
-fn enemy_bundle(
-    assets: &MyAssets,
+/// Components of an enemy described by `prefab`, with health scaled for `difficulty`,
+/// plus the runtime-only state of where it goes and what it drops.
+pub(crate) fn enemy_bundle(
+    rng: &mut impl rand::Rng,
+    prefab: &EnemyPrefab,
+    pickups: &PickupPrefabs,
+    difficulty: Difficulty,
     initial_wait: f32,
     spawn_position: Vec2,
     station_position: Vec2,
 ) -> impl b::Bundle {
-    let pickup_spawn_table = const {
-        &[
-            (PickupSpawnType::Null, 1.0),
-            (PickupSpawnType::Cool, 1.5),
-            (PickupSpawnType::Cohere, 0.4),
-        ]
+    let attackable_part = {
+        let hurt_sound = prefab.hurt_sound.clone();
+        let hurt_pitch = prefab.hurt_pitch;
+        let destruction_particle = prefab.destruction_particle.clone();
+        move |part: PartPrefab| {
+            let health = difficulty.scale_enemy_health(part.health);
+            (
+                Part {
+                    critical: part.critical,
+                },
+                Team::Enemy,
+                Attackable {
+                    health,
+                    max_health: health,
+                    hurt_animation_cooldown: 0.0,
+                    hurt_animation_damage: 0,
+                    destruction_particle: destruction_particle.clone(),
+                    hurt_sound: hurt_sound.clone(),
+                    hurt_pitch,
+                    last_hit_by: None,
+                },
+                p::Collider::circle(part.collider_radius),
+                part_sprite_bundle(part),
+            )
+        }
     };
 
-    let rng = &mut rand::rng();
-
-    let pickup = pickup_spawn_table
+    let pickup = prefab
+        .drops
         .choose_weighted(rng, |&(_, weight)| weight)
-        .unwrap()
-        .0
-        .pickup_bundle(assets, vec2(0., 0.));
+        .ok()
+        .and_then(|&(drop, _)| drop)
+        .map(|drop| drop.pickup_bundle(pickups, vec2(0., 0.)));
 
     (
+        enemy_ship_bundle(
+            prefab,
+            initial_wait,
+            spawn_position,
+            station_position,
+            attackable_part,
+            SpawnIter(pickup.into_iter()),
+        ),
         Team::Enemy,
         Attackable {
-            health: 10,
+            health: difficulty.scale_enemy_health(prefab.health),
+            max_health: difficulty.scale_enemy_health(prefab.health),
             hurt_animation_cooldown: 0.0,
-            destruction_particle: Some(assets.enemy_fragment_sprite.clone()),
-            hurt_sound: assets.enemy_hurt_sound.clone(),
+            hurt_animation_damage: 0,
+            destruction_particle: prefab.destruction_particle.clone(),
+            hurt_sound: prefab.hurt_sound.clone(),
+            hurt_pitch: prefab.hurt_pitch,
             last_hit_by: None,
         },
-        Lifetime(20.0), // TODO: bad substitute for "die when offscreen"
+        // enemies damage if touched
+        // TODO: it would probably be better to use the bullet system than the pickup system, with
+        // some generalizations.
+        Pickup::Damage(prefab.contact_damage),
+        p::Collider::circle(prefab.collider_radius),
+        Gun {
+            cooldown: rng.random_range(0.0..=prefab.max_initial_gun_cooldown),
+            base_cooldown: prefab.gun_cooldown,
+            trigger: false,
+            pattern: prefab.gun_pattern.clone(),
+            aim: PI,
+            shoot_sound: (prefab.shoot_sound.clone(), prefab.shoot_volume),
+        },
+        Drops(prefab.drops.clone()),
+        Threat(prefab.threat),
+        ScoreValue(prefab.score),
+    )
+}
+
+/// Components of an enemy ship that are needed even if it is [`Cosmetic`].
+///
+/// Each of the prefab’s parts is spawned as a child with the components from `part_bundle`,
+/// followed by the `carried` children, such as a pickup.
+fn enemy_ship_bundle<B: b::Bundle>(
+    prefab: &EnemyPrefab,
+    initial_wait: f32,
+    spawn_position: Vec2,
+    station_position: Vec2,
+    part_bundle: impl FnMut(PartPrefab) -> B + Send + Sync + 'static,
+    carried: impl SpawnableList<b::ChildOf> + Send + Sync + 'static,
+) -> impl b::Bundle {
+    (
+        // in case the ship never leaves the playfield to be despawned by `enemy_ship_ai`
+        Lifetime(20.0),
         EnemyShipAi {
             state: AiState::InitialWait(initial_wait),
+            style: prefab.ai,
             station: station_position,
-            time_on_station: 2.0,
+            time_on_station: prefab.station_time,
+            dive_speed: prefab.dive_speed,
+            approach_stiffness: prefab.approach_stiffness,
+            approach_damping: prefab.approach_damping,
+            time_on_screen: 0.0,
         },
-        // enemies damage if touched
-        // TODO: it would probably be better to use the bullet system than the pickup system, with
-        // some generalizations.
-        Pickup::Damage(0.1),
         b::Transform::from_translation(spawn_position.extend(Zees::Enemy.z())),
-        b::Sprite::from_image(assets.enemy_sprite.clone()),
+        b::Sprite::from_image(prefab.sprite.clone()),
         PLAYFIELD_LAYERS,
         p::RigidBody::Kinematic,
-        p::Collider::circle(8.),
         p::LinearVelocity(vec2(0.0, 0.0)),
-        Gun {
-            cooldown: rng.random_range(0.0..=3.0),
-            base_cooldown: 6.0,
-            trigger: false,
-            pattern: Pattern::Single,
-            shoot_sound: (
-                assets.enemy_shoot_sound.clone(),
-                bevy::audio::Volume::Decibels(-20.),
-            ),
-        },
-        b::children![pickup],
+        b::Children::spawn((
+            SpawnIter(prefab.parts.0.clone().into_iter().map(part_bundle)),
+            carried,
+        )),
+    )
+}
+
+/// Components of an enemy [`Part`] that are needed even if it is [`Cosmetic`].
+fn part_sprite_bundle(part: PartPrefab) -> impl b::Bundle {
+    (
+        b::Transform::from_translation(part.offset.extend(0.1)),
+        b::Sprite::from_image(part.sprite),
+        PLAYFIELD_LAYERS,
     )
 }
 
 // -------------------------------------------------------------------------------------------------
 
+/// Stops enemies in place when the run ends, since [`enemy_ship_ai`] and
+/// [`boss_ai_system`](crate::boss::boss_ai_system) are no longer steering them.
+#[allow(
+    clippy::type_complexity,
+    reason = "one query covers both ordinary enemies and bosses"
+)]
+pub(crate) fn freeze_enemies(
+    query: b::Query<
+        (&mut p::LinearVelocity, Option<&mut Gun>),
+        b::Or<(b::With<EnemyShipAi>, b::With<crate::boss::Boss>)>,
+    >,
+) {
+    for (mut velocity, gun) in query {
+        velocity.0 = Vec2::ZERO;
+        if let Some(mut gun) = gun {
+            gun.trigger = false;
+        }
+    }
+}
+
+/// Steers enemy ships through their [`AiState`]s, following their [`Path`] if they have one,
+/// and despawns ships which have left the playfield for good, so that they stop counting towards
+/// the spawner’s [`Threat`] budget.
+#[allow(
+    clippy::type_complexity,
+    reason = "steering uses the optional gun and path of each ship"
+)]
 pub(crate) fn enemy_ship_ai(
+    mut commands: b::Commands,
     time: b::Res<b::Time>,
     query: b::Query<(
+        b::Entity,
         &mut EnemyShipAi,
         &b::Transform,
         &mut p::LinearVelocity,
-        &mut Gun,
+        Option<&mut Gun>,
+        Option<&mut Path>,
     )>,
 ) {
     let dt = time.delta_secs();
 
-    for (mut ai, transform, mut velocity, mut gun) in query {
+    for (entity, mut ai, transform, mut velocity, mut gun, path) in query {
         let current_position = transform.translation.xy();
-        // don't shoot from off-screen because it is unfair *and* makes extra noises
-        let may_fire = PLAYFIELD_RECT.contains(transform.translation.xy());
+        // don't shoot from off-screen, or just after coming on screen,
+        // because it is unfair *and* makes extra noises
+        if PLAYFIELD_RECT.contains(current_position) {
+            ai.time_on_screen += dt;
+        } else if matches!(
+            ai.state,
+            AiState::Dive | AiState::Weave(_) | AiState::Retreat
+        ) && !PLAYFIELD_RECT
+            .inflate(DEPARTURE_MARGIN)
+            .contains(current_position)
+        {
+            // No AI state after the station returns to the playfield after leaving it.
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let may_fire =
+            ai.time_on_screen >= FIRE_GRACE_PERIOD && PLAYFIELD_RECT.contains(current_position);
+        let mut fire = |may_fire: bool| {
+            if let Some(gun) = &mut gun {
+                gun.trigger |= may_fire;
+            }
+        };
 
         match ai.state {
             AiState::InitialWait(wait_time) => {
@@ -253,6 +775,18 @@ pub(crate) fn enemy_ship_ai(
                 }
             }
             AiState::GoToStation => {
+                if let Some(mut path) = path {
+                    let (next_position, arrived) = path.advance(dt);
+                    if dt > 0.0 {
+                        velocity.0 = (next_position - current_position) / dt;
+                    }
+                    if arrived {
+                        ai.state = AiState::WaitAtStation;
+                        commands.entity(entity).remove::<Path>();
+                    }
+                    continue;
+                }
+
                 let station_relative_position = ai.station - current_position;
                 let distance = station_relative_position.length();
 
@@ -260,7 +794,8 @@ pub(crate) fn enemy_ship_ai(
                     ai.state = AiState::WaitAtStation;
                 } else {
                     // fly towards station
-                    let acceleration = station_relative_position * 16.0 - velocity.0 * 4.0;
+                    let acceleration = station_relative_position * ai.approach_stiffness
+                        - velocity.0 * ai.approach_damping;
                     velocity.0 += acceleration * dt;
                 }
             }
@@ -269,17 +804,46 @@ pub(crate) fn enemy_ship_ai(
 
                 ai.time_on_station = new_time_on_station;
                 if new_time_on_station == 0.0 {
-                    ai.state = AiState::Dive;
-                    velocity.0 = vec2(0.0, -80.0);
+                    let speed = ai.dive_speed;
+                    (ai.state, velocity.0) = match ai.style {
+                        AiStyle::Dive => (AiState::Dive, vec2(0.0, -speed)),
+                        AiStyle::Weave => (AiState::Weave(0.0), vec2(0.0, -speed)),
+                        AiStyle::Retreat => (AiState::Retreat, vec2(0.0, speed)),
+                    };
                 } else {
                     velocity.0 = Vec2::ZERO;
                 }
 
-                gun.trigger |= may_fire;
+                fire(may_fire);
             }
             AiState::Dive => {
-                gun.trigger |= may_fire;
+                fire(may_fire);
+            }
+            AiState::Weave(elapsed) => {
+                let elapsed = elapsed + dt;
+                ai.state = AiState::Weave(elapsed);
+                let sideways = (elapsed * 2.0 * PI / WEAVE_PERIOD).sin() * WEAVE_WIDTH;
+                velocity.0 = vec2(sideways, -1.0) * ai.dive_speed;
+                fire(may_fire);
             }
+            AiState::Retreat => {}
         }
     }
 }
+
+// -------------------------------------------------------------------------------------------------
+
+/// Starts the menu background simulation.
+pub(crate) fn spawn_cosmetic_spawner(mut commands: b::Commands) {
+    commands.spawn((Cosmetic, EnemySpawner { cooldown: 2.0 }));
+}
+
+/// Ends the menu background simulation.
+pub(crate) fn despawn_cosmetic_entities(
+    mut commands: b::Commands,
+    query: b::Query<b::Entity, b::With<Cosmetic>>,
+) {
+    for entity in query {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::Mutex;
+
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic as _};
+use bevy::ecs::error::{BevyError, ErrorContext};
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::math::{vec2, vec3};
+use bevy::prelude as b;
+
+use crate::{GameAssets, GameState, PLAYFIELD_RECT, UI_LAYERS, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// When enabled, makes errors returned by systems and observers visible, instead of fatal:
+///
+/// * Each is counted in the [`ERROR_COUNT`] diagnostic.
+/// * The latest one is shown on screen for a few seconds.
+///
+/// Errors are still logged.
+///
+/// When not enabled, Bevy’s default error handler, which panics, is left in place, so that
+/// errors such as [`InvariantsPlugin`](crate::invariants::InvariantsPlugin) violations stop
+/// the game in development and in tests.
+pub(crate) struct ErrorReportPlugin {
+    pub show_errors: bool,
+}
+
+impl b::Plugin for ErrorReportPlugin {
+    fn build(&self, app: &mut b::App) {
+        if !self.show_errors {
+            return;
+        }
+        app.set_error_handler(capture_error)
+            .init_resource::<CapturedErrors>()
+            .register_diagnostic(Diagnostic::new(ERROR_COUNT))
+            .add_systems(b::Last, report_errors_system)
+            .add_systems(
+                b::OnExit(GameState::AssetLoading),
+                spawn_error_toast.after(crate::fallback_assets::insert_fallback_assets_if_missing),
+            );
+    }
+}
+
+/// Total number of errors captured since the app started.
+const ERROR_COUNT: DiagnosticPath = DiagnosticPath::const_new("errors");
+
+/// Errors which [`capture_error()`] has passed on and [`report_errors_system`] has not yet
+/// moved into [`CapturedErrors`].
+///
+/// This has to be a static because error handlers are function pointers with no access to
+/// the world. Since it is shared by every app in the process, only one of them should
+/// enable [`ErrorReportPlugin`].
+static HANDOFF: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Errors captured in this app.
+#[derive(Debug, Default, b::Resource)]
+struct CapturedErrors {
+    total: usize,
+    latest: Option<String>,
+}
+
+/// Text showing the most recently captured error.
+#[derive(Debug, Default, b::Component)]
+struct ErrorToast {
+    /// Real time remaining before the toast disappears.
+    remaining: f32,
+}
+
+/// Real time for which an [`ErrorToast`] stays on screen after the latest error.
+const TOAST_DURATION: f32 = 5.0;
+
+const TOAST_COLOR: b::Color = b::Color::srgb(1.0, 0.45, 0.4);
+
+// -------------------------------------------------------------------------------------------------
+
+fn capture_error(error: BevyError, context: ErrorContext) {
+    let message = format!("{} {} failed: {error}", context.kind(), context.name());
+    b::error!("{message}");
+    if let Ok(mut handoff) = HANDOFF.lock() {
+        handoff.push(message);
+    }
+}
+
+fn spawn_error_toast(mut commands: b::Commands, assets: b::Res<GameAssets>) {
+    commands.spawn((
+        ErrorToast::default(),
+        b::Text2d::new(""),
+        assets.small_mono_font(),
+        b::TextColor(TOAST_COLOR),
+        b::TextLayout::new_with_justify(b::Justify::Left),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        bevy::text::TextBounds::new_horizontal(PLAYFIELD_RECT.width() - 8.0),
+        bevy::sprite::Anchor::BOTTOM_LEFT,
+        b::Transform::from_translation(vec3(
+            PLAYFIELD_RECT.min.x + 4.0,
+            PLAYFIELD_RECT.min.y + 32.0,
+            Zees::UiFront2.z(),
+        )),
+        b::Visibility::Hidden,
+        UI_LAYERS,
+    ));
+}
+
+/// Moves newly captured errors into [`CapturedErrors`], counts them, and shows the latest on
+/// the [`ErrorToast`].
+fn report_errors_system(
+    time: b::Res<b::Time<b::Real>>,
+    mut diagnostics: Diagnostics,
+    mut captured: b::ResMut<CapturedErrors>,
+    toast: Option<b::Single<(&mut ErrorToast, &mut b::Text2d, &mut b::Visibility)>>,
+) {
+    let new_errors = match HANDOFF.lock() {
+        Ok(mut handoff) => std::mem::take(&mut *handoff),
+        Err(_) => Vec::new(),
+    };
+    let is_new = !new_errors.is_empty();
+    captured.total += new_errors.len();
+    if let Some(latest) = new_errors.into_iter().next_back() {
+        captured.latest = Some(latest);
+    }
+    diagnostics.add_measurement(&ERROR_COUNT, || captured.total as f64);
+
+    let Some(toast) = toast else {
+        return;
+    };
+    let (mut toast, mut text, mut visibility) = toast.into_inner();
+    if is_new && let Some(latest) = &captured.latest {
+        text.0 = format!("error #{total}: {latest}", total = captured.total);
+        toast.remaining = TOAST_DURATION;
+        *visibility = b::Visibility::Inherited;
+    } else if toast.remaining > 0.0 {
+        toast.remaining -= time.delta_secs();
+        if toast.remaining <= 0.0 {
+            *visibility = b::Visibility::Hidden;
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude as b;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::GameAssets;
+use crate::enemy::EnemyKind;
+use crate::prefab::{self, BackdropPrefab, EnemyPrefab, PickupPrefabs};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Stand-ins for every asset in [`GameAssets`], which let the game run (without much to see or
+/// hear) when the asset files could not be loaded — for example, if the `assets` folder is
+/// missing in a headless run.
+struct Fallback {
+    image: b::Handle<b::Image>,
+}
+
+impl prefab::ResolvePath for Fallback {
+    fn image(&mut self, _path: String) -> b::Handle<b::Image> {
+        self.image.clone()
+    }
+    fn sound(&mut self, _path: String) -> b::Handle<b::AudioSource> {
+        silence()
+    }
+}
+
+/// Generated 1×1 sprite.
+fn placeholder_image() -> b::Image {
+    b::Image::new_fill(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255, 0, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// A handle to no sound at all. Audio players given this handle wait forever for it to load,
+/// and so stay silent.
+///
+/// We can’t generate a real silent [`b::AudioSource`] because the only audio format enabled is
+/// Ogg Vorbis.
+fn silence() -> b::Handle<b::AudioSource> {
+    b::Handle::default()
+}
+
+/// Inserts stand-in [`GameAssets`] if the real ones failed to load.
+pub(crate) fn insert_fallback_assets_if_missing(world: &mut b::World) -> b::Result {
+    if world.contains_resource::<GameAssets>() {
+        return Ok(());
+    }
+    b::warn!("assets failed to load; using placeholders");
+
+    let image = world
+        .resource_mut::<b::Assets<b::Image>>()
+        .add(placeholder_image());
+    let mut fallback = Fallback {
+        image: image.clone(),
+    };
+    let (pickups, backdrop) = prefab::builtin_prefabs(&mut fallback)?;
+    let mut enemy = |kind| -> Result<_, b::BevyError> {
+        let prefab = prefab::builtin_enemy_prefab(kind, &mut fallback)?;
+        Ok(world.resource_mut::<b::Assets<EnemyPrefab>>().add(prefab))
+    };
+    let basic_enemy = enemy(EnemyKind::Grunt)?;
+    let tank_enemy = enemy(EnemyKind::Tank)?;
+    let swarmer_enemy = enemy(EnemyKind::Swarmer)?;
+    let sniper_enemy = enemy(EnemyKind::Sniper)?;
+    let pickups = world
+        .resource_mut::<b::Assets<PickupPrefabs>>()
+        .add(pickups);
+    let backdrop = world
+        .resource_mut::<b::Assets<BackdropPrefab>>()
+        .add(backdrop);
+
+    world.insert_resource(GameAssets {
+        basic_enemy,
+        tank_enemy,
+        swarmer_enemy,
+        sniper_enemy,
+        enemy_bullet_sprite: image.clone(),
+        enemy_kill_sound: silence(),
+        player_ship_sprite: image.clone(),
+        player_ship_heat_sprite: image.clone(),
+        player_bullet_sprite: image.clone(),
+        player_shoot_sound: silence(),
+        player_hurt_sound: silence(),
+        pickups,
+        pickup_sound: silence(),
+        // The default font handle is Bevy’s built-in font.
+        ui_font: b::Handle::default(),
+        small_prop_font: b::Handle::default(),
+        small_mono_font: b::Handle::default(),
+        playfield_frame_sprite: image.clone(),
+        bar_frame_sprite: image.clone(),
+        bar_fill_base_sprite: image.clone(),
+        bar_fill_temporary_sprite: image.clone(),
+        text_bar_coherence_sprite: image.clone(),
+        text_bar_fever_sprite: image.clone(),
+        text_bar_fervor_sprite: image.clone(),
+        text_bar_fervor_inactive_sprite: image.clone(),
+        screen_heat_fog: image.clone(),
+        star_sprite: image.clone(),
+        muzzle_flash_sprite: image.clone(),
+        heat_vent_sprite: image.clone(),
+        backdrop,
+        backdrop_planet_sprite: image.clone(),
+        backdrop_derelict_sprite: image.clone(),
+        backdrop_comet_sprite: image,
+    });
+    Ok(())
+}
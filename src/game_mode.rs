@@ -0,0 +1,54 @@
+use bevy::prelude as b;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Which game the player is playing, chosen by the menu button that started the run.
+///
+/// Runs started some other way, such as by playing a seed or pressing the start key on the
+/// menu, play the same mode as the previous run.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, b::Resource)]
+pub(crate) enum GameMode {
+    /// The run is won by reaching [`Fervor::WIN_THRESHOLD`](crate::Fervor::WIN_THRESHOLD).
+    #[default]
+    Standard,
+    /// The run cannot be won, the enemies keep coming in greater numbers, and the only goal is
+    /// score.
+    Endless,
+}
+
+/// Threat budget added to the enemy spawner per minute of an [`GameMode::Endless`] run.
+const ENDLESS_THREAT_PER_MINUTE: f32 = 3.0;
+/// Time for the spawner’s interval between formations to halve in an [`GameMode::Endless`] run.
+const ENDLESS_INTERVAL_HALF_LIFE: f32 = 240.0;
+/// Shortest the spawner’s interval may become, as a fraction of normal.
+const ENDLESS_MIN_INTERVAL_SCALE: f32 = 0.35;
+
+// -------------------------------------------------------------------------------------------------
+
+impl GameMode {
+    /// Whether the run can be won by reaching full Fervor.
+    pub fn can_win(self) -> bool {
+        match self {
+            GameMode::Standard => true,
+            GameMode::Endless => false,
+        }
+    }
+
+    /// Threat budget added to the enemy spawner after `elapsed` seconds of the run.
+    pub fn extra_threat_budget(self, elapsed: f32) -> f32 {
+        match self {
+            GameMode::Standard => 0.0,
+            GameMode::Endless => elapsed / 60.0 * ENDLESS_THREAT_PER_MINUTE,
+        }
+    }
+
+    /// Multiplier for the time between enemy formations after `elapsed` seconds of the run.
+    pub fn spawn_interval(self, elapsed: f32) -> f32 {
+        match self {
+            GameMode::Standard => 1.0,
+            GameMode::Endless => (0.5f32)
+                .powf(elapsed / ENDLESS_INTERVAL_HALF_LIFE)
+                .max(ENDLESS_MIN_INTERVAL_SCALE),
+        }
+    }
+}
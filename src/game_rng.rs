@@ -0,0 +1,111 @@
+use bevy::ecs::change_detection::DetectChangesMut as _;
+use bevy::math::vec3;
+use bevy::prelude as b;
+use rand::SeedableRng as _;
+use rand::rngs::SmallRng;
+
+use crate::cutscene::Cutscene;
+use crate::settings::Settings;
+use crate::{GameAssets, GameState, PLAYFIELD_RECT, UI_LAYERS, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Source of randomness for gameplay (as opposed to purely visual or audio variation).
+///
+/// It is reseeded at the start of each run, so that a run can be restarted with the same
+/// enemy formations and drops.
+#[derive(Debug, b::Resource, b::Deref, b::DerefMut)]
+pub(crate) struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: SmallRng,
+}
+
+impl GameRng {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts the random sequence from the given seed.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SmallRng::seed_from_u64(seed);
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        let seed = rand::random();
+        Self {
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+        }
+    }
+}
+
+/// Resource present when a new run should use this seed instead of a random one,
+/// as chosen by the player.
+#[derive(Debug, b::Resource)]
+pub(crate) struct ChosenSeed(pub u64);
+
+/// Number of hexadecimal digits in a seed as shown to the player by [`format_seed()`].
+pub(crate) const SEED_DIGITS: usize = 16;
+
+/// Text showing the seed of the current run, so that it can be shared.
+#[derive(Debug, b::Component)]
+pub(crate) struct SeedText;
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn format_seed(seed: u64) -> String {
+    format!("{seed:016X}")
+}
+
+/// Parses a seed in the format of [`format_seed()`], except that leading zeros may be omitted
+/// and letters may be lowercase.
+pub(crate) fn parse_seed(text: &str) -> Option<u64> {
+    u64::from_str_radix(text, 16).ok()
+}
+
+pub(crate) fn seed_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        SeedText,
+        b::Text2d::new(""),
+        assets.small_mono_font(),
+        b::TextLayout::new_with_justify(b::Justify::Center),
+        bevy::sprite::Anchor::BOTTOM_CENTER,
+        b::Transform::from_translation(vec3(0.0, PLAYFIELD_RECT.min.y + 20.0, Zees::UiMiddle.z())),
+        b::Visibility::Hidden,
+        UI_LAYERS,
+    )
+}
+
+/// Shows the [`SeedText`] on the pause and results screens, unless
+/// [`Settings::streamer_mode`] is set.
+pub(crate) fn update_seed_text_system(
+    game_rng: b::Res<GameRng>,
+    settings: b::Res<Settings>,
+    state: b::Res<b::State<GameState>>,
+    cutscene: Option<b::Res<Cutscene>>,
+    text: b::Single<(&mut b::Text2d, &mut b::Visibility), b::With<SeedText>>,
+) {
+    let (mut text, mut visibility) = text.into_inner();
+
+    let visible = match *state.get() {
+        GameState::Paused => true,
+        GameState::WinOrGameOver => cutscene.is_none(),
+        GameState::AssetLoading | GameState::Menu | GameState::Playing | GameState::HighScores => {
+            false
+        }
+    } && !settings.streamer_mode;
+    visibility.set_if_neq(if visible {
+        b::Visibility::Inherited
+    } else {
+        b::Visibility::Hidden
+    });
+
+    let new_text = format!("Seed {}", format_seed(game_rng.seed()));
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::prelude as b;
+use bevy_enhanced_input::prelude as bei;
+
+use crate::DebugSpeedUp;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Central control of how fast game time passes relative to real time.
+///
+/// This sets the relative speed of [`b::Time<b::Virtual>`], which drives the fixed timestep and
+/// therefore physics too, so all gameplay slows down together; only [`b::Time<b::Real>`] is
+/// unaffected. Pausing is separate and is done by pausing [`avian2d::prelude::Physics`] time
+/// and not running gameplay systems.
+#[derive(Debug, b::Resource)]
+pub(crate) struct GameSpeed {
+    /// Speed chosen by the player for practice. 1.0 is normal.
+    pub practice: f32,
+
+    /// Speed-up for testing, toggled by the [`DebugSpeedUp`] key in debug builds.
+    pub debug: f32,
+
+    /// Temporary slowdowns in effect.
+    effects: Vec<SpeedEffect>,
+}
+
+/// A temporary change in [`GameSpeed`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SpeedEffect {
+    /// Multiplier applied while this effect is active.
+    pub speed: f32,
+    /// Duration in *real* time, so that it is not prolonged by its own slowdown.
+    pub duration: f32,
+}
+
+impl SpeedEffect {
+    /// Brief near-freeze when the player is hit, to make the hit noticeable.
+    pub const HIT_STOP: Self = Self {
+        speed: 0.05,
+        duration: 0.08,
+    };
+
+    /// Slow motion as the player ship dies.
+    pub const DEATH_SLOW_MOTION: Self = Self {
+        speed: 0.25,
+        duration: 1.5,
+    };
+
+    /// Slow motion as the boss is destroyed.
+    pub const KILL_CAM: Self = Self {
+        speed: 0.3,
+        duration: 1.2,
+    };
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self {
+            practice: 1.0,
+            debug: 1.0,
+            effects: Vec::new(),
+        }
+    }
+}
+
+impl GameSpeed {
+    /// Speed factor for debug speed-up.
+    const DEBUG_FAST: f32 = 4.0;
+
+    /// Start a temporary speed change. If several are active at once, the slowest wins.
+    pub fn add_effect(&mut self, effect: SpeedEffect) {
+        self.effects.push(effect);
+    }
+
+    /// Removes all temporary speed changes, e.g. when starting a new game.
+    pub fn clear_effects(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn effective_speed(&self) -> f32 {
+        let effect_speed = self
+            .effects
+            .iter()
+            .map(|effect| effect.speed)
+            .fold(1.0, f32::min);
+        self.practice * self.debug * effect_speed
+    }
+}
+
+pub(crate) struct GameSpeedPlugin;
+
+impl b::Plugin for GameSpeedPlugin {
+    fn build(&self, app: &mut b::App) {
+        app.init_resource::<GameSpeed>()
+            // First, so that the speed applies to this frame's fixed timestep accumulation
+            .add_systems(
+                b::First,
+                apply_game_speed_system.after(bevy::time::TimeSystems),
+            )
+            .add_observer(debug_speed_up_observer);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+fn apply_game_speed_system(
+    real_time: b::Res<b::Time<b::Real>>,
+    mut virtual_time: b::ResMut<b::Time<b::Virtual>>,
+    mut game_speed: b::ResMut<GameSpeed>,
+) {
+    let real_delta = real_time.delta_secs();
+    if !game_speed.effects.is_empty() {
+        game_speed.effects.retain_mut(|effect| {
+            effect.duration -= real_delta;
+            effect.duration > 0.0
+        });
+    }
+
+    let speed = game_speed.effective_speed();
+    if virtual_time.relative_speed() != speed {
+        virtual_time.set_relative_speed(speed);
+    }
+}
+
+fn debug_speed_up_observer(
+    _event: b::On<bei::Start<DebugSpeedUp>>,
+    mut game_speed: b::ResMut<GameSpeed>,
+) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    game_speed.debug = if game_speed.debug == 1.0 {
+        GameSpeed::DEBUG_FAST
+    } else {
+        1.0
+    };
+    b::info!("debug game speed: {}", game_speed.debug);
+}
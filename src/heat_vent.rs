@@ -0,0 +1,155 @@
+use std::f32::consts::TAU;
+
+use bevy::color::Alpha as _;
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::math::{Vec2, Vec3Swizzles as _, vec2};
+use bevy::prelude as b;
+use rand::RngExt as _;
+
+use crate::game_rng::GameRng;
+use crate::quantity::{Adjustment, Fever, Quantities, QuantityDelta, QuantityKind};
+use crate::settings::Settings;
+use crate::{GameAssets, Lifetime, PLAYFIELD_LAYERS, PLAYFIELD_RECT, Player, SpriteId, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// A hazard zone on the playfield which adds temporary Fever while the player is inside it.
+///
+/// Vents appear while Fever stays high (see [`HeatVentSpawner`]), shimmer harmlessly for
+/// [`VENT_WARNING_TIME`] as a warning, and then are active until their [`Lifetime`] runs out.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct HeatVent {
+    radius: f32,
+    /// Time since the vent appeared.
+    age: f32,
+}
+
+/// Decides when [`HeatVent`]s appear.
+#[derive(Debug, Default, b::Resource)]
+pub(crate) struct HeatVentSpawner {
+    /// Time for which Fever has continuously been at least [`VENT_FEVER_THRESHOLD`].
+    high_fever_time: f32,
+    /// Time until another vent may appear.
+    cooldown: f32,
+}
+
+/// Effective Fever at or above which vents start to appear.
+const VENT_FEVER_THRESHOLD: f32 = 0.7;
+/// Time Fever must stay above [`VENT_FEVER_THRESHOLD`] before the first vent appears,
+/// so that a brief spike does not cause any.
+const VENT_SUSTAIN_TIME: f32 = 3.0;
+/// Time between vents while Fever stays high.
+const VENT_INTERVAL: f32 = 4.0;
+/// Most vents that exist at once.
+const MAX_VENTS: usize = 3;
+
+/// Time a vent shimmers before it becomes active.
+const VENT_WARNING_TIME: f32 = 1.2;
+/// Total time a vent exists, including [`VENT_WARNING_TIME`].
+const VENT_LIFETIME: f32 = 7.0;
+/// Time over which a vent fades out at the end of its lifetime.
+const VENT_FADE_TIME: f32 = 0.5;
+/// Temporary Fever added per second while the player is inside an active vent.
+const VENT_FEVER_RATE: f32 = 0.15;
+/// Vents appear in this fraction of the playfield height, measured from the bottom,
+/// where the player usually is.
+const VENT_HEIGHT_FRACTION: f32 = 0.6;
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn reset_heat_vents(mut spawner: b::ResMut<HeatVentSpawner>) {
+    *spawner = HeatVentSpawner::default();
+}
+
+/// Spawns [`HeatVent`]s while Fever stays high.
+pub(crate) fn spawn_heat_vents_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    assets: b::Res<GameAssets>,
+    mut spawner: b::ResMut<HeatVentSpawner>,
+    mut game_rng: b::ResMut<GameRng>,
+    quantities: Quantities,
+    vents: b::Query<(), b::With<HeatVent>>,
+) -> b::Result {
+    let dt = time.delta_secs();
+    spawner.cooldown -= dt;
+    if quantities.get::<Fever>()?.effective_value() < VENT_FEVER_THRESHOLD {
+        spawner.high_fever_time = 0.0;
+        return Ok(());
+    }
+    spawner.high_fever_time += dt;
+    if spawner.high_fever_time < VENT_SUSTAIN_TIME
+        || spawner.cooldown > 0.0
+        || vents.iter().len() >= MAX_VENTS
+    {
+        return Ok(());
+    }
+    spawner.cooldown = VENT_INTERVAL;
+
+    let rng = &mut **game_rng;
+    let radius = rng.random_range(18.0..=30.0);
+    let area = PLAYFIELD_RECT.inflate(-radius);
+    let position = vec2(
+        rng.random_range(area.min.x..=area.max.x),
+        rng.random_range(area.min.y..=area.min.y + area.height() * VENT_HEIGHT_FRACTION),
+    );
+    commands.spawn((
+        HeatVent { radius, age: 0.0 },
+        Lifetime(VENT_LIFETIME),
+        b::Sprite {
+            custom_size: Some(Vec2::splat(radius * 2.0)),
+            color: b::Color::WHITE.with_alpha(0.0),
+            ..b::Sprite::from_image(assets.sprite(SpriteId::HeatVent))
+        },
+        // above the starfield, but below everything that can be touched
+        b::Transform::from_translation(position.extend(Zees::Pickup.z() - 0.5)),
+        PLAYFIELD_LAYERS,
+    ));
+    Ok(())
+}
+
+/// Animates [`HeatVent`]s and adds Fever while the player is inside an active one.
+pub(crate) fn heat_vent_system(
+    time: b::Res<b::Time>,
+    settings: b::Res<Settings>,
+    vents: b::Query<(&mut HeatVent, &b::Transform, &mut b::Sprite)>,
+    player: b::Query<&b::Transform, b::With<Player>>,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
+) {
+    let dt = time.delta_secs();
+    let player_position = player
+        .single()
+        .ok()
+        .map(|transform| transform.translation.xy());
+
+    for (mut vent, transform, mut sprite) in vents {
+        vent.age += dt;
+        let active = vent.age >= VENT_WARNING_TIME;
+
+        // Shimmer: a quick flicker while warning, then a slower, stronger glow.
+        // With reduced motion, the same fade-in and brightness without the flicker.
+        let alpha = match (active, settings.reduced_motion) {
+            (true, false) => 0.75 + 0.15 * (vent.age * TAU * 1.5).sin(),
+            (true, true) => 0.75,
+            (false, false) => {
+                (0.2 + 0.2 * (vent.age * TAU * 4.0).sin()) * (vent.age / VENT_WARNING_TIME)
+            }
+            (false, true) => 0.2 * (vent.age / VENT_WARNING_TIME),
+        };
+        let fade = ((VENT_LIFETIME - vent.age) / VENT_FADE_TIME).clamp(0.0, 1.0);
+        sprite.color = b::Color::WHITE.with_alpha(alpha * fade);
+
+        if active
+            && player_position.is_some_and(|position| {
+                position.distance(transform.translation.xy()) <= vent.radius
+            })
+        {
+            quantity_deltas.write(QuantityDelta::new(
+                QuantityKind::Fever,
+                Adjustment::TemporaryStacking,
+                VENT_FEVER_RATE * dt,
+            ));
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use core::fmt::Write as _;
+
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::math::{vec2, vec3};
+use bevy::prelude as b;
+
+use crate::game_mode::GameMode;
+use crate::game_rng::{GameRng, format_seed};
+use crate::persistence::{self, DataDirectory, FlushPersistentData};
+use crate::run_stats::{RunStats, format_run_time};
+use crate::score::Score;
+use crate::settings::Settings;
+use crate::{GameAssets, GameState, UI_LAYERS, VisibleInState, WinOrGameOver, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// The best runs ever played, loaded from and saved to disk.
+#[derive(Debug, Default, b::Resource, serde::Serialize, serde::Deserialize)]
+pub(crate) struct HighScores {
+    /// Runs in [`GameMode::Standard`]: at most [`MAX_ENTRIES`] entries, highest score first.
+    entries: Vec<HighScoreEntry>,
+
+    /// Runs in [`GameMode::Endless`], kept separately since they are not comparable;
+    /// otherwise like [`Self::entries`].
+    #[serde(default)]
+    endless_entries: Vec<HighScoreEntry>,
+
+    /// Mode and index of the run that was most recently added, to highlight it.
+    #[serde(skip)]
+    latest: Option<(GameMode, usize)>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct HighScoreEntry {
+    score: u64,
+    outcome: WinOrGameOver,
+    /// [`RunStats::elapsed`] at the end of the run.
+    duration_secs: f32,
+    seed: u64,
+}
+
+/// Text listing the [`HighScores`], shown in [`GameState::HighScores`].
+#[derive(Debug, b::Component)]
+struct HighScoresText;
+
+const HIGH_SCORES_FILE: &str = "high_scores.json";
+
+/// Number of runs kept in [`HighScores`].
+const MAX_ENTRIES: usize = 10;
+
+pub(crate) struct HighScoresPlugin;
+
+impl b::Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut b::App) {
+        let high_scores = persistence::load::<HighScores>(app.world().resource(), HIGH_SCORES_FILE)
+            .unwrap_or_default();
+        app.insert_resource(high_scores)
+            .add_observer(save_high_scores_observer)
+            .add_systems(b::OnEnter(GameState::WinOrGameOver), record_high_score)
+            .add_systems(
+                b::Update,
+                update_high_scores_text_system.run_if(b::in_state(GameState::HighScores)),
+            );
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl HighScores {
+    fn entries(&self, mode: GameMode) -> &Vec<HighScoreEntry> {
+        match mode {
+            GameMode::Standard => &self.entries,
+            GameMode::Endless => &self.endless_entries,
+        }
+    }
+
+    fn entries_mut(&mut self, mode: GameMode) -> &mut Vec<HighScoreEntry> {
+        match mode {
+            GameMode::Standard => &mut self.entries,
+            GameMode::Endless => &mut self.endless_entries,
+        }
+    }
+
+    /// Adds the entry to the list for `mode` if it is among the [`MAX_ENTRIES`] best,
+    /// and returns whether it was.
+    fn insert(&mut self, mode: GameMode, entry: HighScoreEntry) -> bool {
+        let entries = self.entries_mut(mode);
+        // Later runs go after earlier runs with the same score, so that a tie does not
+        // displace an existing entry.
+        let index = entries.partition_point(|existing| existing.score >= entry.score);
+        if index >= MAX_ENTRIES {
+            return false;
+        }
+        entries.insert(index, entry);
+        entries.truncate(MAX_ENTRIES);
+        self.latest = Some((mode, index));
+        true
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn high_scores_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        HighScoresText,
+        VisibleInState(GameState::HighScores),
+        b::Text2d::new(""),
+        assets.small_mono_font(),
+        b::TextLayout::new_with_justify(b::Justify::Left),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        bevy::sprite::Anchor::TOP_CENTER,
+        b::Transform::from_translation(vec3(0.0, 70.0, Zees::UiFront2.z())),
+        UI_LAYERS,
+    )
+}
+
+/// Adds the run that just ended to the [`HighScores`], and saves them if it made the list.
+fn record_high_score(
+    mut commands: b::Commands,
+    mut high_scores: b::ResMut<HighScores>,
+    score: b::Res<Score>,
+    stats: b::Res<RunStats>,
+    rng: b::Res<GameRng>,
+    game_mode: b::Res<GameMode>,
+    wog_state: Option<b::Res<b::State<WinOrGameOver>>>,
+) {
+    high_scores.latest = None;
+    let made_the_list = high_scores.insert(
+        *game_mode,
+        HighScoreEntry {
+            score: score.0,
+            outcome: wog_state.map_or(WinOrGameOver::GameOver, |state| state.get().clone()),
+            duration_secs: stats.elapsed,
+            seed: rng.seed(),
+        },
+    );
+    if made_the_list {
+        // save now rather than at exit, in case the game does not exit cleanly
+        commands.trigger(FlushPersistentData);
+    }
+}
+
+fn save_high_scores_observer(
+    _event: b::On<FlushPersistentData>,
+    data_directory: b::Res<DataDirectory>,
+    high_scores: b::Res<HighScores>,
+) {
+    persistence::save(&data_directory, HIGH_SCORES_FILE, &*high_scores);
+}
+
+fn update_high_scores_text_system(
+    high_scores: b::Res<HighScores>,
+    settings: b::Res<Settings>,
+    mut text: b::Single<&mut b::Text2d, b::With<HighScoresText>>,
+) {
+    let mut new_text = String::new();
+    if high_scores.entries.is_empty() && high_scores.endless_entries.is_empty() {
+        new_text.push_str("No runs yet\n");
+    }
+    // Seeds would let viewers replay the streamer’s runs; see `Settings::streamer_mode`.
+    let show_seeds = !settings.streamer_mode;
+    write_entries(&mut new_text, &high_scores, GameMode::Standard, show_seeds);
+    // The endless list is only shown once there is something in it, so that players who have
+    // never tried that mode see the same screen as before.
+    if !high_scores.endless_entries.is_empty() {
+        if !new_text.is_empty() {
+            new_text.push('\n');
+        }
+        new_text.push_str("Endless\n");
+        write_entries(&mut new_text, &high_scores, GameMode::Endless, show_seeds);
+    }
+
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
+
+fn write_entries(text: &mut String, high_scores: &HighScores, mode: GameMode, show_seeds: bool) {
+    for (index, entry) in high_scores.entries(mode).iter().enumerate() {
+        let marker = if high_scores.latest == Some((mode, index)) {
+            '>'
+        } else {
+            ' '
+        };
+        let outcome = match entry.outcome {
+            WinOrGameOver::Win => "Win",
+            WinOrGameOver::GameOver => "Lost",
+        };
+        _ = write!(
+            text,
+            "{marker}{rank:2}. {score:08}  {outcome:4}  {time:>8}",
+            rank = index + 1,
+            score = entry.score,
+            time = format_run_time(entry.duration_secs),
+        );
+        if show_seeds {
+            _ = write!(text, "  {}", format_seed(entry.seed));
+        }
+        text.push('\n');
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An entry which can be told apart from others with the same score by its `seed`.
+    fn entry(score: u64, seed: u64) -> HighScoreEntry {
+        HighScoreEntry {
+            score,
+            outcome: WinOrGameOver::GameOver,
+            duration_secs: 60.0,
+            seed,
+        }
+    }
+
+    fn scores_and_seeds(high_scores: &HighScores, mode: GameMode) -> Vec<(u64, u64)> {
+        high_scores
+            .entries(mode)
+            .iter()
+            .map(|entry| (entry.score, entry.seed))
+            .collect()
+    }
+
+    #[test]
+    fn insert_orders_by_score() {
+        let mut high_scores = HighScores::default();
+        assert!(high_scores.insert(GameMode::Standard, entry(200, 1)));
+        assert!(high_scores.insert(GameMode::Standard, entry(300, 2)));
+        assert!(high_scores.insert(GameMode::Standard, entry(100, 3)));
+        assert_eq!(
+            scores_and_seeds(&high_scores, GameMode::Standard),
+            vec![(300, 2), (200, 1), (100, 3)]
+        );
+        assert_eq!(high_scores.latest, Some((GameMode::Standard, 2)));
+    }
+
+    #[test]
+    fn insert_puts_ties_after_earlier_runs() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(GameMode::Standard, entry(100, 1));
+        high_scores.insert(GameMode::Standard, entry(100, 2));
+        high_scores.insert(GameMode::Standard, entry(100, 3));
+        assert_eq!(
+            scores_and_seeds(&high_scores, GameMode::Standard),
+            vec![(100, 1), (100, 2), (100, 3)]
+        );
+        assert_eq!(high_scores.latest, Some((GameMode::Standard, 2)));
+    }
+
+    #[test]
+    fn insert_truncates_to_max_entries() {
+        let mut high_scores = HighScores::default();
+        for seed in 0..MAX_ENTRIES as u64 {
+            assert!(high_scores.insert(GameMode::Standard, entry(100 + seed, seed)));
+        }
+
+        // Lower than all of a full list, or tied with the last: does not make it.
+        assert!(!high_scores.insert(GameMode::Standard, entry(50, 100)));
+        assert!(!high_scores.insert(GameMode::Standard, entry(100, 101)));
+        assert_eq!(high_scores.entries(GameMode::Standard).len(), MAX_ENTRIES);
+        assert_eq!(high_scores.latest, Some((GameMode::Standard, 0)));
+
+        // Higher than the last: pushes it off the end.
+        assert!(high_scores.insert(GameMode::Standard, entry(105, 102)));
+        let entries = scores_and_seeds(&high_scores, GameMode::Standard);
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert!(!entries.contains(&(100, 0)));
+        // after the five higher scores and the existing 105
+        assert_eq!(high_scores.latest, Some((GameMode::Standard, 5)));
+    }
+
+    #[test]
+    fn insert_keeps_modes_separate() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(GameMode::Standard, entry(100, 1));
+        high_scores.insert(GameMode::Endless, entry(50, 2));
+        assert_eq!(
+            scores_and_seeds(&high_scores, GameMode::Standard),
+            vec![(100, 1)]
+        );
+        assert_eq!(
+            scores_and_seeds(&high_scores, GameMode::Endless),
+            vec![(50, 2)]
+        );
+        assert_eq!(high_scores.latest, Some((GameMode::Endless, 0)));
+    }
+}
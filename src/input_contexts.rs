@@ -0,0 +1,48 @@
+use bevy::ecs::change_detection::DetectChanges as _;
+use bevy::prelude as b;
+use bevy_enhanced_input::prelude as bei;
+
+use crate::{GameState, NonGameInput, Player};
+
+// -------------------------------------------------------------------------------------------------
+
+/// An input context whose actions should only be evaluated in some [`GameState`]s.
+///
+/// Giving each context an explicit set of states lets contexts share bindings, as long as
+/// they are never active at the same time.
+pub(crate) trait StateDependentContext: b::Component {
+    fn active_in(state: &GameState) -> bool;
+}
+
+impl StateDependentContext for Player {
+    fn active_in(state: &GameState) -> bool {
+        matches!(state, GameState::Playing)
+    }
+}
+
+impl StateDependentContext for NonGameInput {
+    fn active_in(state: &GameState) -> bool {
+        !matches!(state, GameState::AssetLoading)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Activates or deactivates every context entity of type `C` when the [`GameState`] changes
+/// or the entity is spawned.
+pub(crate) fn update_context_activity_system<C: StateDependentContext>(
+    mut commands: b::Commands,
+    state: b::Res<b::State<GameState>>,
+    contexts: b::Query<(b::Entity, b::Ref<C>)>,
+) {
+    let active = C::active_in(state.get());
+    for (entity, context) in contexts {
+        if state.is_changed() || context.is_added() {
+            commands.entity(entity).insert(if active {
+                bei::ContextActivity::<C>::ACTIVE
+            } else {
+                bei::ContextActivity::<C>::INACTIVE
+            });
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use core::fmt::Write as _;
+
+use bevy::ecs::entity::EntityHashSet;
+use bevy::ecs::schedule::{IntoScheduleConfigs as _, SystemCondition as _};
+use bevy::prelude as b;
+
+use crate::bullets_and_targets::Attackable;
+use crate::quantity::{Quantity, UpdateFromQuantity};
+use crate::{GameState, Lifetime};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Development aid which checks, at the end of every fixed tick, that entities which should have
+/// been cleaned up were, so that system ordering mistakes are noticed when they happen instead
+/// of as odd gameplay.
+///
+/// Only enabled in builds with debug assertions. A violation is reported as a system error,
+/// which stops the game unless [`ErrorReportPlugin`](crate::error_report::ErrorReportPlugin)
+/// is showing errors instead.
+pub(crate) struct InvariantsPlugin;
+
+impl b::Plugin for InvariantsPlugin {
+    fn build(&self, app: &mut b::App) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        app.add_systems(
+            b::FixedLast,
+            check_invariants_system
+                .run_if(b::in_state(GameState::Playing).or(b::in_state(GameState::WinOrGameOver))),
+        );
+    }
+}
+
+/// Entities which were found to be waiting for cleanup on the previous tick.
+/// Being found again on the next tick means the cleanup did not happen.
+#[derive(Debug, Default)]
+struct Pending {
+    expired: EntityHashSet,
+    dead: EntityHashSet,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+fn check_invariants_system(
+    mut pending: b::Local<Pending>,
+    lifetimes: b::Query<(b::Entity, &Lifetime)>,
+    attackables: b::Query<(b::Entity, &Attackable)>,
+    quantity_displays: b::Query<(b::Entity, &UpdateFromQuantity)>,
+    quantities: b::Query<(), b::With<Quantity>>,
+) -> b::Result {
+    let mut violations = String::new();
+
+    // Lifetimes which have run out should be despawned by expire_lifetimes on the same tick.
+    let expired: EntityHashSet = lifetimes
+        .iter()
+        .filter(|(_, lifetime)| lifetime.0 <= 0.0)
+        .map(|(entity, _)| entity)
+        .collect();
+    for &entity in expired.intersection(&pending.expired) {
+        writeln!(
+            violations,
+            "{entity} has had zero lifetime for more than one tick"
+        )?;
+    }
+    pending.expired = expired;
+
+    // Attackables with no health should be despawned by death_system on the same tick.
+    let dead: EntityHashSet = attackables
+        .iter()
+        .filter(|(_, attackable)| attackable.health == 0)
+        .map(|(entity, _)| entity)
+        .collect();
+    for &entity in dead.intersection(&pending.dead) {
+        writeln!(
+            violations,
+            "{entity} has had zero health for more than one tick"
+        )?;
+    }
+    pending.dead = dead;
+
+    // Quantity entities are permanent, so anything displaying one should never lose it.
+    for (entity, ufq) in quantity_displays {
+        if !quantities.contains(ufq.quantity_entity) {
+            writeln!(
+                violations,
+                "{entity} displays {quantity:?}, which is not a quantity",
+                quantity = ufq.quantity_entity
+            )?;
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(b::BevyError::from(format!(
+            "gameplay invariants violated:\n{violations}"
+        )))
+    }
+}
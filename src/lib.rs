@@ -0,0 +1,2208 @@
+use std::f32::consts::PI;
+
+use avian2d::prelude::{self as p, PhysicsTime as _};
+use bevy::app::PluginGroup as _;
+use bevy::ecs::change_detection::{DetectChanges, DetectChangesMut as _};
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::ecs::schedule::{IntoScheduleConfigs, SystemCondition as _};
+use bevy::ecs::spawn::SpawnRelated as _;
+use bevy::math::{Vec2, Vec3, Vec3Swizzles as _, vec2, vec3};
+use bevy::prelude as b;
+use bevy::prelude::StateSet as _;
+use bevy::state::app::AppExtStates as _;
+use bevy::utils::default;
+use bevy_asset_loader::asset_collection::AssetCollection; // required by derive macro :(
+use bevy_asset_loader::loading_state::LoadingStateAppExt as _;
+use bevy_asset_loader::loading_state::config::ConfigureLoadingState as _;
+use bevy_enhanced_input::prelude as bei;
+use bevy_enhanced_input::prelude::InputContextAppExt as _;
+use rand::RngExt as _;
+
+// -------------------------------------------------------------------------------------------------
+
+mod announcer;
+
+mod backdrop;
+
+mod bench;
+
+mod boss;
+
+mod bug_report;
+
+mod bullets_and_targets;
+use bullets_and_targets::Gun;
+
+mod controls_help;
+
+mod cutscene;
+
+mod dialog;
+
+mod difficulty;
+
+mod enemy;
+
+mod error_report;
+
+mod fallback_assets;
+
+mod game_mode;
+
+mod game_rng;
+
+mod game_speed;
+use game_speed::{GameSpeed, SpeedEffect};
+
+mod heat_vent;
+
+mod high_scores;
+
+mod input_contexts;
+
+mod invariants;
+
+mod lives;
+
+mod mods;
+
+mod objectives;
+
+mod pattern_editor;
+
+mod persistence;
+
+mod prefab;
+
+mod pickup;
+use pickup::Pickup;
+
+mod rendering;
+use rendering::{PLAYFIELD_LAYERS, SCALING_MARGIN, UI_LAYERS, Zees};
+
+mod quantity;
+use quantity::{Coherence, Fervor, Fever, Quantity};
+
+mod replay;
+
+mod run_stats;
+
+mod score;
+
+mod settings;
+
+#[cfg(test)]
+mod smoke_test;
+
+mod sound;
+
+mod status_effects;
+
+mod telemetry;
+
+mod text_effects;
+use text_effects::{TextEffect, TextEffectStyle};
+
+mod text_input;
+
+mod window;
+
+mod wrap;
+
+use crate::bullets_and_targets::Pattern;
+use crate::quantity::{QuantitiesMut, QuantityEntities, UpdateFromQuantity};
+
+// -------------------------------------------------------------------------------------------------
+
+const GAME_NAME: &str = "Interplanetary Recombobulator";
+
+/// Where and how to run the game, for [`GameConfig::default_plugins()`] and
+/// [`RecombobulatorPlugin`].
+///
+/// The default is the configuration of the standalone game.
+#[derive(Clone, Debug)]
+pub struct GameConfig {
+    /// Window to open, or [`None`] to run without a window or GPU.
+    window: Option<b::Window>,
+    /// Directory from which assets are loaded, relative to the executable’s directory or
+    /// `CARGO_MANIFEST_DIR`.
+    asset_path: String,
+    /// Directory for settings, high scores, and mods, instead of the platform’s usual one.
+    data_directory: Option<std::path::PathBuf>,
+    /// Command-line arguments, which may override settings or enable developer tools.
+    args: Vec<String>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            window: Some(b::Window {
+                title: GAME_NAME.to_owned(),
+                resolution: {
+                    let desired_scale = 2;
+                    let cautionary_fudge_pixels = 2;
+                    bevy::window::WindowResolution::new(
+                        SCREEN_SIZE.x * desired_scale + SCALING_MARGIN + cautionary_fudge_pixels,
+                        SCREEN_SIZE.y * desired_scale + SCALING_MARGIN + cautionary_fudge_pixels,
+                    )
+                },
+                // Anything smaller could not show the playfield at 1×;
+                // narrower than the screen is for `Settings::vertical_fit`.
+                resize_constraints: bevy::window::WindowResizeConstraints {
+                    min_width: PLAYFIELD_SIZE.x as f32,
+                    min_height: SCREEN_SIZE.y as f32,
+                    ..default()
+                },
+                ..default()
+            }),
+            asset_path: b::AssetPlugin::default().file_path,
+            data_directory: None,
+            args: Vec::new(),
+        }
+    }
+}
+
+impl GameConfig {
+    /// Opens `window` instead of the standalone game’s window.
+    pub fn window(mut self, window: b::Window) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    /// Runs with no window, no rendering, and no GPU, e.g. for simulation or testing.
+    pub fn headless(mut self) -> Self {
+        self.window = None;
+        self
+    }
+
+    /// Loads assets from `path` instead of `assets`.
+    pub fn asset_path(mut self, path: impl Into<String>) -> Self {
+        self.asset_path = path.into();
+        self
+    }
+
+    /// Reads and writes settings, high scores, and mods in `path` instead of the platform’s
+    /// usual data directory.
+    pub fn data_directory(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.data_directory = Some(path.into());
+        self
+    }
+
+    /// Takes options from the command-line arguments `args`, such as [`std::env::args()`].
+    /// Unrecognized arguments are ignored.
+    ///
+    /// The options are those which override settings, and these which enable developer tools:
+    ///
+    /// * `--show-errors` shows errors on screen instead of stopping the game.
+    /// * `--pattern-editor` enables the enemy pattern editor.
+    /// * `--bench-scenario` runs the benchmark scenario instead of the game.
+    /// * `--telemetry <directory>` writes a timeline of each run into the directory.
+    pub fn args(mut self, args: impl IntoIterator<Item = String>) -> Self {
+        self.args = args.into_iter().collect();
+        self
+    }
+
+    fn has_flag(&self, flag: &str) -> bool {
+        self.args.iter().any(|arg| arg == flag)
+    }
+
+    /// Bevy’s [`b::DefaultPlugins`], configured as the game needs them.
+    ///
+    /// An application which already has the engine’s plugins may use its own instead,
+    /// but the game assumes nearest-neighbor image sampling and 2D spatial audio.
+    pub fn default_plugins(&self) -> bevy::app::PluginGroupBuilder {
+        let plugins = b::DefaultPlugins
+            .set(bevy::audio::AudioPlugin {
+                default_spatial_scale: bevy::audio::SpatialScale::new_2d(0.001),
+                ..default()
+            })
+            .set(b::AssetPlugin {
+                file_path: self.asset_path.clone(),
+                // hot-reload assets while developing
+                watch_for_changes_override: Some(cfg!(debug_assertions)),
+                ..default()
+            })
+            .set(b::ImagePlugin::default_nearest())
+            .set(bevy::log::LogPlugin {
+                custom_layer: bug_report::recent_log_layer,
+                ..default()
+            });
+        match &self.window {
+            Some(window) => plugins.set(b::WindowPlugin {
+                primary_window: Some(window.clone()),
+                ..default()
+            }),
+            None => plugins
+                .disable::<bevy::winit::WinitPlugin>()
+                .set(b::WindowPlugin {
+                    primary_window: None,
+                    exit_condition: bevy::window::ExitCondition::DontExit,
+                    ..default()
+                })
+                .set(bevy::render::RenderPlugin {
+                    render_creation: bevy::render::settings::WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                }),
+        }
+    }
+}
+
+/// The whole game, as a plugin which can be added to an [`b::App`] that has the engine’s
+/// plugins, such as from [`GameConfig::default_plugins()`].
+#[derive(Debug, Default)]
+pub struct RecombobulatorPlugin {
+    config: GameConfig,
+}
+
+impl RecombobulatorPlugin {
+    pub fn new(config: GameConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl b::Plugin for RecombobulatorPlugin {
+    fn build(&self, app: &mut b::App) {
+        // before anything which loads persistent data
+        app.insert_resource(persistence::DataDirectory::new(
+            self.config.data_directory.clone(),
+        ));
+        add_game(app, &self.config);
+    }
+}
+
+/// Adds everything that makes up the game, except for the engine’s own plugins.
+fn add_game(app: &mut b::App, config: &GameConfig) {
+    app.add_plugins(game_speed::GameSpeedPlugin)
+        .add_plugins(prefab::PrefabPlugin)
+        .add_plugins(settings::SettingsPlugin {
+            args: config.args.clone(),
+        })
+        .add_plugins(high_scores::HighScoresPlugin)
+        .init_state::<GameState>()
+        .add_sub_state::<WinOrGameOver>()
+        .add_loading_state(
+            bevy_asset_loader::loading_state::LoadingState::new(GameState::AssetLoading)
+                .continue_to_state(GameState::Menu)
+                .on_failure_continue_to_state(GameState::Menu)
+                .load_collection::<GameAssets>(),
+        )
+        .add_plugins(bevy::sprite_render::Material2dPlugin::<
+            rendering::HeatHazeMaterial,
+        >::default())
+        .add_plugins(bevy_enhanced_input::EnhancedInputPlugin)
+        .add_input_context::<Player>()
+        .add_input_context::<NonGameInput>()
+        .add_input_context::<controls_help::ControlsPreview>()
+        .init_resource::<bevy::input_focus::InputFocus>()
+        .add_message::<quantity::QuantityDelta>()
+        .add_message::<quantity::QuantityHold>()
+        // Gameplay types, for inspection and serialization
+        .register_type::<Player>()
+        .register_type::<PlayerVelocity>()
+        .register_type::<Team>()
+        .register_type::<Star>()
+        .register_type::<Lifetime>()
+        .register_type::<DespawnNextTick>()
+        .register_type::<bullets_and_targets::Bullet>()
+        .register_type::<bullets_and_targets::Attackable>()
+        .register_type::<bullets_and_targets::Part>()
+        .register_type::<status_effects::StatusEffects>()
+        .register_type::<Gun>()
+        .register_type::<bullets_and_targets::PlayerWeapon>()
+        .register_type::<enemy::EnemySpawner>()
+        .register_type::<enemy::Threat>()
+        .register_type::<boss::Boss>()
+        .register_type::<score::ScoreValue>()
+        .register_type::<enemy::Cosmetic>()
+        .register_type::<enemy::EnemyShipAi>()
+        .register_type::<backdrop::BackdropSpawner>()
+        .register_type::<Pickup>()
+        .register_type::<pickup::Carried>()
+        .register_type::<Quantity>()
+        .register_type::<Coherence>()
+        .register_type::<Fever>()
+        .register_type::<Fervor>()
+        .register_type::<run_stats::RunStats>()
+        .init_resource::<run_stats::RunStats>()
+        .init_resource::<objectives::Objectives>()
+        .init_resource::<announcer::KillStreak>()
+        .init_resource::<controls_help::LastInputDevice>()
+        .init_resource::<game_rng::GameRng>()
+        .init_resource::<rendering::CameraZoom>()
+        .init_resource::<rendering::CanvasCursor>()
+        .init_resource::<enemy::SpawnPatterns>()
+        .init_resource::<boss::BossEncounter>()
+        .init_resource::<difficulty::Difficulty>()
+        .init_resource::<difficulty::AdaptiveDifficulty>()
+        .init_resource::<game_mode::GameMode>()
+        .init_resource::<heat_vent::HeatVentSpawner>()
+        .init_resource::<lives::Lives>()
+        .init_resource::<bullets_and_targets::ProjectileLedger>()
+        .init_resource::<score::Score>()
+        .init_resource::<score::Combo>()
+        .init_resource::<window::SessionRecord>()
+        .add_message::<run_stats::RunEvent>()
+        .add_plugins(avian2d::PhysicsPlugins::default())
+        //.add_plugins(avian2d::prelude::PhysicsDebugPlugin::default())
+        .add_systems(
+            b::Startup,
+            (
+                rendering::setup_camera_system,
+                setup_non_game_input,
+                setup_status_text,
+                setup_permanent_gameplay,
+            ),
+        )
+        .add_systems(
+            b::OnExit(GameState::AssetLoading),
+            (
+                fallback_assets::insert_fallback_assets_if_missing,
+                (setup_ui, window::set_window_icon_system, spawn_starfield),
+            )
+                .chain(),
+        )
+        .add_systems(
+            b::OnEnter(GameState::Menu),
+            (
+                reset_quantities_for_new_game,
+                enemy::spawn_cosmetic_spawner,
+                controls_help::spawn_controls_preview,
+            ),
+        )
+        .add_systems(
+            b::OnExit(GameState::Menu),
+            (
+                enemy::despawn_cosmetic_entities,
+                dialog::close_dialogs,
+                controls_help::despawn_controls_preview,
+            ),
+        )
+        .add_systems(
+            b::OnTransition {
+                exited: GameState::Menu,
+                entered: GameState::Playing,
+            },
+            start_run_systems(),
+        )
+        .add_systems(
+            b::OnEnter(GameState::WinOrGameOver),
+            (
+                end_of_game_effects,
+                enemy::freeze_enemies,
+                window::count_session_record,
+            ),
+        )
+        .add_systems(b::OnExit(GameState::WinOrGameOver), despawn_game)
+        // abandoning a run
+        .add_systems(
+            b::OnTransition {
+                exited: GameState::Paused,
+                entered: GameState::Menu,
+            },
+            despawn_game,
+        )
+        // restarting a run
+        .add_systems(
+            b::OnEnter(GameState::Playing),
+            (
+                despawn_game,
+                start_run_systems(),
+                |mut commands: b::Commands| commands.remove_resource::<RestartRun>(),
+            )
+                .chain()
+                .run_if(b::resource_exists::<RestartRun>),
+        )
+        .add_systems(b::OnEnter(GameState::Paused), pause)
+        .add_systems(
+            b::OnExit(GameState::Paused),
+            (unpause, dialog::close_dialogs),
+        )
+        .add_observer(pause_unpause_observer)
+        .add_observer(escape_observer)
+        .add_observer(restart_observer)
+        .add_observer(dialog::ask_abandon_run_observer)
+        .add_observer(dialog::ask_seed_observer)
+        .add_observer(dialog::play_seed_observer)
+        .add_observer(dialog::close_dialogs_observer)
+        .add_observer(dialog::quit_to_menu_observer)
+        .add_observer(persistence::quit_game_observer)
+        .add_systems(
+            b::Update,
+            // UI systems
+            (
+                wrap_stars_system,
+                rendering::fit_canvas_to_window_system,
+                rendering::update_heat_haze_system,
+                (
+                    rendering::fade_trail_images_system,
+                    bullets_and_targets::early_fade_system,
+                )
+                    .chain(),
+                rendering::update_camera_zoom_system,
+                (update_status_text_system, text_effects::text_effect_system).chain(),
+                run_stats::update_run_timer_text_system,
+                score::update_score_text_system,
+                score::update_combo_text_system,
+                lives::update_lives_text_system,
+                objectives::update_objectives_text_system,
+                announcer::expire_callout_system,
+                game_rng::update_seed_text_system,
+                text_input::text_input_system,
+                (
+                    button_system,
+                    // every frame, since the buttons are respawned with the menu
+                    // without the difficulty having changed
+                    difficulty::difficulty_button_system,
+                )
+                    .chain(),
+                set_ui_visibility_from_state,
+                window::update_window_title_system.run_if(
+                    b::state_changed::<GameState>
+                        .or(b::state_changed::<WinOrGameOver>)
+                        .or(b::resource_changed::<settings::Settings>)
+                        .or(window::presence_in_title),
+                ),
+                (
+                    controls_help::track_input_device_system,
+                    controls_help::update_controls_text_system,
+                )
+                    .chain(),
+                input_contexts::update_context_activity_system::<Player>,
+                input_contexts::update_context_activity_system::<NonGameInput>,
+            ),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            // These need to be ordered because which order they run in affects mechanics,
+            // such as bullet range and who wins.
+            (
+                // despawning may continue when dead/won
+                (despawn_next_tick, expire_lifetimes),
+                (
+                    apply_movement,
+                    pickup::pickup_system,
+                    bullets_and_targets::gun_cooldown,
+                    enemy::enemy_ship_ai,
+                    boss::boss_ai_system,
+                    bullets_and_targets::homing_bullets_system,
+                    bullets_and_targets::fire_gun_system,
+                )
+                    .chain()
+                    .run_if(simulation_running),
+                bullets_and_targets::cull_offscreen_bullets_system,
+                bullets_and_targets::bullet_hit_system, // hits may continue when dead/won
+                bullets_and_targets::fever_pressure_system.run_if(simulation_running),
+                heat_vent::heat_vent_system.run_if(simulation_running),
+                // continues after the run ends, so that the ship does not stay blinked out
+                lives::invulnerability_system,
+                status_effects::status_effects_system.run_if(simulation_running),
+                boss::boss_phase_system.run_if(simulation_running),
+                bullets_and_targets::player_health_is_fever_system.run_if(simulation_running),
+                bullets_and_targets::death_system,
+                boss::boss_death_system,
+            )
+                .chain()
+                .run_if(b::in_state(GameState::Playing).or(b::in_state(GameState::WinOrGameOver))),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            // Cosmetic, but on game time so that it freezes when paused.
+            (
+                (
+                    bullets_and_targets::hurt_cooldown_system,
+                    bullets_and_targets::hurt_flash_system,
+                    status_effects::status_tint_system,
+                )
+                    .chain(),
+                (
+                    rendering::spawn_trail_images_system,
+                    bullets_and_targets::cap_rendered_projectiles_system,
+                )
+                    .chain(),
+            )
+                .after(bullets_and_targets::bullet_hit_system)
+                .run_if(b::in_state(GameState::Playing).or(b::in_state(GameState::WinOrGameOver))),
+        )
+        .add_systems(
+            b::Update,
+            bullets_and_targets::refit_colliders_to_images_system,
+        )
+        .add_systems(b::Update, bullets_and_targets::beam_system)
+        .add_systems(b::PreUpdate, rendering::update_canvas_cursor_system)
+        .add_systems(b::Update, apply_aim.run_if(b::in_state(GameState::Playing)))
+        .add_systems(
+            b::PostUpdate,
+            sound::follow_emitters_system.before(bevy::transform::TransformSystems::Propagate),
+        )
+        .add_systems(
+            b::PostUpdate,
+            rendering::snap_ui_text_to_pixels_system
+                .after(bevy::transform::TransformSystems::Propagate),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            (
+                // deltas sent by hits after the run has ended are discarded
+                quantity::apply_quantity_deltas_system.run_if(simulation_running),
+                lives::lose_life_system.run_if(simulation_running),
+                bullets_and_targets::player_overheat_system.run_if(simulation_running),
+                quantity::quantity_behaviors_system.run_if(simulation_running),
+                (
+                    quantity::update_quantity_display_system_1,
+                    quantity::update_quantity_display_system_2,
+                ),
+            )
+                .chain()
+                // gameplay systems send QuantityDeltas which should be applied on the same frame
+                .after(bullets_and_targets::death_system),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            enemy::spawn_enemies_system
+                .run_if(b::in_state(GameState::Playing).or(b::in_state(GameState::Menu))),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            backdrop::spawn_backdrop_props_system.run_if(simulation_running),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            boss::spawn_boss_system.run_if(simulation_running),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            heat_vent::spawn_heat_vents_system.run_if(simulation_running),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            wrap::update_wrap_ghosts_system
+                .after(apply_movement)
+                .before(bullets_and_targets::bullet_hit_system),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            // Menu background simulation; see `enemy::Cosmetic`
+            (expire_lifetimes, enemy::enemy_ship_ai).run_if(b::in_state(GameState::Menu)),
+        )
+        .add_systems(
+            b::FixedUpdate,
+            (
+                run_stats::run_clock_system.run_if(simulation_running),
+                run_stats::count_run_events_system.run_if(simulation_running),
+                difficulty::adaptive_difficulty_system.run_if(simulation_running),
+                announcer::kill_streak_system.run_if(simulation_running),
+                score::combo_decay_system.run_if(simulation_running),
+                objectives::track_objectives_system
+                    .after(quantity::quantity_behaviors_system)
+                    .run_if(simulation_running),
+                run_stats::record_splits_system
+                    .after(quantity::quantity_behaviors_system)
+                    .run_if(simulation_running),
+                rendering::punch_on_big_kills_system.run_if(simulation_running),
+            )
+                .after(bullets_and_targets::death_system),
+        )
+        .add_plugins(telemetry::TelemetryPlugin {
+            directory: telemetry::directory_from_args(config.args.iter().cloned()),
+        })
+        .add_plugins(replay::ReplayPlugin {
+            import: replay::path_from_args(config.args.iter().cloned()),
+        })
+        .add_plugins(bench::BenchPlugin {
+            enabled: config.has_flag("--bench-scenario"),
+        })
+        .add_plugins(invariants::InvariantsPlugin)
+        .add_plugins(error_report::ErrorReportPlugin {
+            show_errors: config.has_flag("--show-errors"),
+        })
+        .add_plugins(bug_report::BugReportPlugin)
+        .add_plugins(pattern_editor::PatternEditorPlugin {
+            enabled: config.has_flag("--pattern-editor"),
+        })
+        .add_plugins(mods::ModsPlugin)
+        .add_plugins(cutscene::CutscenePlugin)
+        .add_observer(bullets_and_targets::hurt_side_effects_observer)
+        .add_observer(bullets_and_targets::player_input_fire_gun)
+        .add_observer(bullets_and_targets::player_input_bomb)
+        .add_observer(bullets_and_targets::player_input_switch_weapon)
+        .add_observer(bullets_and_targets::ledger_add_bullet_observer)
+        .add_observer(bullets_and_targets::ledger_remove_bullet_observer)
+        .add_observer(bullets_and_targets::ledger_add_cosmetic_observer)
+        .add_observer(bullets_and_targets::ledger_remove_cosmetic_observer);
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Size of UI enclosing playfield, for pixel rendering
+const SCREEN_SIZE: b::UVec2 = b::uvec2(640, 480);
+
+/// Size of the playfield.
+/// If you change this, the assets must be changed to match too
+const PLAYFIELD_SIZE: b::UVec2 = b::uvec2(320, 460);
+
+const SCREEN_RECT: b::Rect = b::Rect {
+    min: vec2(SCREEN_SIZE.x as f32 / -2., SCREEN_SIZE.y as f32 / -2.),
+    max: vec2(SCREEN_SIZE.x as f32 / 2., SCREEN_SIZE.y as f32 / 2.),
+};
+const PLAYFIELD_RECT: b::Rect = b::Rect {
+    min: vec2(PLAYFIELD_SIZE.x as f32 / -2., PLAYFIELD_SIZE.y as f32 / -2.),
+    max: vec2(PLAYFIELD_SIZE.x as f32 / 2., PLAYFIELD_SIZE.y as f32 / 2.),
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Player ship entity
+///
+/// Note that player-related entities are also identified by [`Team`].
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+#[require(
+    b::Transform,
+    p::CollidingEntities,
+    PlayerVelocity,
+    bullets_and_targets::WeaponUpgrades,
+    bullets_and_targets::PlayerWeapon
+)]
+struct Player;
+
+/// Velocity of the [`Player`] ship during the last tick, as limited by the edges of the playfield.
+///
+/// The ship is moved by [`apply_movement`] rather than by physics, so this is not
+/// a [`p::LinearVelocity`].
+#[derive(Clone, Copy, Debug, Default, b::Component, b::Reflect)]
+#[reflect(Component)]
+struct PlayerVelocity(Vec2);
+
+/// Which side of the fight this entity belongs to.
+/// Bullets and damageable entities need to be on a team.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, b::Component, b::Reflect, serde::Serialize)]
+#[reflect(Component)]
+enum Team {
+    Player,
+    Enemy,
+}
+impl Team {
+    pub fn should_hurt(self, other_team: Team) -> bool {
+        other_team != self
+    }
+}
+
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+struct Star {
+    /// Velocity the star normally moves at.
+    base_velocity: Vec2,
+}
+
+/// Decremented by game time and despawns the entity when it is zero.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+struct Lifetime(f32);
+
+/// Despawns the entity at the start of the next fixed-update tick, by [`despawn_next_tick`].
+///
+/// This is used for bullets which have hit something, so that they may hit every target
+/// they are touching this tick and not any more.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+struct DespawnNextTick;
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Default, b::States)]
+enum GameState {
+    #[default]
+    AssetLoading,
+
+    /// Game not started. Game entities do not exist.
+    Menu,
+
+    Playing,
+
+    Paused,
+
+    /// Game entities exist but are frozen.
+    WinOrGameOver,
+
+    /// Showing the [`high_scores::HighScores`]. Game entities do not exist.
+    HighScores,
+}
+
+#[derive(
+    Clone, Eq, PartialEq, Debug, Hash, Default, b::SubStates, serde::Serialize, serde::Deserialize,
+)]
+#[source(GameState = GameState::WinOrGameOver)]
+enum WinOrGameOver {
+    #[default]
+    GameOver,
+
+    Win,
+}
+
+#[derive(Debug, b::Component)]
+struct StatusText;
+
+#[derive(Debug, b::Component)]
+enum ButtonAction {
+    SetState(GameState),
+    /// Start a new run in the given [`game_mode::GameMode`].
+    NewGame(game_mode::GameMode),
+    /// Trigger [`dialog::AskAbandonRun`].
+    AskAbandonRun,
+    /// Trigger [`dialog::AskSeed`].
+    AskSeed,
+    /// Choose the [`difficulty::Difficulty`] of the next run.
+    SetDifficulty(difficulty::Difficulty),
+    /// Trigger [`dialog::CloseDialogs`].
+    CloseDialog,
+    /// Trigger [`dialog::QuitToMenu`].
+    QuitToMenu,
+    /// Trigger [`persistence::QuitGame`].
+    #[cfg_attr(target_family = "wasm", expect(dead_code))]
+    QuitGame,
+}
+
+#[derive(Debug, b::Component)]
+struct VisibleInState(GameState);
+
+/// Entity that is the parent of all entities making up a given Quantity's bar display
+#[derive(Debug, b::Component)]
+struct BarParent<T>(T);
+
+#[derive(Debug, b::Component)]
+struct BarLabelSprite<T>(T);
+
+/// The frame around the playfield, which is tinted to show gameplay status.
+#[derive(Debug, b::Component)]
+struct PlayfieldFrame;
+
+/// Marker next to the [`Fervor`] bar label which is shown when [`quantity::fervor_is_active()`]
+/// is close to changing.
+#[derive(Debug, b::Component)]
+struct FervorFlipMarker;
+
+/// Assets that will be loaded up-front before the game is willing to start,
+/// and kept loaded by the handles stored as a resource.
+#[derive(b::Resource, bevy_asset_loader::asset_collection::AssetCollection)]
+struct GameAssets {
+    // Enemy assets
+    #[asset(path = "prefabs/basic.enemy.ron")]
+    basic_enemy: b::Handle<prefab::EnemyPrefab>,
+    #[asset(path = "prefabs/tank.enemy.ron")]
+    tank_enemy: b::Handle<prefab::EnemyPrefab>,
+    #[asset(path = "prefabs/swarmer.enemy.ron")]
+    swarmer_enemy: b::Handle<prefab::EnemyPrefab>,
+    #[asset(path = "prefabs/sniper.enemy.ron")]
+    sniper_enemy: b::Handle<prefab::EnemyPrefab>,
+    #[asset(path = "enemy-bullet.png")]
+    enemy_bullet_sprite: b::Handle<b::Image>,
+    #[asset(path = "enemy-kill.ogg")]
+    enemy_kill_sound: b::Handle<b::AudioSource>,
+
+    // Player assets
+    #[asset(path = "player-ship.png")]
+    player_ship_sprite: b::Handle<b::Image>,
+    #[asset(path = "player-ship-heat.png")]
+    player_ship_heat_sprite: b::Handle<b::Image>,
+    #[asset(path = "player-bullet.png")]
+    player_bullet_sprite: b::Handle<b::Image>,
+    #[asset(path = "player-shoot.ogg")]
+    player_shoot_sound: b::Handle<b::AudioSource>,
+    #[asset(path = "player-hurt.ogg")]
+    player_hurt_sound: b::Handle<b::AudioSource>,
+
+    // Pickups
+    #[asset(path = "prefabs/all.pickups.ron")]
+    pickups: b::Handle<prefab::PickupPrefabs>,
+    #[asset(path = "pickup.ogg")]
+    pickup_sound: b::Handle<b::AudioSource>,
+
+    // UI
+    #[asset(path = "Kenney Future.ttf")]
+    ui_font: b::Handle<b::Font>,
+    #[asset(path = "Kenney Mini Square.ttf")]
+    small_prop_font: b::Handle<b::Font>,
+    #[asset(path = "Kenney Mini Square Mono.ttf")]
+    small_mono_font: b::Handle<b::Font>,
+    #[asset(path = "playfield-frame.png")]
+    playfield_frame_sprite: b::Handle<b::Image>,
+    #[asset(path = "bar-frame.png")]
+    bar_frame_sprite: b::Handle<b::Image>,
+    #[asset(path = "bar-fill-base.png")]
+    bar_fill_base_sprite: b::Handle<b::Image>,
+    #[asset(path = "bar-fill-temporary.png")]
+    bar_fill_temporary_sprite: b::Handle<b::Image>,
+    #[asset(path = "text-bar-coherence.png")]
+    text_bar_coherence_sprite: b::Handle<b::Image>,
+    #[asset(path = "text-bar-fever.png")]
+    text_bar_fever_sprite: b::Handle<b::Image>,
+    #[asset(path = "text-bar-fervor.png")]
+    text_bar_fervor_sprite: b::Handle<b::Image>,
+    #[asset(path = "text-bar-fervor-inactive.png")]
+    text_bar_fervor_inactive_sprite: b::Handle<b::Image>,
+    #[asset(path = "screen-heat-fog.png")]
+    screen_heat_fog: b::Handle<b::Image>,
+
+    // Misc
+    #[asset(path = "star.png")]
+    star_sprite: b::Handle<b::Image>,
+    #[asset(path = "muzzle-flash.png")]
+    muzzle_flash_sprite: b::Handle<b::Image>,
+    #[asset(path = "heat-vent.png")]
+    heat_vent_sprite: b::Handle<b::Image>,
+
+    // Backdrop
+    #[asset(path = "prefabs/deep-space.backdrop.ron")]
+    backdrop: b::Handle<prefab::BackdropPrefab>,
+    #[asset(path = "backdrop-planet.png")]
+    backdrop_planet_sprite: b::Handle<b::Image>,
+    #[asset(path = "backdrop-derelict.png")]
+    backdrop_derelict_sprite: b::Handle<b::Image>,
+    #[asset(path = "backdrop-comet.png")]
+    backdrop_comet_sprite: b::Handle<b::Image>,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Context entity for inputs that shouldn’t depend on gameplay state, such as the escape key.
+#[derive(Debug, b::Component)]
+struct NonGameInput;
+
+#[derive(Debug, bei::InputAction)]
+#[action_output(b::Vec2)]
+struct Move;
+
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct Shoot;
+
+/// Spends Fervor to clear enemy bullets and damage all enemies;
+/// see [`bullets_and_targets::player_input_bomb`].
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct Bomb;
+
+/// Switches between the [`bullets_and_targets::PlayerWeapon`]s;
+/// see [`bullets_and_targets::player_input_switch_weapon`].
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct SwitchWeapon;
+
+/// Direction to fire in, when [`settings::Settings::twin_stick`] is enabled.
+/// The mouse cursor position is also used, by [`apply_aim`].
+#[derive(Debug, bei::InputAction)]
+#[action_output(b::Vec2)]
+struct Aim;
+
+/// Pauses and unpauses the game.
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct TogglePause;
+
+/// Pauses the game, or backs out of whatever is happening when not playing.
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct Escape;
+
+/// Abandons the current run and starts a new one with the same seed.
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct Restart;
+
+/// Raises [`settings::Settings::volume`].
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct VolumeUp;
+
+/// Lowers [`settings::Settings::volume`].
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct VolumeDown;
+
+/// Toggles fast game speed, in debug builds only.
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct DebugSpeedUp;
+
+// -------------------------------------------------------------------------------------------------
+
+impl GameAssets {
+    /// Width of every character in [`Self::small_mono_font()`].
+    const SMALL_MONO_FONT_ADVANCE: f32 = 6.0;
+
+    // these methods know what a good font size for pixel matching is
+    fn small_prop_font(&self) -> b::TextFont {
+        b::TextFont {
+            font: self.small_prop_font.clone(),
+            font_size: 8.0,
+            font_smoothing: bevy::text::FontSmoothing::None,
+            ..default()
+        }
+    }
+    fn small_mono_font(&self) -> b::TextFont {
+        b::TextFont {
+            font: self.small_mono_font.clone(),
+            font_size: 8.0,
+            font_smoothing: bevy::text::FontSmoothing::None,
+            ..default()
+        }
+    }
+
+    fn sprite(&self, id: SpriteId) -> b::Handle<b::Image> {
+        match id {
+            SpriteId::EnemyBullet => &self.enemy_bullet_sprite,
+            SpriteId::PlayerShip => &self.player_ship_sprite,
+            SpriteId::PlayerShipHeat => &self.player_ship_heat_sprite,
+            SpriteId::PlayerBullet => &self.player_bullet_sprite,
+            SpriteId::PlayfieldFrame => &self.playfield_frame_sprite,
+            SpriteId::BarFrame => &self.bar_frame_sprite,
+            SpriteId::BarFillBase => &self.bar_fill_base_sprite,
+            SpriteId::BarFillTemporary => &self.bar_fill_temporary_sprite,
+            SpriteId::TextBarCoherence => &self.text_bar_coherence_sprite,
+            SpriteId::TextBarFever => &self.text_bar_fever_sprite,
+            SpriteId::TextBarFervor => &self.text_bar_fervor_sprite,
+            SpriteId::TextBarFervorInactive => &self.text_bar_fervor_inactive_sprite,
+            SpriteId::ScreenHeatFog => &self.screen_heat_fog,
+            SpriteId::Star => &self.star_sprite,
+            SpriteId::MuzzleFlash => &self.muzzle_flash_sprite,
+            SpriteId::HeatVent => &self.heat_vent_sprite,
+            SpriteId::BackdropPlanet => &self.backdrop_planet_sprite,
+            SpriteId::BackdropDerelict => &self.backdrop_derelict_sprite,
+            SpriteId::BackdropComet => &self.backdrop_comet_sprite,
+        }
+        .clone()
+    }
+
+    fn sound(&self, id: SoundId) -> b::Handle<b::AudioSource> {
+        match id {
+            SoundId::EnemyKill => &self.enemy_kill_sound,
+            SoundId::PlayerShoot => &self.player_shoot_sound,
+            SoundId::PlayerHurt => &self.player_hurt_sound,
+            SoundId::Pickup => &self.pickup_sound,
+        }
+        .clone()
+    }
+}
+
+/// Names an image in [`GameAssets`], for [`GameAssets::sprite()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, b::Reflect, serde::Deserialize)]
+enum SpriteId {
+    EnemyBullet,
+    PlayerShip,
+    PlayerShipHeat,
+    PlayerBullet,
+    PlayfieldFrame,
+    BarFrame,
+    BarFillBase,
+    BarFillTemporary,
+    TextBarCoherence,
+    TextBarFever,
+    TextBarFervor,
+    TextBarFervorInactive,
+    ScreenHeatFog,
+    Star,
+    MuzzleFlash,
+    HeatVent,
+    BackdropPlanet,
+    BackdropDerelict,
+    BackdropComet,
+}
+
+/// Names a sound in [`GameAssets`], for [`GameAssets::sound()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Deserialize)]
+enum SoundId {
+    EnemyKill,
+    PlayerShoot,
+    PlayerHurt,
+    Pickup,
+}
+
+// -------------------------------------------------------------------------------------------------
+// Startup systems (not all literally `Startup` schedule)
+
+fn setup_non_game_input(mut commands: b::Commands) {
+    commands.spawn((
+        NonGameInput,
+        bei::actions!(
+            NonGameInput[(
+                bei::Action::<TogglePause>::new(),
+                bei::bindings![
+                    b::KeyCode::Tab,
+                    b::KeyCode::KeyP,
+                    b::GamepadButton::Start,
+                ],
+            ), (
+                bei::Action::<Escape>::new(),
+                bei::bindings![
+                    b::KeyCode::Escape,
+                    b::GamepadButton::Select
+                ],
+            ), (
+                bei::Action::<Restart>::new(),
+                bei::bindings![b::KeyCode::KeyR],
+            ), (
+                bei::Action::<VolumeUp>::new(),
+                bei::bindings![b::KeyCode::Equal, b::KeyCode::NumpadAdd],
+            ), (
+                bei::Action::<VolumeDown>::new(),
+                bei::bindings![b::KeyCode::Minus, b::KeyCode::NumpadSubtract],
+            ), (
+                bei::Action::<DebugSpeedUp>::new(),
+                bei::bindings![b::KeyCode::Backquote],
+            ), (
+                bei::Action::<bug_report::SaveBugReport>::new(),
+                bei::bindings![b::KeyCode::F9],
+            )]
+        ),
+    ));
+}
+
+/// Early startup doesn't need assets to be ready ... except for the UI font which we will patch in
+fn setup_status_text(mut commands: b::Commands) {
+    // Gameplay status text; also used for loading
+    commands.spawn((
+        StatusText,
+        TextEffect::default(),
+        b::Text2d::new(""),
+        bevy::text::TextBounds {
+            // if we don’t set this, the text wraps undesirably, maybe because it gets changed?
+            width: Some(PLAYFIELD_SIZE.x as f32),
+            height: None,
+        },
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        b::TextLayout::new_with_justify(b::Justify::Center),
+        bevy::sprite::Anchor::CENTER,
+        b::Transform::from_translation(vec3(0.0, 100.0, 0.0)),
+        UI_LAYERS,
+    ));
+}
+
+fn setup_ui(
+    mut commands: b::Commands,
+    assets: b::Res<GameAssets>,
+    quantity_entities: b::Res<QuantityEntities>,
+    status_text: b::Single<b::Entity, b::With<StatusText>>,
+) {
+    let QuantityEntities {
+        coherence,
+        fever,
+        fervor,
+    } = *quantity_entities;
+
+    commands.entity(*status_text).insert(b::TextFont {
+        font: assets.ui_font.clone(),
+        font_size: 26.0,
+        font_smoothing: bevy::text::FontSmoothing::None,
+        ..default()
+    });
+
+    commands.spawn((
+        PlayfieldFrame,
+        b::Sprite::from_image(assets.sprite(SpriteId::PlayfieldFrame)),
+        b::Transform::from_xyz(0., 0., Zees::UiFront.z()),
+        UI_LAYERS,
+    ));
+
+    commands
+        .spawn(bar_bundle(
+            Fever,
+            &assets,
+            assets.sprite(SpriteId::TextBarFever),
+            fever,
+            vec2(PLAYFIELD_RECT.min.x - 30.0, PLAYFIELD_RECT.min.y),
+            b::Color::srgb_u8(0xFF, 0x42, 0x42),
+        ))
+        .with_child(bar_threshold_marker_bundle(Fever::LOSE_THRESHOLD));
+    commands.spawn(bar_bundle(
+        Coherence,
+        &assets,
+        assets.sprite(SpriteId::TextBarCoherence),
+        coherence,
+        vec2(PLAYFIELD_RECT.max.x + 30.0, PLAYFIELD_RECT.min.y),
+        b::Color::srgb_u8(0xAA, 0xFF, 0x33),
+    ));
+    commands
+        .spawn(bar_bundle(
+            Fervor,
+            &assets,
+            assets.sprite(SpriteId::TextBarFervor),
+            fervor,
+            vec2(PLAYFIELD_RECT.max.x + 70.0, PLAYFIELD_RECT.min.y),
+            b::Color::srgb_u8(0x55, 0xAA, 0xFF),
+        ))
+        .with_children(|bar| {
+            bar.spawn(bar_threshold_marker_bundle(Fervor::WIN_THRESHOLD));
+            bar.spawn((
+                FervorFlipMarker,
+                b::Sprite {
+                    custom_size: Some(vec2(3.0, 3.0)),
+                    ..default()
+                },
+                b::Transform::from_translation(vec3(4.0, 12.0, Zees::UiFront2.z())),
+                b::Visibility::Hidden,
+                UI_LAYERS,
+            ));
+        });
+
+    // New Game and Quit buttons
+    commands
+        .spawn((
+            b::Node {
+                width: b::percent(100),
+                height: b::percent(100),
+                flex_direction: b::FlexDirection::Column,
+                align_items: b::AlignItems::Center,
+                justify_content: b::JustifyContent::Center,
+                row_gap: b::px(10),
+                ..default()
+            },
+            VisibleInState(GameState::Menu),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(b::Node {
+                    column_gap: b::px(10),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(sized_button_bundle(
+                        &assets,
+                        "New Game",
+                        ButtonAction::NewGame(game_mode::GameMode::Standard),
+                        b::px(180),
+                    ));
+                    row.spawn(sized_button_bundle(
+                        &assets,
+                        "Endless",
+                        ButtonAction::NewGame(game_mode::GameMode::Endless),
+                        b::px(180),
+                    ));
+                });
+            parent.spawn(button_bundle(&assets, "Play Seed", ButtonAction::AskSeed));
+            parent
+                .spawn(b::Node {
+                    column_gap: b::px(10),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for difficulty in difficulty::Difficulty::ALL {
+                        row.spawn(sized_button_bundle(
+                            &assets,
+                            difficulty.label(),
+                            ButtonAction::SetDifficulty(difficulty),
+                            b::px(120),
+                        ));
+                    }
+                });
+            parent.spawn(button_bundle(
+                &assets,
+                "High Scores",
+                ButtonAction::SetState(GameState::HighScores),
+            ));
+            // Web pages can't be quit
+            #[cfg(not(target_family = "wasm"))]
+            parent.spawn(button_bundle(&assets, "Quit", ButtonAction::QuitGame));
+        });
+
+    // Back to Menu button for Game Over
+    commands.spawn((
+        b::Node {
+            width: b::percent(100),
+            height: b::percent(100),
+            align_items: b::AlignItems::Center,
+            justify_content: b::JustifyContent::Center,
+            ..default()
+        },
+        VisibleInState(GameState::WinOrGameOver),
+        b::children![button_bundle(
+            &assets,
+            "Menu",
+            ButtonAction::SetState(GameState::Menu)
+        )],
+    ));
+
+    // Back to Menu button for High Scores, below the list
+    commands.spawn((
+        b::Node {
+            width: b::percent(100),
+            height: b::percent(100),
+            align_items: b::AlignItems::Center,
+            justify_content: b::JustifyContent::End,
+            flex_direction: b::FlexDirection::Column,
+            padding: b::UiRect::bottom(b::px(60)),
+            ..default()
+        },
+        VisibleInState(GameState::HighScores),
+        b::children![button_bundle(
+            &assets,
+            "Menu",
+            ButtonAction::SetState(GameState::Menu)
+        )],
+    ));
+
+    // Unpause, abandon, and quit buttons
+    commands
+        .spawn((
+            b::Node {
+                width: b::percent(100),
+                height: b::percent(100),
+                flex_direction: b::FlexDirection::Column,
+                align_items: b::AlignItems::Center,
+                justify_content: b::JustifyContent::Center,
+                row_gap: b::px(10),
+                ..default()
+            },
+            VisibleInState(GameState::Paused),
+        ))
+        .with_children(|parent| {
+            parent.spawn(button_bundle(
+                &assets,
+                "Resume",
+                ButtonAction::SetState(GameState::Playing),
+            ));
+            parent.spawn(button_bundle(&assets, "Menu", ButtonAction::AskAbandonRun));
+            #[cfg(not(target_family = "wasm"))]
+            parent.spawn(button_bundle(&assets, "Quit", ButtonAction::QuitGame));
+        });
+
+    commands.spawn(run_stats::run_timer_text_bundle(&assets));
+    commands.spawn(score::score_text_bundle(&assets));
+    commands.spawn(lives::lives_text_bundle(&assets));
+    commands.spawn(score::combo_text_bundle(&assets));
+    commands.spawn(objectives::objectives_text_bundle(&assets));
+    commands.spawn(announcer::callout_text_bundle(&assets));
+    commands.spawn(settings::volume_text_bundle(&assets));
+    commands.spawn(game_rng::seed_text_bundle(&assets));
+    commands.spawn(high_scores::high_scores_text_bundle(&assets));
+
+    // Controls and credits text
+    let text_margin = 6.0;
+    commands.spawn((
+        controls_help::ControlsText,
+        b::Text2d::new(""),
+        bevy::text::TextBounds {
+            width: Some(PLAYFIELD_RECT.min.x - SCREEN_RECT.min.x - 40.0),
+            height: None,
+        },
+        assets.small_prop_font(),
+        b::TextLayout::new_with_justify(b::Justify::Left),
+        bevy::sprite::Anchor::TOP_LEFT,
+        b::Transform::from_translation(vec3(
+            SCREEN_RECT.min.x + text_margin,
+            SCREEN_RECT.max.y - text_margin,
+            Zees::UiMiddle.z(),
+        )),
+        UI_LAYERS,
+    ));
+    commands.spawn((
+        b::Text2d::new(indoc::indoc! {
+            "
+                Code and art
+                by kpreid
+                switchb.org/kpreid/
+                github.com/kpreid/
+                
+                Some fonts
+                by Kenney 
+                www.kenney.nl/
+
+                Sound effects
+                made with jsfxr
+                sfxr.me/
+
+                Made with Bevy
+                for Bevy Jam #7
+                bevy.org/
+            ",
+        }),
+        assets.small_prop_font(),
+        b::TextLayout::new_with_justify(b::Justify::Left),
+        bevy::sprite::Anchor::BOTTOM_LEFT,
+        b::Transform::from_translation(vec3(
+            SCREEN_RECT.min.x + text_margin,
+            SCREEN_RECT.min.y + text_margin,
+            Zees::UiMiddle.z(),
+        )),
+        UI_LAYERS,
+    ));
+
+    commands.spawn((
+        b::Sprite {
+            image: assets.sprite(SpriteId::ScreenHeatFog),
+            custom_size: Some(SCREEN_SIZE.as_vec2()),
+            image_mode: b::SpriteImageMode::Tiled {
+                tile_x: true,
+                tile_y: true,
+                stretch_value: 2.0,
+            },
+            ..default()
+        },
+        b::Transform::from_translation(vec3(0.0, 0.0, Zees::FullScreenCover.z())),
+        PLAYFIELD_LAYERS,
+        UpdateFromQuantity {
+            quantity_entity: fever,
+            property: quantity::UpdateProperty::TemporaryValue,
+            effect: quantity::UpdateEffect::PulsingOpacity,
+        },
+    ));
+}
+
+fn button_bundle(assets: &GameAssets, label: &str, action: ButtonAction) -> impl b::Bundle {
+    sized_button_bundle(assets, label, action, b::px(240))
+}
+
+/// A [`button_bundle()`] of a different width, for putting several side by side.
+fn sized_button_bundle(
+    assets: &GameAssets,
+    label: &str,
+    action: ButtonAction,
+    width: b::Val,
+) -> impl b::Bundle {
+    let text_bundle = (
+        b::Text::new(label),
+        b::TextFont {
+            font: assets.ui_font.clone(),
+            font_size: 27.0,
+            ..default()
+        },
+        b::TextLayout::new_with_justify(b::Justify::Left),
+        b::TextColor(b::Color::srgb(0.9, 0.9, 0.9)),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+    );
+    (
+        b::Button,
+        b::Node {
+            width,
+            height: b::px(65),
+            border: b::UiRect::all(b::px(5)),
+            justify_content: b::JustifyContent::Center,
+            align_items: b::AlignItems::Center,
+            border_radius: b::BorderRadius::MAX,
+            ..default()
+        },
+        action,
+        b::BorderColor::all(b::Color::WHITE),
+        b::BackgroundColor(NORMAL_BUTTON),
+        b::children![text_bundle],
+    )
+}
+
+/// Build the UI for a [`Quantity`] bar
+fn bar_bundle<Marker: Copy + Send + Sync + 'static>(
+    marker: Marker,
+    assets: &GameAssets,
+    label: b::Handle<b::Image>,
+    quantity_entity: b::Entity,
+    position: Vec2,
+    tint: bevy::color::Color,
+) -> impl b::Bundle {
+    // Layout in bar-local coordinates: +X is along the bar, and +Y is away from the playfield.
+    let bar_frame_thickness = 4.0;
+    let label_position = vec2(10.0, 12.0);
+    // Space reserved for the longest label sprite.
+    let label_column_width = 96.0;
+    let percentage_column_width =
+        quantity::PERCENTAGE_TEXT_CHARS as f32 * GameAssets::SMALL_MONO_FONT_ADVANCE;
+    // Percentage texts are positioned by their bottom right corners.
+    let base_percentage_position = vec3(
+        label_position.x + label_column_width + percentage_column_width,
+        quantity::BAR_WIDTH / 2.0 + bar_frame_thickness / 2.0,
+        Zees::UiFront2.z(),
+    );
+    let temporary_percentage_position = base_percentage_position
+        + vec3(
+            percentage_column_width + GameAssets::SMALL_MONO_FONT_ADVANCE,
+            0.0,
+            0.0,
+        );
+    let percentage_font = assets.small_mono_font();
+
+    (
+        BarParent(marker),
+        b::children![
+            (
+                b::Sprite::from_image(assets.sprite(SpriteId::BarFrame)),
+                b::Transform::from_translation(vec3(-bar_frame_thickness, 0.0, Zees::UiFront.z())),
+                bevy::sprite::Anchor::CENTER_LEFT,
+                UI_LAYERS,
+            ),
+            (
+                b::Sprite {
+                    image: assets.sprite(SpriteId::BarFillBase),
+                    image_mode: quantity::BAR_FILL_IMAGE_MODE,
+                    color: tint,
+                    ..default()
+                },
+                b::Transform::from_translation(vec3(0.0, 0.0, Zees::UiBack.z())),
+                bevy::sprite::Anchor::CENTER_LEFT,
+                quantity::UpdateFromQuantity {
+                    quantity_entity,
+                    property: quantity::UpdateProperty::BaseValue,
+                    effect: quantity::UpdateEffect::BarLength,
+                },
+                UI_LAYERS,
+            ),
+            (
+                b::Sprite {
+                    image: assets.sprite(SpriteId::BarFillTemporary),
+                    image_mode: quantity::BAR_FILL_IMAGE_MODE,
+                    color: tint,
+                    ..default()
+                },
+                b::Transform::from_translation(vec3(0.0, 0.0, Zees::UiMiddle.z())),
+                bevy::sprite::Anchor::CENTER_LEFT,
+                quantity::UpdateFromQuantity {
+                    quantity_entity,
+                    property: quantity::UpdateProperty::TemporaryValue,
+                    effect: quantity::UpdateEffect::BarLength,
+                },
+                UI_LAYERS,
+            ),
+            (
+                // Fancy label sprite
+                BarLabelSprite(marker),
+                b::Sprite::from_image(label),
+                bevy::sprite::Anchor::CENTER_LEFT,
+                b::Transform::from_translation(label_position.extend(Zees::UiFront2.z())),
+                UI_LAYERS,
+            ),
+            (
+                // Text for base percentage
+                b::Text2d::new(""),
+                b::TextLayout::new_with_justify(b::Justify::Right),
+                bevy::sprite::Anchor::BOTTOM_RIGHT,
+                percentage_font.clone(),
+                quantity::UpdateFromQuantity {
+                    quantity_entity,
+                    property: quantity::UpdateProperty::BaseValue,
+                    effect: quantity::UpdateEffect::TextPercentage
+                },
+                b::Transform::from_translation(base_percentage_position),
+                UI_LAYERS,
+            ),
+            (
+                // Text for temporary percentage
+                b::Text2d::new(""),
+                b::TextLayout::new_with_justify(b::Justify::Right),
+                bevy::sprite::Anchor::BOTTOM_RIGHT,
+                percentage_font,
+                quantity::UpdateFromQuantity {
+                    quantity_entity,
+                    property: quantity::UpdateProperty::TemporaryStack,
+                    effect: quantity::UpdateEffect::TextPercentage
+                },
+                b::Transform::from_translation(temporary_percentage_position),
+                UI_LAYERS,
+            )
+        ],
+        b::Visibility::Hidden,
+        quantity::UpdateFromQuantity {
+            quantity_entity,
+            property: quantity::UpdateProperty::TemporaryValue,
+            effect: quantity::UpdateEffect::VisibleIfEverNotZero,
+        },
+        b::Transform {
+            translation: position.extend(0.0),
+            rotation: b::Quat::from_rotation_z(PI / 2.),
+            ..default()
+        },
+    )
+}
+
+/// Build a tick mark across a [`Quantity`] bar at the given value, to be a child of the
+/// [`bar_bundle()`] entity.
+fn bar_threshold_marker_bundle(threshold: f32) -> impl b::Bundle {
+    (
+        b::Sprite {
+            color: b::Color::WHITE,
+            custom_size: Some(vec2(2.0, quantity::BAR_WIDTH + 6.0)),
+            ..default()
+        },
+        b::Transform::from_translation(vec3(
+            quantity::BAR_LENGTH * threshold,
+            0.0,
+            Zees::UiFront2.z(),
+        )),
+        UI_LAYERS,
+    )
+}
+
+/// Spawn the entities that participate in gameplay rules and which exist forever.
+/// Also the input bindings that don’t relate to the player ship.
+fn setup_permanent_gameplay(mut commands: b::Commands, settings: b::Res<settings::Settings>) {
+    let starting = settings.starting_quantities.clamped();
+    let coherence = commands
+        .spawn((Coherence, Quantity::new(starting.coherence)))
+        .id();
+    let fever = commands.spawn((Fever, Quantity::new(starting.fever))).id();
+    let fervor = commands
+        .spawn((Fervor, Quantity::new(starting.fervor)))
+        .id();
+    commands.insert_resource(QuantityEntities {
+        coherence,
+        fever,
+        fervor,
+    });
+}
+
+#[allow(
+    clippy::type_complexity,
+    reason = "the bars to hide have either of two markers"
+)]
+fn reset_quantities_for_new_game(
+    mut quantities: QuantitiesMut,
+    settings: b::Res<settings::Settings>,
+    bars_to_hide: b::Query<
+        &mut b::Visibility,
+        b::Or<(b::With<BarParent<Coherence>>, b::With<BarParent<Fervor>>)>,
+    >,
+) -> b::Result {
+    let starting = settings.starting_quantities.clamped();
+    let [mut coherence, mut fever, mut fervor] = quantities.all_mut()?;
+    *coherence = Quantity::new(starting.coherence);
+    *fever = Quantity::new(starting.fever);
+    *fervor = Quantity::new(starting.fervor);
+
+    // Reset sticky visibility of bars
+    for mut bar_vis in bars_to_hide {
+        *bar_vis = b::Visibility::Hidden;
+    }
+    Ok(())
+}
+
+/// Systems which reset everything for a new run and start it, in order.
+///
+/// These are used both when starting a run from the menu and when restarting one,
+/// so that the two cannot get out of step.
+fn start_run_systems() -> bevy::ecs::schedule::ScheduleConfigs<bevy::ecs::system::ScheduleSystem> {
+    (
+        reset_quantities_for_new_game,
+        run_stats::reset_run_stats,
+        boss::reset_boss_encounter,
+        heat_vent::reset_heat_vents,
+        lives::reset_lives,
+        difficulty::reset_adaptive_difficulty,
+        score::reset_score,
+        start_new_game,
+        objectives::choose_objectives,
+    )
+        .chain()
+}
+
+#[allow(
+    clippy::too_many_arguments,
+    reason = "a new run resets many independent resources"
+)]
+fn start_new_game(
+    mut commands: b::Commands,
+    assets: b::Res<GameAssets>,
+    quantity_entities: b::Res<QuantityEntities>,
+    mut game_speed: b::ResMut<GameSpeed>,
+    mut game_rng: b::ResMut<game_rng::GameRng>,
+    restart: Option<b::Res<RestartRun>>,
+    chosen_seed: Option<b::Res<game_rng::ChosenSeed>>,
+    settings: b::Res<settings::Settings>,
+) {
+    game_speed.clear_effects();
+
+    let seed = if let Some(chosen_seed) = chosen_seed {
+        commands.remove_resource::<game_rng::ChosenSeed>();
+        chosen_seed.0
+    } else if restart.is_some() {
+        game_rng.seed()
+    } else {
+        rand::random()
+    };
+    game_rng.reseed(seed);
+    bevy::log::info!("starting run with seed {}", game_rng::format_seed(seed));
+
+    let player = commands
+        .spawn((
+            Player,
+            Team::Player,
+            bullets_and_targets::Attackable {
+                // any health below the max translates into fever increase via player_health_is_fever_system()
+                health: u8::MAX,
+                max_health: u8::MAX,
+                hurt_animation_cooldown: 0.0,
+                hurt_animation_damage: 0,
+                destruction_particle: None, // TODO: add one
+                hurt_sound: assets.sound(SoundId::PlayerHurt),
+                hurt_pitch: 1.0,
+                last_hit_by: None,
+            },
+            // note: this sprite needs to not be a child so hurt_flash_system can modify it
+            b::Sprite::from_image(assets.sprite(SpriteId::PlayerShip)),
+            b::Transform::from_xyz(0., PLAYFIELD_RECT.min.y + 20.0, Zees::Player.z()),
+            PLAYFIELD_LAYERS,
+            b::Visibility::Visible,
+            p::Collider::circle(PLAYER_COLLIDER_RADIUS),
+            Gun {
+                cooldown: 0.0,
+                base_cooldown: bullets_and_targets::PLAYER_GUN_COOLDOWN,
+                trigger: false,
+                pattern: Pattern::COHERENT,
+                aim: 0.0,
+                shoot_sound: (
+                    assets.sound(SoundId::PlayerShoot),
+                    bevy::audio::Volume::Decibels(-10.),
+                ),
+            },
+            b::children![(
+                b::Sprite::from_image(assets.sprite(SpriteId::PlayerShipHeat)),
+                b::Transform::from_xyz(0., 0., Zees::AbovePlayer.z() - Zees::Player.z()),
+                UpdateFromQuantity {
+                    quantity_entity: quantity_entities.fever,
+                    property: quantity::UpdateProperty::TemporaryValue,
+                    effect: quantity::UpdateEffect::Opacity,
+                },
+            )],
+        ))
+        .id();
+
+    insert_player_actions::<Player>(&mut commands.entity(player), &settings);
+
+    if settings.wrap_playfield {
+        commands.spawn(wrap::wrap_ghost_bundle(&assets, player));
+    }
+
+    commands.spawn(enemy::EnemySpawner { cooldown: 0.0 });
+    commands.spawn(backdrop::BackdropSpawner { cooldown: 10.0 });
+}
+
+/// Gives `entity` the player’s actions and their bindings, in the input context `C`.
+///
+/// This is generic so that [`controls_help::ControlsPreview`] can have the same bindings.
+fn insert_player_actions<C: b::Component>(
+    entity: &mut bevy::ecs::system::EntityCommands<'_>,
+    settings: &settings::Settings,
+) {
+    if settings.twin_stick {
+        // right stick aims instead of moving
+        entity.insert(bei::actions!(C[
+            (
+                bei::Action::<Move>::new(),
+                bei::DeadZone::default(),
+                bei::Bindings::spawn((
+                    bei::Cardinal::wasd_keys(),
+                    bei::Cardinal::arrows(),
+                    bei::Cardinal::dpad(),
+                    bei::Axial::left_stick(),
+                )),
+            ),
+            (
+                bei::Action::<Aim>::new(),
+                bei::DeadZone::default(),
+                bei::Bindings::spawn(bei::Axial::right_stick()),
+            ),
+            shoot_action(),
+            bomb_action(),
+            switch_weapon_action(),
+        ]));
+    } else {
+        entity.insert(bei::actions!(C[
+            (
+                bei::Action::<Move>::new(),
+                bei::DeadZone::default(),
+                //bei::SmoothNudge::default(),
+                bei::Bindings::spawn((
+                    bei::Cardinal::wasd_keys(),
+                    bei::Cardinal::arrows(),
+                    bei::Cardinal::dpad(),
+                    bei::Axial::left_stick(),
+                    bei::Axial::right_stick(),
+                )),
+            ),
+            shoot_action(),
+            bomb_action(),
+            switch_weapon_action(),
+        ]));
+    }
+}
+
+fn shoot_action() -> impl b::Bundle {
+    (
+        bei::Action::<Shoot>::new(),
+        bei::bindings![
+            b::KeyCode::Space,
+            b::KeyCode::ShiftLeft,
+            b::KeyCode::ShiftRight,
+            b::KeyCode::Enter,
+            // let almost any button work for shooting including left-handed ones
+            b::GamepadButton::South,
+            b::GamepadButton::West,
+            b::GamepadButton::East,
+            b::GamepadButton::North,
+            b::GamepadButton::RightTrigger,
+            b::GamepadButton::RightTrigger2,
+            b::GamepadButton::LeftTrigger,
+            b::GamepadButton::LeftTrigger2,
+        ],
+    )
+}
+
+fn bomb_action() -> impl b::Bundle {
+    (
+        bei::Action::<Bomb>::new(),
+        bei::bindings![
+            b::KeyCode::KeyX,
+            b::KeyCode::KeyB,
+            // every face button and trigger already shoots
+            b::GamepadButton::LeftThumb,
+        ],
+    )
+}
+
+fn switch_weapon_action() -> impl b::Bundle {
+    (
+        bei::Action::<SwitchWeapon>::new(),
+        bei::bindings![
+            b::KeyCode::KeyQ,
+            b::KeyCode::KeyC,
+            b::GamepadButton::RightThumb,
+        ],
+    )
+}
+
+/// Resource present when entering [`GameState::Playing`] should replace the current or finished
+/// run with a new one using the same seed, instead of resuming it.
+#[derive(Debug, b::Resource)]
+struct RestartRun;
+
+/// Despawn everything [`start_new_game`] spawns, to return to the menu or restart
+#[allow(
+    clippy::type_complexity,
+    reason = "the filter lists every kind of thing a run spawns"
+)]
+fn despawn_game(
+    mut commands: b::Commands,
+    things: b::Query<
+        b::Entity,
+        (
+            b::Or<(
+                b::With<Team>,
+                b::With<enemy::EnemySpawner>,
+                b::With<backdrop::BackdropSpawner>,
+                b::With<wrap::WrapGhost>,
+                b::With<Lifetime>,
+            )>,
+            b::Without<Star>, // stars are not gameplay relevant and persist while not playing
+        ),
+    >,
+    // assets: b::Res<GameAssets>,
+) {
+    bevy::log::info!("despawn_game");
+    for entity in things {
+        commands.entity(entity).despawn();
+    }
+    // start_new_game(commands, assets)
+}
+
+/// Run condition for systems that affect the outcome or record of a run.
+///
+/// After the run is won or lost, bullets keep flying and hitting things for the sake of
+/// spectacle, but nothing that happens then may change quantities, stats, or enemy behavior.
+fn simulation_running(state: b::Res<b::State<GameState>>) -> bool {
+    *state.get() == GameState::Playing
+}
+
+/// Effects that occur on win or loss
+fn end_of_game_effects(
+    mut commands: b::Commands,
+    wog_state: b::Res<b::State<WinOrGameOver>>,
+    player_query: b::Query<b::Entity, b::With<Player>>,
+    mut game_speed: b::ResMut<GameSpeed>,
+) {
+    match **wog_state {
+        WinOrGameOver::GameOver => {
+            game_speed.add_effect(SpeedEffect::DEATH_SLOW_MOTION);
+
+            // Delete player ship. TODO: do a nice explosion
+            for player_ship in player_query {
+                commands.entity(player_ship).despawn();
+            }
+        }
+        WinOrGameOver::Win => {
+            // The win sequence despawns everything on the enemy team so it can't keep attacking
+            // the player or looking like it will, then shows the player leaving.
+            commands.insert_resource(cutscene::Cutscene::new(cutscene::WIN_SEQUENCE));
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Speed of the [`Player`] ship, in pixels per second.
+const PLAYER_SPEED: f32 = 180.0;
+
+/// Radius of the [`Player`] ship’s collider.
+const PLAYER_COLLIDER_RADIUS: f32 = 7.0;
+
+fn apply_movement(
+    action: b::Single<&bei::Action<Move>, b::With<bei::ActionOf<Player>>>,
+    time: b::Res<b::Time>,
+    settings: b::Res<settings::Settings>,
+    player_query: b::Query<(&mut b::Transform, &mut PlayerVelocity), b::With<Player>>,
+) -> b::Result {
+    let movement: b::Vec2 = ***action;
+    let dt = time.delta_secs();
+    for (mut transform, mut velocity) in player_query {
+        let old_position = transform.translation.xy();
+        let moved_position = old_position + movement * PLAYER_SPEED * dt;
+        let clamped_position = moved_position.clamp(PLAYFIELD_RECT.min, PLAYFIELD_RECT.max);
+        // Position after this tick’s movement but before wrapping, so that wrapping
+        // does not count as a jump in velocity.
+        let unwrapped_position = if settings.wrap_playfield {
+            vec2(moved_position.x, clamped_position.y)
+        } else {
+            clamped_position
+        };
+        transform.translation.x = if settings.wrap_playfield {
+            wrap::wrap_x(unwrapped_position.x)
+        } else {
+            unwrapped_position.x
+        };
+        transform.translation.y = unwrapped_position.y;
+        velocity.0 = if dt > 0.0 {
+            (unwrapped_position - old_position) / dt
+        } else {
+            Vec2::ZERO
+        };
+    }
+    Ok(())
+}
+
+/// Points the player’s gun, and ship, in the direction of the [`Aim`] action or the mouse cursor.
+///
+/// Does nothing unless [`settings::Settings::twin_stick`] is enabled, since otherwise the player
+/// has no [`Aim`] action.
+fn apply_aim(
+    aim_action: b::Single<&bei::Action<Aim>, b::With<bei::ActionOf<Player>>>,
+    mut cursor_moved: b::MessageReader<bevy::window::CursorMoved>,
+    cursor: b::Res<rendering::CanvasCursor>,
+    player: b::Single<(&mut b::Transform, &mut Gun), b::With<Player>>,
+    // Point the mouse was last moved to, so that the ship keeps facing it while moving.
+    mut cursor_target: b::Local<Option<Vec2>>,
+) {
+    let (mut transform, mut gun) = player.into_inner();
+
+    if cursor_moved.read().count() > 0 {
+        *cursor_target = cursor.playfield;
+    }
+
+    let stick: Vec2 = ***aim_action;
+    let direction = if stick != Vec2::ZERO {
+        // stick takes over until the mouse is moved again
+        *cursor_target = None;
+        stick
+    } else if let Some(target) = *cursor_target {
+        target - transform.translation.xy()
+    } else {
+        // no aiming input; keep the previous aim
+        return;
+    };
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    gun.aim = Vec2::Y.angle_to(direction);
+    transform.rotation = b::Quat::from_rotation_z(gun.aim);
+}
+
+// -------------------------------------------------------------------------------------------------
+// Other game behaviors use `run_if`; physics pausing needs explicit action
+
+fn pause(mut time: b::ResMut<b::Time<p::Physics>>) {
+    time.pause();
+}
+
+fn unpause(mut time: b::ResMut<b::Time<p::Physics>>) {
+    time.unpause();
+}
+
+fn pause_unpause_observer(
+    _event: b::On<bei::Start<TogglePause>>,
+    state: b::ResMut<b::State<GameState>>,
+    mut next_state: b::ResMut<b::NextState<GameState>>,
+) {
+    bevy::log::info!("pause_unpause");
+    (*next_state).set_if_neq(match *state.get() {
+        GameState::AssetLoading | GameState::HighScores => return,
+        GameState::Playing => GameState::Paused,
+        GameState::Paused | GameState::Menu => GameState::Playing,
+        GameState::WinOrGameOver => GameState::Menu,
+    });
+}
+
+/// Like [`pause_unpause_observer`], except that when paused, it asks whether to abandon the run,
+/// or cancels that question.
+fn escape_observer(
+    _event: b::On<bei::Start<Escape>>,
+    mut commands: b::Commands,
+    state: b::ResMut<b::State<GameState>>,
+    mut next_state: b::ResMut<b::NextState<GameState>>,
+    dialogs: b::Query<(), b::With<dialog::ModalDialog>>,
+) {
+    (*next_state).set_if_neq(match *state.get() {
+        GameState::AssetLoading => return,
+        GameState::Playing => GameState::Paused,
+        GameState::Paused => {
+            if dialogs.is_empty() {
+                commands.trigger(dialog::AskAbandonRun);
+            } else {
+                commands.trigger(dialog::CloseDialogs);
+            }
+            return;
+        }
+        GameState::Menu => {
+            if !dialogs.is_empty() {
+                commands.trigger(dialog::CloseDialogs);
+                return;
+            }
+            GameState::Playing
+        }
+        GameState::WinOrGameOver | GameState::HighScores => GameState::Menu,
+    });
+}
+
+/// Starts the run over, when there is a run to start over.
+fn restart_observer(
+    _event: b::On<bei::Start<Restart>>,
+    mut commands: b::Commands,
+    state: b::Res<b::State<GameState>>,
+    mut next_state: b::ResMut<b::NextState<GameState>>,
+) {
+    match *state.get() {
+        GameState::Paused | GameState::WinOrGameOver => {
+            commands.insert_resource(RestartRun);
+            next_state.set(GameState::Playing);
+        }
+        GameState::AssetLoading | GameState::Menu | GameState::Playing | GameState::HighScores => {}
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+fn despawn_next_tick(
+    mut commands: b::Commands,
+    query: b::Query<b::Entity, b::With<DespawnNextTick>>,
+) {
+    for entity in query {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn expire_lifetimes(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    query: b::Query<(b::Entity, &mut Lifetime)>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut lifetime) in query {
+        let new_lifetime = lifetime.0 - delta;
+        if new_lifetime > 0. {
+            lifetime.0 = new_lifetime;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Number of [`Star`]s in the starfield; divided by 3 if [`settings::Settings::low_spec`].
+const STAR_COUNT: usize = 240;
+
+/// Distance beyond the top and bottom of the playfield which stars travel before wrapping,
+/// so that they appear and disappear out of sight.
+const STAR_WRAP_MARGIN: f32 = 20.0;
+
+/// Horizontal distance beyond the playfield which stars may be placed in,
+/// so that they can be seen past its edges.
+const STAR_OVERFLOW_X: f32 = 30.0;
+
+/// Spawns the fixed set of [`Star`]s, scattered over the whole playfield.
+/// They are moved back to the top by [`wrap_stars_system`] instead of ever being despawned.
+fn spawn_starfield(
+    mut commands: b::Commands,
+    assets: b::Res<GameAssets>,
+    settings: b::Res<settings::Settings>,
+) {
+    let count = if settings.low_spec {
+        STAR_COUNT / 3
+    } else {
+        STAR_COUNT
+    };
+    let rng = &mut rand::rng();
+    for _ in 0..count {
+        let y = rng.random_range(
+            PLAYFIELD_RECT.min.y - STAR_WRAP_MARGIN..=PLAYFIELD_RECT.max.y + STAR_WRAP_MARGIN,
+        );
+        commands.spawn(star_bundle(&assets, vec2(random_star_x(rng), y)));
+    }
+}
+
+fn star_bundle(assets: &GameAssets, position: Vec2) -> impl b::Bundle {
+    let size = rand::rng().random_range(3.0..=6.0);
+    let speed = size * 3.0;
+    let velocity = vec2(0.0, -speed);
+    let size_exact = if size < 5.0 { 3.0 } else { 6.0 };
+    (
+        Star {
+            base_velocity: velocity,
+        },
+        b::Sprite::from_image(assets.sprite(SpriteId::Star)),
+        b::Transform::from_translation(position.extend(Zees::Starfield.z()))
+            .with_rotation(b::Quat::from_rotation_z(-velocity.angle_to(Vec2::NEG_Y)))
+            .with_scale(Vec3::splat(size_exact / 3.0)), // sprite size is 3
+        PLAYFIELD_LAYERS,
+        p::RigidBody::Kinematic,
+        p::Collider::circle(1.0), // TODO: use a simple movement system w/o physics so as not to exercise collision
+        p::LinearVelocity(velocity),
+    )
+}
+
+fn random_star_x(rng: &mut impl rand::Rng) -> f32 {
+    rng.random_range(
+        PLAYFIELD_RECT.min.x - STAR_OVERFLOW_X..=PLAYFIELD_RECT.max.x + STAR_OVERFLOW_X,
+    )
+}
+
+/// Moves each [`Star`] which has gone past the bottom of the playfield back up to the top,
+/// at a new horizontal position so that the pattern does not visibly repeat.
+fn wrap_stars_system(stars: b::Query<&mut b::Transform, b::With<Star>>) {
+    let bottom = PLAYFIELD_RECT.min.y - STAR_WRAP_MARGIN;
+    let height = PLAYFIELD_RECT.height() + STAR_WRAP_MARGIN * 2.0;
+    let rng = &mut rand::rng();
+    for mut transform in stars {
+        if transform.translation.y < bottom {
+            // rem_euclid rather than adding height once, in case of a large step
+            transform.translation.y =
+                bottom + (transform.translation.y - bottom).rem_euclid(height);
+            transform.translation.x = random_star_x(rng);
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+fn update_status_text_system(
+    state: b::Res<b::State<GameState>>,
+    wog_state: Option<b::Res<b::State<WinOrGameOver>>>,
+    cutscene: Option<b::Res<cutscene::Cutscene>>,
+    mut effect: b::Single<&mut TextEffect, b::With<StatusText>>,
+) {
+    let (new_text, style) = match *state.get() {
+        _ if cutscene.is_some() => ("", TextEffectStyle::Plain),
+        GameState::AssetLoading => ("Loading", TextEffectStyle::Plain),
+        GameState::Menu => (GAME_NAME, TextEffectStyle::Plain),
+        GameState::WinOrGameOver => match *wog_state.unwrap().get() {
+            WinOrGameOver::GameOver => ("Game Overheated", TextEffectStyle::Pulse),
+            WinOrGameOver::Win => ("Win", TextEffectStyle::Rainbow),
+        },
+        GameState::Playing => ("", TextEffectStyle::Plain),
+        GameState::Paused => ("Paused", TextEffectStyle::Plain),
+        GameState::HighScores => ("High Scores", TextEffectStyle::Plain),
+    };
+
+    effect.set(new_text, style);
+}
+
+// based off of https://bevy.org/examples/ui-user-interface/button/
+const NORMAL_BUTTON: b::Color = b::Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: b::Color = b::Color::srgb(0.5, 0.25, 0.25);
+const PRESSED_BUTTON: b::Color = b::Color::srgb(0.75, 0.75, 0.35);
+
+/// based off of https://bevy.org/examples/ui-user-interface/button/
+#[allow(
+    clippy::type_complexity,
+    reason = "only buttons whose interaction changed are queried"
+)]
+fn button_system(
+    mut commands: b::Commands,
+    mut input_focus: b::ResMut<bevy::input_focus::InputFocus>,
+    mut interaction_query: b::Query<
+        (
+            b::Entity,
+            Option<&ButtonAction>,
+            &b::Interaction,
+            &mut b::BackgroundColor,
+            &mut b::Button,
+        ),
+        b::Changed<b::Interaction>,
+    >,
+    // state: b::Res<b::State<GameState>>,
+    mut next_state: b::ResMut<b::NextState<GameState>>,
+    mut difficulty: b::ResMut<difficulty::Difficulty>,
+    mut game_mode: b::ResMut<game_mode::GameMode>,
+) {
+    for (entity, action, interaction, mut color, mut button) in &mut interaction_query {
+        match *interaction {
+            b::Interaction::Pressed => {
+                input_focus.set(entity);
+                *color = PRESSED_BUTTON.into();
+                button.set_changed();
+
+                // TODO: would be better if this went through the same kind of path as key bindings
+                match action {
+                    Some(ButtonAction::SetState(state)) => {
+                        next_state.set(state.clone());
+                    }
+                    Some(&ButtonAction::NewGame(mode)) => {
+                        game_mode.set_if_neq(mode);
+                        next_state.set(GameState::Playing);
+                    }
+                    Some(ButtonAction::AskAbandonRun) => commands.trigger(dialog::AskAbandonRun),
+                    Some(ButtonAction::AskSeed) => commands.trigger(dialog::AskSeed),
+                    Some(&ButtonAction::SetDifficulty(chosen)) => {
+                        difficulty.set_if_neq(chosen);
+                    }
+                    Some(ButtonAction::CloseDialog) => commands.trigger(dialog::CloseDialogs),
+                    Some(ButtonAction::QuitToMenu) => commands.trigger(dialog::QuitToMenu),
+                    Some(ButtonAction::QuitGame) => commands.trigger(persistence::QuitGame),
+                    None => b::warn!("Button {entity:?} has no action"),
+                }
+            }
+            b::Interaction::Hovered => {
+                input_focus.set(entity);
+                *color = HOVERED_BUTTON.into();
+                button.set_changed();
+            }
+            b::Interaction::None => {
+                input_focus.clear();
+                *color = NORMAL_BUTTON.into();
+                button.set_changed();
+            }
+        }
+    }
+}
+
+fn set_ui_visibility_from_state(
+    entities: b::Query<(&mut b::Visibility, &VisibleInState)>,
+    state: b::Res<b::State<GameState>>,
+    cutscene: Option<b::Res<cutscene::Cutscene>>,
+    mut was_in_cutscene: b::Local<bool>,
+) {
+    let in_cutscene = cutscene.is_some();
+    if !state.is_changed() && in_cutscene == *was_in_cutscene {
+        return;
+    }
+    *was_in_cutscene = in_cutscene;
+
+    // currently, just hides all buttons only in the new game state, or during a cutscene
+    for (mut visibility, expected_state) in entities {
+        *visibility = if expected_state.0 == **state && !in_cutscene {
+            b::Visibility::Inherited
+        } else {
+            b::Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::ecs::change_detection::DetectChangesMut as _;
+use bevy::math::{vec2, vec3};
+use bevy::prelude as b;
+
+use crate::quantity::{QuantitiesMut, QuantityKind};
+use crate::run_stats::RunEvent;
+use crate::settings::{Settings, update_hud_font_size};
+use crate::{Fever, GameAssets, GameState, PLAYFIELD_RECT, Player, UI_LAYERS, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Spare ships the player has left in the current run.
+///
+/// Fever reaching [`Fever::LOSE_THRESHOLD`] uses one up instead of ending the run, unless there
+/// are none left; see [`lose_life_system`].
+#[derive(Debug, b::Resource)]
+pub(crate) struct Lives(pub u8);
+
+/// The [`Player`] cannot be hurt, because they just lost a life.
+#[derive(Debug, b::Component)]
+pub(crate) struct Invulnerable {
+    /// Game time remaining before the player can be hurt again.
+    remaining: f32,
+}
+
+/// Text showing the remaining [`Lives`].
+#[derive(Debug, b::Component)]
+pub(crate) struct LivesText;
+
+/// [`Lives`] at the start of a run.
+const STARTING_LIVES: u8 = 2;
+
+/// Fever after losing a life.
+const LIFE_LOST_FEVER: f32 = 0.5;
+
+/// Time for which the player is [`Invulnerable`] after losing a life.
+const INVULNERABILITY_TIME: f32 = 2.0;
+
+/// Times per second the player’s ship blinks while [`Invulnerable`].
+const BLINK_RATE: f32 = 10.0;
+
+// -------------------------------------------------------------------------------------------------
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self(STARTING_LIVES)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn reset_lives(mut lives: b::ResMut<Lives>) {
+    *lives = Lives::default();
+}
+
+/// If Fever has reached [`Fever::LOSE_THRESHOLD`] and the player has a life to spare, uses it up
+/// to bring Fever back down to [`LIFE_LOST_FEVER`] and make the player briefly [`Invulnerable`].
+///
+/// Must run after this tick’s quantity changes and before
+/// [`quantity_behaviors_system`](crate::quantity::quantity_behaviors_system), which would
+/// otherwise end the run.
+pub(crate) fn lose_life_system(
+    mut commands: b::Commands,
+    mut lives: b::ResMut<Lives>,
+    mut quantities: QuantitiesMut,
+    player: b::Query<b::Entity, b::With<Player>>,
+    mut run_events: b::MessageWriter<RunEvent>,
+) -> b::Result {
+    let mut fever = quantities.kind_mut(QuantityKind::Fever)?;
+    if fever.effective_value() < Fever::LOSE_THRESHOLD || lives.0 == 0 {
+        return Ok(());
+    }
+    lives.0 -= 1;
+    fever.reset_to(LIFE_LOST_FEVER);
+    for player in player {
+        commands.entity(player).insert(Invulnerable {
+            remaining: INVULNERABILITY_TIME,
+        });
+    }
+    run_events.write(RunEvent::LifeLost);
+    Ok(())
+}
+
+/// Counts down [`Invulnerable`], blinking the player’s ship meanwhile.
+pub(crate) fn invulnerability_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    settings: b::Res<Settings>,
+    player: b::Query<(b::Entity, &mut Invulnerable, &mut b::Visibility)>,
+) {
+    for (entity, mut invulnerable, mut visibility) in player {
+        invulnerable.remaining -= time.delta_secs();
+        if invulnerable.remaining <= 0.0 {
+            commands.entity(entity).remove::<Invulnerable>();
+            visibility.set_if_neq(b::Visibility::Visible);
+            continue;
+        }
+        let shown = settings.reduced_motion || (invulnerable.remaining * BLINK_RATE) % 1.0 < 0.5;
+        visibility.set_if_neq(if shown {
+            b::Visibility::Visible
+        } else {
+            b::Visibility::Hidden
+        });
+    }
+}
+
+pub(crate) fn lives_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        LivesText,
+        b::Text2d::new(""),
+        assets.small_mono_font(),
+        b::TextLayout::new_with_justify(b::Justify::Left),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        bevy::sprite::Anchor::BOTTOM_LEFT,
+        b::Transform::from_translation(vec3(
+            PLAYFIELD_RECT.min.x + 4.0,
+            PLAYFIELD_RECT.min.y + 4.0,
+            Zees::UiFront2.z(),
+        )),
+        b::Visibility::Hidden,
+        UI_LAYERS,
+    )
+}
+
+pub(crate) fn update_lives_text_system(
+    lives: b::Res<Lives>,
+    assets: b::Res<GameAssets>,
+    settings: b::Res<Settings>,
+    state: b::Res<b::State<GameState>>,
+    text: b::Single<(&mut b::Text2d, &mut b::TextFont, &mut b::Visibility), b::With<LivesText>>,
+) {
+    let (mut text, font, mut visibility) = text.into_inner();
+
+    update_hud_font_size(&settings, &assets, font);
+
+    let visible = matches!(state.get(), GameState::Playing | GameState::Paused);
+    visibility.set_if_neq(if visible {
+        b::Visibility::Inherited
+    } else {
+        b::Visibility::Hidden
+    });
+
+    let new_text = format!("Spare ships: {}", lives.0);
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
@@ -0,0 +1,231 @@
+use std::path::Path;
+
+use bevy::prelude as b;
+
+use crate::enemy::{EnemyKind, PATTERN_HEIGHT, PATTERN_WIDTH, SpawnPattern, SpawnPatterns};
+use crate::persistence::DataDirectory;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Loads user content packs from the `mods` folder in the data directory.
+///
+/// Each subdirectory of `mods` is a pack. Currently, the only kind of content that can be
+/// modded is enemy formations: a pack may contain a `patterns.txt` file of [`SpawnPattern`]s,
+/// which are added to the built-in ones. In that file, each pattern is [`PATTERN_HEIGHT`] lines
+/// of up to [`PATTERN_WIDTH`] characters: a letter for an enemy, as in [`SpawnPattern`],
+/// or `.` for no enemy.
+/// Blank lines and lines starting with `#` are ignored.
+///
+/// Problems with mods are logged, and the offending file is skipped.
+pub(crate) struct ModsPlugin;
+
+impl b::Plugin for ModsPlugin {
+    fn build(&self, app: &mut b::App) {
+        app.add_systems(b::Startup, load_mods);
+    }
+}
+
+/// Directory in the data directory which contains the packs.
+pub(crate) const MODS_DIRECTORY: &str = "mods";
+
+/// File in a pack which contains [`SpawnPattern`]s.
+pub(crate) const PATTERNS_FILE: &str = "patterns.txt";
+
+// -------------------------------------------------------------------------------------------------
+
+fn load_mods(data_directory: b::Res<DataDirectory>, mut patterns: b::ResMut<SpawnPatterns>) {
+    let Some(mods_directory) = data_directory
+        .0
+        .as_ref()
+        .map(|dir| dir.join(MODS_DIRECTORY))
+    else {
+        return;
+    };
+    let packs = match std::fs::read_dir(&mods_directory) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return,
+        Err(error) => {
+            b::error!("failed to list {}: {error}", mods_directory.display());
+            return;
+        }
+    };
+
+    let mut pack_paths: Vec<_> = packs
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    // load in a consistent order so that seeded runs are repeatable
+    pack_paths.sort();
+
+    for pack_path in pack_paths {
+        let patterns_path = pack_path.join(PATTERNS_FILE);
+        if !patterns_path.exists() {
+            continue;
+        }
+        match load_patterns_file(&patterns_path) {
+            Ok(new_patterns) => {
+                b::info!(
+                    "loaded {} spawn patterns from {}",
+                    new_patterns.len(),
+                    patterns_path.display()
+                );
+                patterns.0.extend(new_patterns);
+            }
+            Err(error) => b::error!("skipping {}: {error}", patterns_path.display()),
+        }
+    }
+}
+
+fn load_patterns_file(path: &Path) -> Result<Vec<SpawnPattern>, String> {
+    let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+    parse_patterns(&text)
+}
+
+fn parse_patterns(text: &str) -> Result<Vec<SpawnPattern>, String> {
+    let mut patterns = Vec::new();
+    let mut current: SpawnPattern = [[b' '; PATTERN_WIDTH]; PATTERN_HEIGHT];
+    let mut rows_so_far = 0;
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            if rows_so_far != 0 {
+                return Err(format!(
+                    "line {line_number}: pattern ended after {rows_so_far} rows; \
+                     expected {PATTERN_HEIGHT}"
+                ));
+            }
+            continue;
+        }
+
+        let width = line.chars().count();
+        if width > PATTERN_WIDTH {
+            return Err(format!(
+                "line {line_number}: row is {width} characters wide; at most {PATTERN_WIDTH} allowed"
+            ));
+        }
+        for (xi, ch) in line.chars().enumerate() {
+            current[rows_so_far][xi] = match u8::try_from(ch) {
+                Ok(b'.' | b' ') => b' ',
+                Ok(byte) if EnemyKind::from_pattern_char(byte).is_some() => byte,
+                _ => {
+                    return Err(format!(
+                        "line {line_number}: unexpected character {ch:?}; \
+                         expected 'X', 'T', 'S', 'N', or '.'"
+                    ));
+                }
+            };
+        }
+
+        rows_so_far += 1;
+        if rows_so_far == PATTERN_HEIGHT {
+            if current.as_flattened().iter().all(|&ch| ch == b' ') {
+                return Err(format!("line {line_number}: pattern has no enemies"));
+            }
+            patterns.push(current);
+            current = [[b' '; PATTERN_WIDTH]; PATTERN_HEIGHT];
+            rows_so_far = 0;
+        }
+    }
+
+    if rows_so_far != 0 {
+        return Err(format!(
+            "end of file: pattern ended after {rows_so_far} rows; expected {PATTERN_HEIGHT}"
+        ));
+    }
+    Ok(patterns)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_patterns_reads_each_pattern() {
+        let patterns = parse_patterns(indoc::indoc! {"
+            # comments and blank lines separate patterns
+            X........X
+            ..........
+            ....TT
+            SSSS......
+
+            .........N
+            ..........
+            ..........
+            ..........
+        "})
+        .unwrap();
+        assert_eq!(
+            patterns,
+            vec![
+                [
+                    *b"X        X",
+                    *b"          ",
+                    *b"    TT    ",
+                    *b"SSSS      ",
+                ],
+                [
+                    *b"         N",
+                    *b"          ",
+                    *b"          ",
+                    *b"          ",
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_patterns_rejects_wide_row() {
+        let error = parse_patterns("X.........X\n").unwrap_err();
+        assert!(
+            error.starts_with("line 1: row is 11 characters wide"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn parse_patterns_rejects_short_pattern() {
+        let error = parse_patterns("X\nX\n\nX\n").unwrap_err();
+        assert!(
+            error.starts_with("line 3: pattern ended after 2 rows"),
+            "{error}"
+        );
+
+        let error = parse_patterns("X\nX\nX\n").unwrap_err();
+        assert!(
+            error.starts_with("end of file: pattern ended after 3 rows"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn parse_patterns_rejects_unknown_character() {
+        let error = parse_patterns("X..?\n").unwrap_err();
+        assert!(
+            error.starts_with("line 1: unexpected character '?'"),
+            "{error}"
+        );
+
+        // reported whole, not as one byte of its encoding
+        let error = parse_patterns("X..é\n").unwrap_err();
+        assert!(
+            error.starts_with("line 1: unexpected character 'é'"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn parse_patterns_rejects_empty_pattern() {
+        let error = parse_patterns("..........\n\n\n....\n").unwrap_err();
+        assert!(
+            error.starts_with("line 2: pattern ended after 1 rows"),
+            "{error}"
+        );
+
+        let error = parse_patterns("..\n..\n..\n..\n").unwrap_err();
+        assert_eq!(error, "line 4: pattern has no enemies");
+    }
+}
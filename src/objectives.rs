@@ -0,0 +1,206 @@
+use std::fmt::Write as _;
+
+use bevy::ecs::change_detection::DetectChangesMut as _;
+use bevy::math::{vec2, vec3};
+use bevy::prelude as b;
+use rand::seq::IndexedRandom as _;
+
+use crate::cutscene::Cutscene;
+use crate::game_rng::GameRng;
+use crate::pickup::PickupSpawnType;
+use crate::quantity::{Coherence, Quantities};
+use crate::run_stats::RunEvent;
+use crate::score::Score;
+use crate::{GameAssets, GameState, PLAYFIELD_RECT, UI_LAYERS, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// An optional goal for a run, which the player is shown and may try to achieve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Objective {
+    /// Collect this many pickups of the given type.
+    Collect(PickupSpawnType, u32),
+    /// Kill this many enemies.
+    Kill(u32),
+    /// Have [`Coherence`] reach this effective value.
+    ReachCoherence(f32),
+}
+
+/// All the [`Objective`]s a run may be given.
+const OBJECTIVES: &[Objective] = &[
+    Objective::Collect(PickupSpawnType::Cool, 10),
+    Objective::Collect(PickupSpawnType::Cohere, 4),
+    Objective::Kill(60),
+    Objective::ReachCoherence(0.8),
+];
+
+/// Number of [`Objective`]s given to each run.
+const OBJECTIVES_PER_RUN: usize = 2;
+
+/// Points added to the [`Score`] for completing an [`Objective`].
+const OBJECTIVE_BONUS: u64 = 5000;
+
+/// The [`Objective`]s of the current run and progress towards them.
+#[derive(Debug, Default, b::Resource)]
+pub(crate) struct Objectives(Vec<ObjectiveProgress>);
+
+#[derive(Debug)]
+struct ObjectiveProgress {
+    objective: Objective,
+    /// Count or value achieved so far, to be compared with [`Objective::goal()`].
+    progress: f32,
+}
+
+/// Text listing the current [`Objectives`].
+#[derive(Debug, b::Component)]
+pub(crate) struct ObjectivesText;
+
+// -------------------------------------------------------------------------------------------------
+
+impl Objective {
+    fn goal(self) -> f32 {
+        match self {
+            Objective::Collect(_, count) | Objective::Kill(count) => count as f32,
+            Objective::ReachCoherence(value) => value,
+        }
+    }
+
+    fn description(self) -> String {
+        match self {
+            Objective::Collect(kind, count) => format!("Collect {count} {kind:?}"),
+            Objective::Kill(count) => format!("Destroy {count} enemies"),
+            Objective::ReachCoherence(value) => {
+                format!("Reach {:.0}% Coherence", value * 100.0)
+            }
+        }
+    }
+}
+
+impl ObjectiveProgress {
+    fn is_complete(&self) -> bool {
+        self.progress >= self.objective.goal()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Picks the [`Objectives`] for a new run.
+///
+/// Uses the [`GameRng`] so that restarting a run with the same seed also repeats its objectives.
+pub(crate) fn choose_objectives(
+    mut objectives: b::ResMut<Objectives>,
+    mut game_rng: b::ResMut<GameRng>,
+) {
+    objectives.0 = OBJECTIVES
+        .sample(&mut **game_rng, OBJECTIVES_PER_RUN)
+        .map(|&objective| ObjectiveProgress {
+            objective,
+            progress: 0.0,
+        })
+        .collect();
+}
+
+/// Updates progress towards the [`Objectives`], and awards [`OBJECTIVE_BONUS`] for each one
+/// completed.
+pub(crate) fn track_objectives_system(
+    mut events: b::MessageReader<RunEvent>,
+    quantities: Quantities,
+    mut objectives: b::ResMut<Objectives>,
+    mut score: b::ResMut<Score>,
+) -> b::Result {
+    let coherence = quantities.get::<Coherence>()?;
+
+    let events: Vec<RunEvent> = events.read().copied().collect();
+    for entry in &mut objectives.0 {
+        if entry.is_complete() {
+            continue;
+        }
+        match entry.objective {
+            Objective::Collect(wanted, _) => {
+                entry.progress += events
+                    .iter()
+                    .filter(|&&event| {
+                        matches!(event, RunEvent::PickupCollected(kind) if kind == wanted)
+                    })
+                    .count() as f32;
+            }
+            Objective::Kill(_) => {
+                entry.progress += events
+                    .iter()
+                    .filter(|&&event| matches!(event, RunEvent::EnemyKilled))
+                    .count() as f32;
+            }
+            // Coherence maxed by a surge was not earned, so it does not count.
+            Objective::ReachCoherence(_) if !coherence.is_maxed() => {
+                entry.progress = entry.progress.max(coherence.effective_value());
+            }
+            Objective::ReachCoherence(_) => {}
+        }
+        if entry.is_complete() {
+            score.0 += OBJECTIVE_BONUS;
+        }
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn objectives_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        ObjectivesText,
+        b::Text2d::new(""),
+        assets.small_prop_font(),
+        b::TextLayout::new_with_justify(b::Justify::Right),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        bevy::sprite::Anchor::TOP_RIGHT,
+        b::Transform::from_translation(vec3(
+            PLAYFIELD_RECT.max.x - 4.0,
+            PLAYFIELD_RECT.max.y - 4.0,
+            Zees::UiFront2.z(),
+        )),
+        b::Visibility::Hidden,
+        UI_LAYERS,
+    )
+}
+
+pub(crate) fn update_objectives_text_system(
+    objectives: b::Res<Objectives>,
+    state: b::Res<b::State<GameState>>,
+    cutscene: Option<b::Res<Cutscene>>,
+    text: b::Single<(&mut b::Text2d, &mut b::Visibility), b::With<ObjectivesText>>,
+) {
+    let (mut text, mut visibility) = text.into_inner();
+
+    let visible = match *state.get() {
+        GameState::Playing | GameState::Paused => true,
+        GameState::WinOrGameOver => cutscene.is_none(),
+        GameState::AssetLoading | GameState::Menu | GameState::HighScores => false,
+    };
+    visibility.set_if_neq(if visible {
+        b::Visibility::Inherited
+    } else {
+        b::Visibility::Hidden
+    });
+    if !visible {
+        return;
+    }
+
+    let mut new_text = String::new();
+    for entry in &objectives.0 {
+        if !new_text.is_empty() {
+            new_text.push('\n');
+        }
+        new_text.push_str(&entry.objective.description());
+        if entry.is_complete() {
+            write!(new_text, " - done! +{OBJECTIVE_BONUS}").unwrap();
+        } else if let Objective::Collect(..) | Objective::Kill(_) = entry.objective {
+            write!(new_text, " ({}/{})", entry.progress, entry.objective.goal()).unwrap();
+        }
+    }
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
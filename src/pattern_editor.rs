@@ -0,0 +1,290 @@
+use std::path::Path;
+
+use bevy::ecs::change_detection::DetectChanges as _;
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::ecs::spawn::SpawnRelated as _;
+use bevy::math::Vec2;
+use bevy::prelude as b;
+use bevy_enhanced_input::prelude as bei;
+use bevy_enhanced_input::prelude::InputContextAppExt as _;
+
+use crate::difficulty::Difficulty;
+use crate::enemy::{
+    Cosmetic, EnemyKind, EnemySpawner, PATTERN_HEIGHT, PATTERN_WIDTH, SpawnPattern,
+    pattern_cell_position, spawn_pattern,
+};
+use crate::game_rng::GameRng;
+use crate::input_contexts::{self, StateDependentContext};
+use crate::mods::{MODS_DIRECTORY, PATTERNS_FILE};
+use crate::persistence::{self, DataDirectory};
+use crate::prefab::Prefabs;
+use crate::rendering::CanvasCursor;
+use crate::{GameState, PLAYFIELD_LAYERS, VisibleInState, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Developer tool for designing enemy [`SpawnPattern`]s.
+///
+/// Only enabled if the game is started with the `--pattern-editor` option.
+/// While playing, enemies are no longer spawned automatically; instead, a grid of the possible
+/// enemy positions is shown. Click on cells to place or remove an enemy of the selected kind,
+/// which F8 cycles through. Then:
+///
+/// * F5 spawns the pattern,
+/// * F6 appends the pattern to the `patterns.txt` of the [`SAVE_PACK`] mod pack
+///   (see [`ModsPlugin`](crate::mods::ModsPlugin)), which loads it the next time the game
+///   starts, and
+/// * F7 clears the pattern.
+pub(crate) struct PatternEditorPlugin {
+    pub enabled: bool,
+}
+
+impl b::Plugin for PatternEditorPlugin {
+    fn build(&self, app: &mut b::App) {
+        if !self.enabled {
+            return;
+        }
+        b::info!("enemy pattern editor enabled");
+
+        app.insert_resource(EditedPattern([[b' '; PATTERN_WIDTH]; PATTERN_HEIGHT]))
+            .insert_resource(SelectedKind(KINDS[0]))
+            .add_input_context::<PatternEditorInput>()
+            .add_systems(b::Startup, setup_pattern_editor)
+            .add_systems(
+                b::Update,
+                (
+                    input_contexts::update_context_activity_system::<PatternEditorInput>,
+                    update_cells_system,
+                ),
+            )
+            .add_systems(
+                b::FixedUpdate,
+                suppress_spawners_system
+                    .before(crate::enemy::spawn_enemies_system)
+                    .run_if(b::in_state(GameState::Playing)),
+            )
+            .add_observer(paint_observer)
+            .add_observer(preview_observer)
+            .add_observer(save_observer)
+            .add_observer(clear_observer)
+            .add_observer(select_kind_observer);
+    }
+}
+
+/// The pattern being edited.
+#[derive(Debug, b::Resource)]
+struct EditedPattern(SpawnPattern);
+
+/// Pattern character of the kind of enemy which [`paint_observer`] places.
+#[derive(Debug, b::Resource)]
+struct SelectedKind(u8);
+
+/// Sprite showing one cell of the [`EditedPattern`].
+#[derive(Debug, b::Component)]
+struct EditorCell {
+    xi: usize,
+    yi: usize,
+}
+
+/// Context entity for pattern editor inputs.
+#[derive(Debug, b::Component)]
+struct PatternEditorInput;
+
+impl StateDependentContext for PatternEditorInput {
+    fn active_in(state: &GameState) -> bool {
+        matches!(state, GameState::Playing)
+    }
+}
+
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct PaintCell;
+
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct PreviewPattern;
+
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct SavePattern;
+
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct ClearPattern;
+
+#[derive(Debug, bei::InputAction)]
+#[action_output(bool)]
+struct SelectNextKind;
+
+/// Name of the mod pack to which [`save_observer`] saves patterns.
+const SAVE_PACK: &str = "pattern-editor";
+
+/// Pattern characters of the kinds of enemy, in the order [`SelectNextKind`] cycles through them.
+const KINDS: [u8; 4] = *b"XTSN";
+
+const CELL_SIZE: f32 = 12.0;
+const EMPTY_CELL_COLOR: b::Color = b::Color::srgba(1.0, 1.0, 1.0, 0.1);
+
+// -------------------------------------------------------------------------------------------------
+
+fn setup_pattern_editor(mut commands: b::Commands) {
+    commands.spawn((
+        PatternEditorInput,
+        bei::actions!(
+            PatternEditorInput[(
+                bei::Action::<PaintCell>::new(),
+                bei::bindings![b::MouseButton::Left],
+            ), (
+                bei::Action::<PreviewPattern>::new(),
+                bei::bindings![b::KeyCode::F5],
+            ), (
+                bei::Action::<SavePattern>::new(),
+                bei::bindings![b::KeyCode::F6],
+            ), (
+                bei::Action::<ClearPattern>::new(),
+                bei::bindings![b::KeyCode::F7],
+            ), (
+                bei::Action::<SelectNextKind>::new(),
+                bei::bindings![b::KeyCode::F8],
+            )]
+        ),
+    ));
+
+    for yi in 0..PATTERN_HEIGHT {
+        for xi in 0..PATTERN_WIDTH {
+            commands.spawn((
+                EditorCell { xi, yi },
+                b::Sprite::from_color(EMPTY_CELL_COLOR, Vec2::splat(CELL_SIZE)),
+                b::Transform::from_translation(
+                    pattern_cell_position(xi, yi).extend(Zees::AbovePlayer.z()),
+                ),
+                PLAYFIELD_LAYERS,
+                VisibleInState(GameState::Playing),
+            ));
+        }
+    }
+}
+
+/// Keeps enemies from spawning except by [`preview_observer`].
+fn suppress_spawners_system(
+    mut commands: b::Commands,
+    spawners: b::Query<b::Entity, (b::With<EnemySpawner>, b::Without<Cosmetic>)>,
+) {
+    for entity in spawners {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn update_cells_system(
+    pattern: b::Res<EditedPattern>,
+    cells: b::Query<(&EditorCell, &mut b::Sprite)>,
+) {
+    if !pattern.is_changed() {
+        return;
+    }
+    for (&EditorCell { xi, yi }, mut sprite) in cells {
+        sprite.color = cell_color(pattern.0[yi][xi]);
+    }
+}
+
+/// Color of a cell containing the pattern character `ch`.
+fn cell_color(ch: u8) -> b::Color {
+    match EnemyKind::from_pattern_char(ch) {
+        None => EMPTY_CELL_COLOR,
+        Some(EnemyKind::Grunt) => b::Color::srgba(1.0, 0.3, 0.3, 0.6),
+        Some(EnemyKind::Tank) => b::Color::srgba(1.0, 0.7, 0.2, 0.6),
+        Some(EnemyKind::Swarmer) => b::Color::srgba(0.4, 1.0, 0.4, 0.6),
+        Some(EnemyKind::Sniper) => b::Color::srgba(0.4, 0.6, 1.0, 0.6),
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+fn paint_observer(
+    _event: b::On<bei::Start<PaintCell>>,
+    cursor: b::Res<CanvasCursor>,
+    selected: b::Res<SelectedKind>,
+    mut pattern: b::ResMut<EditedPattern>,
+) {
+    let Some(cursor) = cursor.playfield else {
+        return;
+    };
+    for yi in 0..PATTERN_HEIGHT {
+        for xi in 0..PATTERN_WIDTH {
+            if (pattern_cell_position(xi, yi) - cursor).abs().max_element() <= CELL_SIZE / 2.0 {
+                let cell = &mut pattern.0[yi][xi];
+                *cell = if *cell == selected.0 {
+                    b' '
+                } else {
+                    selected.0
+                };
+            }
+        }
+    }
+}
+
+fn preview_observer(
+    _event: b::On<bei::Start<PreviewPattern>>,
+    mut commands: b::Commands,
+    prefabs: Prefabs,
+    pattern: b::Res<EditedPattern>,
+    mut game_rng: b::ResMut<GameRng>,
+    difficulty: b::Res<Difficulty>,
+) -> b::Result {
+    spawn_pattern(
+        &mut commands,
+        &mut **game_rng,
+        &prefabs,
+        &pattern.0,
+        false,
+        *difficulty,
+    )
+}
+
+/// Appends the pattern to the `patterns.txt` of the [`SAVE_PACK`] pack, in the format which
+/// [`ModsPlugin`](crate::mods::ModsPlugin) loads.
+fn save_observer(
+    _event: b::On<bei::Start<SavePattern>>,
+    pattern: b::Res<EditedPattern>,
+    data_directory: b::Res<DataDirectory>,
+) {
+    if pattern.0.as_flattened().iter().all(|&ch| ch == b' ') {
+        // the whole file would fail to load
+        b::warn!("not saving empty spawn pattern");
+        return;
+    }
+
+    let mut text = String::new();
+    for row in pattern.0 {
+        text.extend(
+            row.iter()
+                .map(|&ch| if ch == b' ' { '.' } else { char::from(ch) }),
+        );
+        text.push('\n');
+    }
+    text.push('\n');
+
+    let relative_path = Path::new(MODS_DIRECTORY)
+        .join(SAVE_PACK)
+        .join(PATTERNS_FILE);
+    if let Some(path) = persistence::append_text(&data_directory, &relative_path, &text) {
+        b::info!("saved spawn pattern to {}:\n{text}", path.display());
+    } else if data_directory.0.is_none() {
+        b::warn!("no data directory to save spawn pattern in:\n{text}");
+    }
+}
+
+fn clear_observer(_event: b::On<bei::Start<ClearPattern>>, mut pattern: b::ResMut<EditedPattern>) {
+    pattern.0 = [[b' '; PATTERN_WIDTH]; PATTERN_HEIGHT];
+}
+
+fn select_kind_observer(
+    _event: b::On<bei::Start<SelectNextKind>>,
+    mut selected: b::ResMut<SelectedKind>,
+) {
+    let index = KINDS.iter().position(|&ch| ch == selected.0).unwrap_or(0);
+    selected.0 = KINDS[(index + 1) % KINDS.len()];
+    if let Some(kind) = EnemyKind::from_pattern_char(selected.0) {
+        b::info!("pattern editor now places {kind:?} enemies");
+    }
+}
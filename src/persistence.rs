@@ -0,0 +1,145 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude as b;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Event which asks every module that has persistent data to write it to disk now.
+/// Observers of this event should use [`save()`].
+#[derive(Debug, b::Event)]
+pub(crate) struct FlushPersistentData;
+
+/// Event which saves persistent data and exits the application.
+#[derive(Debug, b::Event)]
+pub(crate) struct QuitGame;
+
+/// Directory in which persistent data files are stored,
+/// or [`None`] if there is no suitable place (such as on the web).
+///
+/// Inserted by [`RecombobulatorPlugin`](crate::RecombobulatorPlugin) before anything else,
+/// so that each [`b::App`] may have its own.
+#[derive(Clone, Debug, b::Resource)]
+pub(crate) struct DataDirectory(pub Option<PathBuf>);
+
+// -------------------------------------------------------------------------------------------------
+
+impl DataDirectory {
+    /// The directory chosen by [`GameConfig::data_directory()`](crate::GameConfig::data_directory)
+    /// if any, or else the platform’s usual one.
+    ///
+    /// The platform’s directory is [`None`] in tests, so that they neither read nor overwrite
+    /// the player’s data.
+    pub(crate) fn new(chosen: Option<PathBuf>) -> Self {
+        Self(chosen.or_else(platform_data_directory))
+    }
+}
+
+/// The platform’s usual directory for this game’s data.
+fn platform_data_directory() -> Option<PathBuf> {
+    if cfg!(target_family = "wasm") || cfg!(test) {
+        return None;
+    }
+    let env_path = |name: &str| std::env::var_os(name).map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        env_path("APPDATA")?
+    } else if cfg!(target_os = "macos") {
+        env_path("HOME")?.join("Library/Application Support")
+    } else {
+        env_path("XDG_DATA_HOME").or_else(|| Some(env_path("HOME")?.join(".local/share")))?
+    };
+    Some(base.join("interplanetary-recombobulator"))
+}
+
+/// Reads a value previously written by [`save()`], if there is one.
+/// Errors other than the file not existing are logged.
+pub(crate) fn load<T: serde::de::DeserializeOwned>(
+    directory: &DataDirectory,
+    file_name: &str,
+) -> Option<T> {
+    let path = directory.0.as_ref()?.join(file_name);
+    let result = std::fs::File::open(&path).and_then(|file| {
+        serde_json::from_reader(std::io::BufReader::new(file)).map_err(std::io::Error::from)
+    });
+    match result {
+        Ok(value) => Some(value),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => {
+            b::error!("failed to read {}: {error}", path.display());
+            None
+        }
+    }
+}
+
+/// Writes a value to a file in the data directory. Errors are logged.
+pub(crate) fn save<T: serde::Serialize>(directory: &DataDirectory, file_name: &str, value: &T) {
+    let Some(directory) = &directory.0 else {
+        return;
+    };
+    let path = directory.join(file_name);
+    let result = std::fs::create_dir_all(directory)
+        .and_then(|()| std::fs::File::create(&path))
+        .and_then(|file| {
+            serde_json::to_writer_pretty(std::io::BufWriter::new(file), value)
+                .map_err(std::io::Error::from)
+        });
+    if let Err(error) = result {
+        b::error!("failed to write {}: {error}", path.display());
+    }
+}
+
+/// Appends `text` to the file at `relative_path` in the data directory, creating the file and
+/// its parent directories if needed. Returns the file’s path if it was written; errors are logged.
+pub(crate) fn append_text(
+    directory: &DataDirectory,
+    relative_path: &Path,
+    text: &str,
+) -> Option<PathBuf> {
+    let path = directory.0.as_ref()?.join(relative_path);
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+        })
+        .and_then(|mut file| std::io::Write::write_all(&mut file, text.as_bytes()));
+    match result {
+        Ok(()) => Some(path),
+        Err(error) => {
+            b::error!("failed to write {}: {error}", path.display());
+            None
+        }
+    }
+}
+
+/// Path in `directory` for a new file or directory named after the current time, such as
+/// `{prefix}1760000000000{extension}`, which does not exist yet.
+///
+/// The time is in milliseconds, and a numeric suffix is added if something by that name
+/// already exists, so that files written in quick succession do not overwrite each other.
+pub(crate) fn timestamped_path(directory: &Path, prefix: &str, extension: &str) -> PathBuf {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let mut path = directory.join(format!("{prefix}{timestamp}{extension}"));
+    let mut suffix = 2;
+    while path.exists() {
+        path = directory.join(format!("{prefix}{timestamp}-{suffix}{extension}"));
+        suffix += 1;
+    }
+    path
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn quit_game_observer(
+    _event: b::On<QuitGame>,
+    mut commands: b::Commands,
+    mut app_exit: b::MessageWriter<b::AppExit>,
+) {
+    commands.trigger(FlushPersistentData);
+    app_exit.write(b::AppExit::Success);
+}
@@ -1,126 +1,315 @@
 use avian2d::prelude::{self as p};
+use bevy::ecs::entity::EntityHashSet;
+use bevy::ecs::reflect::ReflectComponent;
 use bevy::math::{Vec2, vec2};
 use bevy::prelude as b;
+use rand::seq::IndexedRandom as _;
+use rand_distr::Distribution as _;
 
-use crate::bullets_and_targets::Hurt;
-use crate::quantity::{QCoherenceMut, QFeverMut};
-use crate::rendering::{PLAYFIELD_LAYERS, Zees};
-use crate::{Lifetime, Player};
+use crate::bullets_and_targets::{Hurt, PlayerWeapon, WeaponUpgrades};
+use crate::lives::Invulnerable;
+use crate::prefab::PickupPrefabs;
+use crate::quantity::{
+    Adjustment, Quantity, QuantityDelta, QuantityHold, QuantityKind, fervor_is_active,
+};
+use crate::rendering::{CameraZoom, PLAYFIELD_LAYERS, Zees};
+use crate::run_stats::RunEvent;
+use crate::wrap::WrapGhost;
+use crate::{Gun, Lifetime, Player, SoundId};
 
 // -------------------------------------------------------------------------------------------------
 
-/// On colliding with [`Player`], has an effect and despawns the entity.
+/// On colliding with [`Player`], has an effect and despawns the entity, unless it is [`Carried`].
 /// This is used for both pickups and colliding with enemies.
-#[derive(Debug, b::Component)]
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) enum Pickup {
-    /// Does nothing.
-    /// Immediately vanishes.
-    /// Used as a placeholder when a pickup bundle is required, but not wanted for gameplay.
-    Null,
-
     /// Increase [`Fever`] by this amount, and depict it as a damaging hit.
     Damage(f32),
     /// Decrease [`Fever`] by this amount.
     Cool(f32),
     /// Increase [`Coherence`] by this amount.
     Cohere(f32),
+    /// Increase [`Fervor`] temporarily by `fervor`, and hold [`Coherence`] at its maximum for
+    /// `duration` seconds.
+    Surge { fervor: f32, duration: f32 },
+    /// Upgrade the [`Player`]’s [`Gun`](crate::Gun); see [`WeaponUpgrades`].
+    /// At the maximum level of the upgrade, increase [`Coherence`] by this amount instead.
+    Upgrade(WeaponUpgrade, f32),
+}
+
+/// A kind of upgrade to the [`Player`]’s gun, collected as a [`Pickup::Upgrade`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, b::Reflect, serde::Deserialize, serde::Serialize)]
+pub(crate) enum WeaponUpgrade {
+    /// Wider spread; see [`WeaponUpgrades::wide`].
+    Wide,
+    /// Faster fire; see [`WeaponUpgrades::rapid`].
+    Rapid,
+    /// More damage; see [`WeaponUpgrades::heavy`].
+    Heavy,
 }
 
+/// Marks a [`Pickup`] which is being carried by an enemy, and so cannot be collected yet.
+/// Removed when the pickup is dropped.
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct Carried;
+
+/// What an enemy may drop when it is destroyed: relative chances of each kind of pickup,
+/// where [`None`] is nothing.
+///
+/// Whether the enemy carries a pickup at all is chosen when it spawns. Which kind the pickup
+/// turns out to be is chosen again when it is dropped, by [`choose_drop()`], so that it can
+/// depend on the state of the game at that time.
+#[derive(Clone, Debug, b::Component)]
+pub(crate) struct Drops(pub Vec<(Option<PickupSpawnType>, f32)>);
+
 /// Category of [`Pickup`] to spawn.
 /// Determines the exact value and appearance using its internal logic.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
 pub(crate) enum PickupSpawnType {
-    /// Invisible and immediately vanishes.
-    /// Used as a placeholder when a pickup bundle is required, but not wanted for gameplay.
-    Null,
-
     Cool,
 
     Cohere,
+
+    /// Rare comeback pickup; see [`Pickup::Surge`].
+    Surge,
+
+    /// Rare weapon upgrade; see [`Pickup::Upgrade`].
+    Upgrade(WeaponUpgrade),
 }
 
 // -------------------------------------------------------------------------------------------------
 
 impl PickupSpawnType {
-    pub(crate) fn pickup_bundle(&self, assets: &crate::MyAssets, position: Vec2) -> impl b::Bundle {
-        let image = match self {
-            PickupSpawnType::Null => &assets.pickup_cool_sprite,
-            PickupSpawnType::Cool => &assets.pickup_cool_sprite,
-            PickupSpawnType::Cohere => &assets.pickup_cohere_sprite,
-        };
-
-        let effect = match self {
-            PickupSpawnType::Null => Pickup::Null,
-            PickupSpawnType::Cool => Pickup::Cool(0.1),
-            PickupSpawnType::Cohere => Pickup::Cohere(0.1),
-        };
-
-        // kludge to make null have no visible effect
-        let visibility = match self {
-            PickupSpawnType::Null => b::Visibility::Hidden,
-            _ => b::Visibility::Visible,
-        };
-
+    pub(crate) fn pickup_bundle(
+        &self,
+        prefabs: &PickupPrefabs,
+        position: Vec2,
+    ) -> impl b::Bundle + use<> {
         // This bundle contains the parts of the pickup that exist while it is being carried
         // by an enemy. The parts for its independent existence will be added when it drops
         // from the enemy by after_drop_bundle().
         (
-            b::Sprite::from_image(image.clone()),
-            effect,
-            visibility,
+            self.appearance_and_effect(prefabs),
+            Carried,
+            b::Visibility::Visible,
             b::Transform::from_translation(position.extend(Zees::Pickup.z())),
             PLAYFIELD_LAYERS,
         )
     }
+
+    /// The parts of the pickup which depend on its type, which are replaced if
+    /// [`choose_drop()`] changes it.
+    pub(crate) fn appearance_and_effect(&self, prefabs: &PickupPrefabs) -> (b::Sprite, Pickup) {
+        let image = match self {
+            PickupSpawnType::Cool => &prefabs.cool_sprite,
+            PickupSpawnType::Cohere => &prefabs.cohere_sprite,
+            PickupSpawnType::Surge => &prefabs.surge_sprite,
+            PickupSpawnType::Upgrade(WeaponUpgrade::Wide) => &prefabs.wide_sprite,
+            PickupSpawnType::Upgrade(WeaponUpgrade::Rapid) => &prefabs.rapid_sprite,
+            PickupSpawnType::Upgrade(WeaponUpgrade::Heavy) => &prefabs.heavy_sprite,
+        };
+
+        let effect = match self {
+            PickupSpawnType::Cool => Pickup::Cool(prefabs.cool_amount),
+            PickupSpawnType::Cohere => Pickup::Cohere(prefabs.cohere_amount),
+            PickupSpawnType::Surge => Pickup::Surge {
+                fervor: prefabs.surge_amount,
+                duration: prefabs.surge_duration,
+            },
+            PickupSpawnType::Upgrade(upgrade) => {
+                Pickup::Upgrade(*upgrade, prefabs.upgrade_overflow_coherence)
+            }
+        };
+
+        (b::Sprite::from_image(image.clone()), effect)
+    }
 }
 
+/// Fraction of the carrier’s velocity which a dropped [`Pickup`] keeps.
+const INHERITED_VELOCITY_FRACTION: f32 = 0.4;
+
+/// Largest speed of the random push a dropped [`Pickup`] gets.
+const DROP_KICK_SPEED: f32 = 25.0;
+
 /// Bundle of components to add to a [`Pickup`] entity when it stops being carried by an enemy
 /// and starts existing on its own.
-pub(crate) fn after_drop_bundle(pickup: &Pickup) -> impl b::Bundle {
+///
+/// It drifts down the playfield, carrying on some of the motion of its carrier.
+pub(crate) fn after_drop_bundle<R: rand::Rng>(
+    carrier_velocity: Vec2,
+    rng: &mut R,
+) -> impl b::Bundle + use<R> {
+    let kick = Vec2::from(rand_distr::UnitDisc.sample(rng)) * DROP_KICK_SPEED;
+    let velocity = vec2(0.0, -100.0) + carrier_velocity * INHERITED_VELOCITY_FRACTION + kick;
     (
-        Lifetime(match pickup {
-            Pickup::Null => 0.0, // go away immediately
-            _ => 20.0,           // TODO: bad substitute for "die when offscreen"
-        }),
+        Lifetime(20.0), // TODO: bad substitute for "die when offscreen"
         p::RigidBody::Kinematic,
         p::Collider::circle(8.), // a bit oversized to make it easier to collect
-        p::LinearVelocity(vec2(0.0, -100.0)),
+        p::LinearVelocity(velocity),
         p::AngularVelocity(0.6),
     )
 }
 
+/// Multiplier of the chance of dropping [`PickupSpawnType::Cohere`] while fervor is active,
+/// to reward aggressive play.
+const FERVOR_COHERE_WEIGHT: f32 = 2.5;
+
+/// Fever level above which [`PickupSpawnType::Cool`] and [`PickupSpawnType::Surge`] become
+/// more likely to drop.
+const HIGH_FEVER: f32 = 0.6;
+
+/// Multiplier of the chance of dropping [`PickupSpawnType::Cool`] or [`PickupSpawnType::Surge`]
+/// at full fever. Between [`HIGH_FEVER`] and full fever, the multiplier is interpolated from 1.
+const FULL_FEVER_COMEBACK_WEIGHT: f32 = 3.0;
+
+/// Chooses which kind of pickup a destroyed enemy drops, from the kinds in its [`Drops`] table
+/// weighted by the current quantities: toward [`PickupSpawnType::Cohere`] while fervor is
+/// active, and toward [`PickupSpawnType::Cool`] and [`PickupSpawnType::Surge`] when fever is
+/// high, so that the game helps whoever needs it.
+///
+/// Returns [`None`] only if the table has no kinds of pickup in it.
+pub(crate) fn choose_drop(
+    drops: &Drops,
+    fever: &Quantity,
+    coherence: &Quantity,
+    rng: &mut impl rand::Rng,
+) -> Option<PickupSpawnType> {
+    let cohere_weight = if fervor_is_active(fever, coherence) {
+        FERVOR_COHERE_WEIGHT
+    } else {
+        1.0
+    };
+    let high_fever_fraction =
+        ((fever.effective_value() - HIGH_FEVER) / (1.0 - HIGH_FEVER)).clamp(0.0, 1.0);
+    let comeback_weight = 1.0 + (FULL_FEVER_COMEBACK_WEIGHT - 1.0) * high_fever_fraction;
+
+    drops
+        .0
+        .choose_weighted(rng, |&(drop, weight)| match drop {
+            Some(PickupSpawnType::Cool | PickupSpawnType::Surge) => weight * comeback_weight,
+            Some(PickupSpawnType::Cohere) => weight * cohere_weight,
+            Some(PickupSpawnType::Upgrade(_)) => weight,
+            // whether there is a pickup at all was already decided
+            None => 0.0,
+        })
+        .ok()
+        .and_then(|&(drop, _)| drop)
+}
+
+/// Camera zoom punch when a [`Pickup::Surge`] is collected.
+const SURGE_PUNCH: f32 = 0.06;
+
 // -------------------------------------------------------------------------------------------------
 
+#[allow(
+    clippy::too_many_arguments,
+    clippy::type_complexity,
+    reason = "a pickup may affect the gun, quantities, sound, and camera"
+)]
 pub(crate) fn pickup_system(
     mut commands: b::Commands,
-    player_query: b::Single<(b::Entity, &p::CollidingEntities), b::With<Player>>,
-    pickups: b::Query<(&Pickup, &b::Transform)>,
-    mut coherence: QCoherenceMut,
-    mut fever: QFeverMut,
-    assets: b::Res<crate::MyAssets>,
+    player_query: b::Single<
+        (
+            b::Entity,
+            &p::CollidingEntities,
+            &mut Gun,
+            &mut WeaponUpgrades,
+            &PlayerWeapon,
+            b::Has<Invulnerable>,
+        ),
+        b::With<Player>,
+    >,
+    wrap_ghosts: b::Query<&p::CollidingEntities, b::With<WrapGhost>>,
+    pickups: b::Query<(&Pickup, &b::Transform), b::Without<Carried>>,
+    mut quantity_deltas: b::MessageWriter<QuantityDelta>,
+    mut quantity_holds: b::MessageWriter<QuantityHold>,
+    mut run_events: b::MessageWriter<RunEvent>,
+    assets: b::Res<crate::GameAssets>,
+    mut camera_zoom: b::ResMut<CameraZoom>,
 ) -> b::Result {
-    let (player_entity, player_collisions) = player_query.into_inner();
-    for &pickup_entity in &player_collisions.0 {
+    let (player_entity, player_collisions, mut gun, mut upgrades, weapon, invulnerable) =
+        player_query.into_inner();
+    // A pickup touching both the ship and its ghost must only be collected once.
+    let touching: EntityHashSet = player_collisions
+        .0
+        .iter()
+        .chain(wrap_ghosts.iter().flat_map(|collisions| &collisions.0))
+        .copied()
+        .collect();
+    for pickup_entity in touching {
         let Ok((pickup, &pickup_transform)) = pickups.get(pickup_entity) else {
             // not a pickup
             continue;
         };
+        if invulnerable && matches!(pickup, Pickup::Damage(_)) {
+            // passes harmlessly through
+            continue;
+        }
 
         let mut sound_asset = None;
 
         match *pickup {
-            Pickup::Null => {}
             Pickup::Damage(amount) => {
-                fever.adjust_permanent_including_temporary(amount);
-                commands.trigger(Hurt(player_entity));
+                quantity_deltas.write(QuantityDelta::new(
+                    QuantityKind::Fever,
+                    Adjustment::PermanentIncludingTemporary,
+                    amount,
+                ));
+                run_events.write(RunEvent::PlayerDamaged);
+                // colliding with an enemy gets the mildest hit feedback
+                commands.trigger(Hurt {
+                    target: player_entity,
+                    damage: 1,
+                });
             }
             Pickup::Cool(amount) => {
-                fever.adjust_permanent_clearing_temporary(-amount);
-                sound_asset = Some(assets.pickup_sound.clone());
+                quantity_deltas.write(QuantityDelta::new(
+                    QuantityKind::Fever,
+                    Adjustment::PermanentClearingTemporary,
+                    -amount,
+                ));
+                run_events.write(RunEvent::PickupCollected(PickupSpawnType::Cool));
+                sound_asset = Some(assets.sound(SoundId::Pickup));
             }
             Pickup::Cohere(amount) => {
-                coherence.adjust_permanent_clearing_temporary(amount);
-                sound_asset = Some(assets.pickup_sound.clone());
+                quantity_deltas.write(QuantityDelta::new(
+                    QuantityKind::Coherence,
+                    Adjustment::PermanentClearingTemporary,
+                    amount,
+                ));
+                run_events.write(RunEvent::PickupCollected(PickupSpawnType::Cohere));
+                sound_asset = Some(assets.sound(SoundId::Pickup));
+            }
+            Pickup::Surge { fervor, duration } => {
+                quantity_deltas.write(QuantityDelta::new(
+                    QuantityKind::Fervor,
+                    Adjustment::TemporaryStacking,
+                    fervor,
+                ));
+                quantity_holds.write(QuantityHold {
+                    quantity: QuantityKind::Coherence,
+                    duration,
+                });
+                run_events.write(RunEvent::PickupCollected(PickupSpawnType::Surge));
+                camera_zoom.punch(SURGE_PUNCH);
+                // the announcer plays its own sting for this pickup
+            }
+            Pickup::Upgrade(upgrade, overflow_coherence) => {
+                if upgrades.add(upgrade) {
+                    gun.pattern = upgrades.pattern(*weapon);
+                    gun.base_cooldown = upgrades.cooldown(*weapon);
+                } else {
+                    // already fully upgraded; still worth something
+                    quantity_deltas.write(QuantityDelta::new(
+                        QuantityKind::Coherence,
+                        Adjustment::PermanentClearingTemporary,
+                        overflow_coherence,
+                    ));
+                }
+                run_events.write(RunEvent::PickupCollected(PickupSpawnType::Upgrade(upgrade)));
+                sound_asset = Some(assets.sound(SoundId::Pickup));
             }
         }
 
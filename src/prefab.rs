@@ -0,0 +1,543 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{
+    AssetApp as _, AssetLoader, LoadContext, UntypedAssetId, VisitAssetDependencies,
+};
+use bevy::ecs::system::SystemParam;
+use bevy::math::Vec2;
+use bevy::prelude as b;
+
+use crate::bullets_and_targets::Pattern;
+use crate::enemy::{AiStyle, EnemyKind};
+use crate::pickup::PickupSpawnType;
+use crate::{GameAssets, SpriteId};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Loads data files describing the art and stats of game objects:
+///
+/// * `.enemy.ron` files are [`EnemyPrefab`]s.
+/// * `.pickups.ron` files are [`PickupPrefabs`].
+/// * `.backdrop.ron` files are [`BackdropPrefab`]s.
+///
+/// The spawning code supplies the remaining fields which are only known at run time.
+pub(crate) struct PrefabPlugin;
+
+impl b::Plugin for PrefabPlugin {
+    fn build(&self, app: &mut b::App) {
+        app.init_asset::<EnemyPrefab>()
+            .init_asset::<PickupPrefabs>()
+            .init_asset::<BackdropPrefab>()
+            .register_asset_loader(EnemyPrefabLoader)
+            .register_asset_loader(PickupPrefabsLoader)
+            .register_asset_loader(BackdropPrefabLoader);
+    }
+}
+
+/// Art and stats of a kind of enemy ship.
+#[derive(Debug, b::Asset, b::TypePath)]
+pub(crate) struct EnemyPrefab {
+    #[dependency]
+    pub sprite: b::Handle<b::Image>,
+    #[dependency]
+    pub destruction_particle: Option<b::Handle<b::Image>>,
+    #[dependency]
+    pub hurt_sound: b::Handle<b::AudioSource>,
+    /// Playback speed of [`Self::hurt_sound`], so that kinds sharing a sound sound different.
+    pub hurt_pitch: f32,
+    pub health: u8,
+    pub collider_radius: f32,
+    /// Damage to the player if touched.
+    pub contact_damage: f32,
+
+    pub gun_pattern: Pattern,
+    pub gun_cooldown: f32,
+    /// The first shot comes after a random time up to this.
+    pub max_initial_gun_cooldown: f32,
+    #[dependency]
+    pub shoot_sound: b::Handle<b::AudioSource>,
+    pub shoot_volume: bevy::audio::Volume,
+
+    /// Relative chances of what the enemy drops when destroyed; [`None`] is nothing.
+    pub drops: Vec<(Option<PickupSpawnType>, f32)>,
+
+    /// What the enemy does after [`Self::station_time`] at its place in the formation.
+    pub ai: AiStyle,
+    pub station_time: f32,
+    /// Speed, in pixels per second, of the movement chosen by [`Self::ai`].
+    pub dive_speed: f32,
+    /// Average speed, in pixels per second, of flying in along an entry
+    /// [`Path`](crate::enemy::Path).
+    pub entry_speed: f32,
+    /// Acceleration towards the station, per pixel of distance from it, when flying in without
+    /// an entry path. Higher is more darting; lower is more sluggish.
+    pub approach_stiffness: f32,
+    /// Deceleration, per unit of velocity, when flying in without an entry path.
+    /// Lower relative to [`Self::approach_stiffness`] overshoots the station more.
+    pub approach_damping: f32,
+
+    /// How much the enemy counts towards the spawner’s threat budget;
+    /// see [`Threat`](crate::enemy::Threat).
+    pub threat: f32,
+
+    /// Points for destroying the enemy; see [`ScoreValue`](crate::score::ScoreValue).
+    pub score: u32,
+
+    /// Separately destructible pieces of the enemy, such as turrets or wings.
+    /// If any are critical, the enemy cannot be hurt directly, and is destroyed when the last
+    /// critical part is.
+    #[dependency]
+    pub parts: PartPrefabs,
+}
+
+/// Art and stats of one [`Part`](crate::bullets_and_targets::Part) of an [`EnemyPrefab`].
+#[derive(Clone, Debug)]
+pub(crate) struct PartPrefab {
+    pub sprite: b::Handle<b::Image>,
+    /// Position relative to the enemy’s center.
+    pub offset: Vec2,
+    pub health: u8,
+    pub collider_radius: f32,
+    /// Whether destroying this part counts towards destroying the whole enemy.
+    pub critical: bool,
+}
+
+/// The parts of an [`EnemyPrefab`]. This is a separate type only so that the parts’ sprites can
+/// be declared as dependencies of the prefab.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PartPrefabs(pub Vec<PartPrefab>);
+
+impl VisitAssetDependencies for PartPrefabs {
+    fn visit_dependencies(&self, visit: &mut impl FnMut(UntypedAssetId)) {
+        for part in &self.0 {
+            visit(part.sprite.id().untyped());
+        }
+    }
+}
+
+/// Art and amounts of each kind of pickup.
+#[derive(Debug, b::Asset, b::TypePath)]
+pub(crate) struct PickupPrefabs {
+    #[dependency]
+    pub cool_sprite: b::Handle<b::Image>,
+    pub cool_amount: f32,
+    #[dependency]
+    pub cohere_sprite: b::Handle<b::Image>,
+    pub cohere_amount: f32,
+    #[dependency]
+    pub surge_sprite: b::Handle<b::Image>,
+    /// Temporary [`Fervor`](crate::quantity::Fervor) granted by a surge.
+    pub surge_amount: f32,
+    /// Time, in seconds, for which a surge holds
+    /// [`Coherence`](crate::quantity::Coherence) at its maximum.
+    pub surge_duration: f32,
+    #[dependency]
+    pub wide_sprite: b::Handle<b::Image>,
+    #[dependency]
+    pub rapid_sprite: b::Handle<b::Image>,
+    #[dependency]
+    pub heavy_sprite: b::Handle<b::Image>,
+    /// Coherence granted by a weapon upgrade which is already at its maximum level.
+    pub upgrade_overflow_coherence: f32,
+}
+
+/// The background scenery of a stretch of space: which props drift past behind the starfield,
+/// and how often.
+#[derive(Debug, b::Asset, b::TypePath, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BackdropPrefab {
+    /// Range of time, in seconds, between one prop appearing and the next.
+    pub interval: (f32, f32),
+    pub props: Vec<BackdropProp>,
+}
+
+impl BackdropPrefab {
+    /// Checks for values which would make the spawner panic or props never arrive.
+    fn validate(self) -> Result<Self, b::BevyError> {
+        check_range(self.interval, "backdrop interval")?;
+        if self.interval.0 < 0.0 {
+            return Err(b::BevyError::from("backdrop interval must not be negative"));
+        }
+        for prop in &self.props {
+            check_range(prop.speed, "backdrop prop speed")?;
+            // Props must move downward to cross the playfield in a finite time.
+            if prop.speed.0 <= 0.0 {
+                return Err(b::BevyError::from("backdrop prop speed must be positive"));
+            }
+            if !(0.0..90.0).contains(&prop.max_angle) {
+                return Err(b::BevyError::from(
+                    "backdrop prop max_angle must be at least 0 and less than 90 degrees",
+                ));
+            }
+            if !(prop.max_spin >= 0.0 && prop.max_spin.is_finite()) {
+                return Err(b::BevyError::from(
+                    "backdrop prop max_spin must be finite and not negative",
+                ));
+            }
+            if !(prop.weight >= 0.0 && prop.weight.is_finite()) {
+                return Err(b::BevyError::from(
+                    "backdrop prop weight must be finite and not negative",
+                ));
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// A kind of [`BackdropPrefab`] prop.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BackdropProp {
+    pub sprite: SpriteId,
+    /// Relative chance of this prop being chosen.
+    pub weight: f32,
+    /// Range of speeds, in pixels per second.
+    /// Should be slower than the slowest star, since props are farther away.
+    pub speed: (f32, f32),
+    /// Largest angle, in degrees, by which the direction of travel differs from straight down.
+    pub max_angle: f32,
+    /// Whether the sprite, drawn heading downward, is rotated to face its direction of travel.
+    pub face_heading: bool,
+    /// Largest rate of spin, in radians per second.
+    pub max_spin: f32,
+}
+
+/// Access to the prefabs in [`GameAssets`], which are assumed to be loaded.
+#[derive(SystemParam)]
+pub(crate) struct Prefabs<'w> {
+    assets: b::Res<'w, GameAssets>,
+    enemies: b::Res<'w, b::Assets<EnemyPrefab>>,
+    pickups: b::Res<'w, b::Assets<PickupPrefabs>>,
+    backdrops: b::Res<'w, b::Assets<BackdropPrefab>>,
+}
+
+impl Prefabs<'_> {
+    pub fn enemy(&self, kind: EnemyKind) -> Result<&EnemyPrefab, b::BevyError> {
+        let handle = match kind {
+            EnemyKind::Grunt => &self.assets.basic_enemy,
+            EnemyKind::Tank => &self.assets.tank_enemy,
+            EnemyKind::Swarmer => &self.assets.swarmer_enemy,
+            EnemyKind::Sniper => &self.assets.sniper_enemy,
+        };
+        self.enemies
+            .get(handle)
+            .ok_or_else(|| b::BevyError::from("asset not loaded"))
+    }
+
+    /// The plainest enemy, whose art and sounds are also used for things which are not
+    /// ordinary enemies, such as the boss.
+    pub fn basic_enemy(&self) -> Result<&EnemyPrefab, b::BevyError> {
+        self.enemy(EnemyKind::Grunt)
+    }
+
+    pub fn pickups(&self) -> Result<&PickupPrefabs, b::BevyError> {
+        self.pickups
+            .get(&self.assets.pickups)
+            .ok_or_else(|| b::BevyError::from("asset not loaded"))
+    }
+
+    pub fn backdrop(&self) -> Result<&BackdropPrefab, b::BevyError> {
+        self.backdrops
+            .get(&self.assets.backdrop)
+            .ok_or_else(|| b::BevyError::from("asset not loaded"))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// File formats and loaders
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnemyPrefabFile {
+    sprite: String,
+    destruction_particle: Option<String>,
+    hurt_sound: String,
+    hurt_pitch: f32,
+    health: u8,
+    collider_radius: f32,
+    contact_damage: f32,
+    gun_pattern: Pattern,
+    gun_cooldown: f32,
+    max_initial_gun_cooldown: f32,
+    shoot_sound: String,
+    shoot_volume_db: f32,
+    drops: Vec<(Option<PickupSpawnType>, f32)>,
+    ai: AiStyle,
+    station_time: f32,
+    dive_speed: f32,
+    entry_speed: f32,
+    approach_stiffness: f32,
+    approach_damping: f32,
+    threat: f32,
+    score: u32,
+    #[serde(default)]
+    parts: Vec<PartPrefabFile>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PartPrefabFile {
+    sprite: String,
+    offset: (f32, f32),
+    health: u8,
+    collider_radius: f32,
+    critical: bool,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PickupPrefabsFile {
+    cool: PickupFile,
+    cohere: PickupFile,
+    surge: SurgePickupFile,
+    wide: SpriteFile,
+    rapid: SpriteFile,
+    heavy: SpriteFile,
+    upgrade_overflow_coherence: f32,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PickupFile {
+    sprite: String,
+    amount: f32,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpriteFile {
+    sprite: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SurgePickupFile {
+    sprite: String,
+    amount: f32,
+    duration: f32,
+}
+
+/// Turns the asset paths in prefab files into handles.
+pub(crate) trait ResolvePath {
+    fn image(&mut self, path: String) -> b::Handle<b::Image>;
+    fn sound(&mut self, path: String) -> b::Handle<b::AudioSource>;
+}
+
+impl ResolvePath for LoadContext<'_> {
+    fn image(&mut self, path: String) -> b::Handle<b::Image> {
+        self.load(path)
+    }
+    fn sound(&mut self, path: String) -> b::Handle<b::AudioSource> {
+        self.load(path)
+    }
+}
+
+impl EnemyPrefabFile {
+    /// Checks for values which would make spawning the enemy panic or misbehave.
+    fn validate(&self) -> Result<(), b::BevyError> {
+        if self.drops.is_empty() {
+            return Err(b::BevyError::from("enemy must have at least one drop"));
+        }
+        if !(self.max_initial_gun_cooldown >= 0.0 && self.max_initial_gun_cooldown.is_finite()) {
+            return Err(b::BevyError::from(
+                "enemy max_initial_gun_cooldown must be finite and not negative",
+            ));
+        }
+        if self.parts.iter().any(|part| part.health == 0) {
+            // it would be dead on arrival
+            return Err(b::BevyError::from("enemy part health must be at least 1"));
+        }
+        if !(self.threat > 0.0 && self.threat.is_finite()) {
+            // a free enemy would let the spawner fill the screen
+            return Err(b::BevyError::from(
+                "enemy threat must be positive and finite",
+            ));
+        }
+        if !(self.entry_speed > 0.0 && self.entry_speed.is_finite()) {
+            // the entry path’s duration is its length divided by this
+            return Err(b::BevyError::from(
+                "enemy entry_speed must be positive and finite",
+            ));
+        }
+        if !(self.hurt_pitch > 0.0 && self.hurt_pitch.is_finite()) {
+            return Err(b::BevyError::from(
+                "enemy hurt_pitch must be positive and finite",
+            ));
+        }
+        Ok(())
+    }
+
+    fn resolve(self, paths: &mut impl ResolvePath) -> Result<EnemyPrefab, b::BevyError> {
+        self.validate()?;
+        Ok(EnemyPrefab {
+            sprite: paths.image(self.sprite),
+            destruction_particle: self.destruction_particle.map(|path| paths.image(path)),
+            hurt_sound: paths.sound(self.hurt_sound),
+            hurt_pitch: self.hurt_pitch,
+            health: self.health,
+            collider_radius: self.collider_radius,
+            contact_damage: self.contact_damage,
+            gun_pattern: self.gun_pattern,
+            gun_cooldown: self.gun_cooldown,
+            max_initial_gun_cooldown: self.max_initial_gun_cooldown,
+            shoot_sound: paths.sound(self.shoot_sound),
+            shoot_volume: bevy::audio::Volume::Decibels(self.shoot_volume_db),
+            drops: self.drops,
+            ai: self.ai,
+            station_time: self.station_time,
+            dive_speed: self.dive_speed,
+            entry_speed: self.entry_speed,
+            approach_stiffness: self.approach_stiffness,
+            approach_damping: self.approach_damping,
+            threat: self.threat,
+            score: self.score,
+            parts: PartPrefabs(
+                self.parts
+                    .into_iter()
+                    .map(|part| PartPrefab {
+                        sprite: paths.image(part.sprite),
+                        offset: Vec2::from(part.offset),
+                        health: part.health,
+                        collider_radius: part.collider_radius,
+                        critical: part.critical,
+                    })
+                    .collect(),
+            ),
+        })
+    }
+}
+
+impl PickupPrefabsFile {
+    fn resolve(self, paths: &mut impl ResolvePath) -> PickupPrefabs {
+        PickupPrefabs {
+            cool_sprite: paths.image(self.cool.sprite),
+            cool_amount: self.cool.amount,
+            cohere_sprite: paths.image(self.cohere.sprite),
+            cohere_amount: self.cohere.amount,
+            surge_sprite: paths.image(self.surge.sprite),
+            surge_amount: self.surge.amount,
+            surge_duration: self.surge.duration,
+            wide_sprite: paths.image(self.wide.sprite),
+            rapid_sprite: paths.image(self.rapid.sprite),
+            heavy_sprite: paths.image(self.heavy.sprite),
+            upgrade_overflow_coherence: self.upgrade_overflow_coherence,
+        }
+    }
+}
+
+/// Parses the prefab files built into the executable, for use when the asset files are
+/// unavailable.
+pub(crate) fn builtin_prefabs(
+    paths: &mut impl ResolvePath,
+) -> Result<(PickupPrefabs, BackdropPrefab), b::BevyError> {
+    let pickups: PickupPrefabsFile =
+        ron::de::from_str(include_str!("../assets/prefabs/all.pickups.ron"))?;
+    let backdrop: BackdropPrefab =
+        ron::de::from_str(include_str!("../assets/prefabs/deep-space.backdrop.ron"))?;
+    Ok((pickups.resolve(paths), backdrop.validate()?))
+}
+
+/// Parses the enemy prefab file for `kind` built into the executable, like
+/// [`builtin_prefabs()`].
+pub(crate) fn builtin_enemy_prefab(
+    kind: EnemyKind,
+    paths: &mut impl ResolvePath,
+) -> Result<EnemyPrefab, b::BevyError> {
+    let source = match kind {
+        EnemyKind::Grunt => include_str!("../assets/prefabs/basic.enemy.ron"),
+        EnemyKind::Tank => include_str!("../assets/prefabs/tank.enemy.ron"),
+        EnemyKind::Swarmer => include_str!("../assets/prefabs/swarmer.enemy.ron"),
+        EnemyKind::Sniper => include_str!("../assets/prefabs/sniper.enemy.ron"),
+    };
+    ron::de::from_str::<EnemyPrefabFile>(source)?.resolve(paths)
+}
+
+#[derive(Default, b::TypePath)]
+struct EnemyPrefabLoader;
+
+impl AssetLoader for EnemyPrefabLoader {
+    type Asset = EnemyPrefab;
+    type Settings = ();
+    type Error = b::BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        read_ron::<EnemyPrefabFile>(reader)
+            .await?
+            .resolve(load_context)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["enemy.ron"]
+    }
+}
+
+#[derive(Default, b::TypePath)]
+struct PickupPrefabsLoader;
+
+impl AssetLoader for PickupPrefabsLoader {
+    type Asset = PickupPrefabs;
+    type Settings = ();
+    type Error = b::BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        Ok(read_ron::<PickupPrefabsFile>(reader)
+            .await?
+            .resolve(load_context))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pickups.ron"]
+    }
+}
+
+#[derive(Default, b::TypePath)]
+struct BackdropPrefabLoader;
+
+impl AssetLoader for BackdropPrefabLoader {
+    type Asset = BackdropPrefab;
+    type Settings = ();
+    type Error = b::BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        read_ron::<BackdropPrefab>(reader).await?.validate()
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["backdrop.ron"]
+    }
+}
+
+/// Checks that `(min, max)` is a usable range of finite numbers.
+fn check_range((min, max): (f32, f32), what: &str) -> Result<(), b::BevyError> {
+    if !(min.is_finite() && max.is_finite()) {
+        return Err(b::BevyError::from(format!("{what} must be finite")));
+    }
+    if min > max {
+        return Err(b::BevyError::from(format!(
+            "{what} range is inverted: {min} > {max}"
+        )));
+    }
+    Ok(())
+}
+
+async fn read_ron<T: serde::de::DeserializeOwned>(
+    reader: &mut dyn Reader,
+) -> Result<T, b::BevyError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    Ok(ron::de::from_bytes(&bytes)?)
+}
@@ -1,18 +1,27 @@
 use core::fmt::Write as _;
 use std::f64::consts::PI;
 
+use bevy::color::Mix as _;
 use bevy::ecs::change_detection::DetectChangesMut;
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::ecs::system::SystemParam;
 use bevy::math::vec2;
 use bevy::prelude as b;
 
+use crate::boss::Boss;
+use crate::difficulty::Difficulty;
+use crate::game_mode::GameMode;
 use crate::rendering::{OuterCamera, PlayfieldCamera};
-use crate::{GameState, WinOrGameOver};
+use crate::{GameState, SpriteId, WinOrGameOver};
 
 // -------------------------------------------------------------------------------------------------
 
 /// A value between 0 and 1 that is displayed to the player as a bar.
 /// Other components on this entity define which quantity it is and how systems affect it.
-#[derive(Debug, b::Component)]
+///
+/// Gameplay systems should not modify quantities directly, but send [`QuantityDelta`]s.
+#[derive(Clone, Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) struct Quantity {
     /// Base value of the quantity, persisting unless changed.
     base: f32,
@@ -20,37 +29,98 @@ pub(crate) struct Quantity {
     /// An increase which becomes permanent if another increase is applied before this is removed.
     /// How removals happen depend on the specific quantity.
     temporary_stack: f32,
+
+    /// Time, in seconds, for which the effective value is held at 1 regardless of the other
+    /// fields. Counts down in [`quantity_behaviors_system`].
+    maxed_remaining: f32,
 }
 
 /// [`Quantity`] 1/3; affects shooting.
-#[derive(Clone, Copy, Debug, b::Component)]
+#[derive(Clone, Copy, Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) struct Coherence;
 
 /// [`Quantity`] 2/3; maxing it is game over.
-#[derive(Clone, Copy, Debug, b::Component)]
+#[derive(Clone, Copy, Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) struct Fever;
 
 /// [`Quantity`] 3/3; maxing it is a win.
-#[derive(Clone, Copy, Debug, b::Component)]
+#[derive(Clone, Copy, Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
 pub(crate) struct Fervor;
 
+/// Identifies one of the [`Quantity`] entities without needing a query for it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize)]
+pub(crate) enum QuantityKind {
+    Coherence,
+    Fever,
+    Fervor,
+}
+
+/// How a [`QuantityDelta`] modifies its [`Quantity`].
+/// Each of these corresponds to a `Quantity::adjust_*()` method.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize)]
+pub(crate) enum Adjustment {
+    PermanentIncludingTemporary,
+    PermanentClearingTemporary,
+    PermanentKeepingTemporary,
+    TemporaryCommittingPrevious,
+    TemporaryStacking,
+}
+
+/// Message requesting a change to a [`Quantity`].
+///
+/// All of these are applied by [`apply_quantity_deltas_system`], which gives us one place to
+/// log or modify the effects of gameplay on quantities.
+#[derive(Clone, Copy, Debug, b::Message)]
+pub(crate) struct QuantityDelta {
+    pub quantity: QuantityKind,
+    pub adjustment: Adjustment,
+    pub amount: f32,
+}
+
+/// Message requesting that a [`Quantity`]’s effective value be held at its maximum for
+/// `duration` seconds, or longer if it is already held for longer.
+///
+/// Applied by [`apply_quantity_deltas_system`] along with the [`QuantityDelta`]s.
+#[derive(Clone, Copy, Debug, b::Message)]
+pub(crate) struct QuantityHold {
+    pub quantity: QuantityKind,
+    pub duration: f32,
+}
+
 /// Specifies a [`Quantity`] this entity should update its visual appearance (e.g. bar length) from.
 /// Does not specify what type of update should be performed.
+///
+/// This is a relationship, so if the quantity entity is despawned, this component is removed
+/// rather than left pointing at nothing, and the display stops updating.
+/// To bind the display to a new quantity entity, insert a new `UpdateFromQuantity`.
 #[derive(Debug, b::Component)]
+#[relationship(relationship_target = QuantityDisplays)]
 pub(crate) struct UpdateFromQuantity {
+    #[relationship]
     pub quantity_entity: b::Entity,
     pub property: UpdateProperty,
     pub effect: UpdateEffect,
 }
 
-#[derive(Debug)]
+/// The entities whose [`UpdateFromQuantity`] refers to this [`Quantity`] entity.
+#[derive(Debug, b::Component)]
+#[relationship_target(relationship = UpdateFromQuantity)]
+pub(crate) struct QuantityDisplays(Vec<b::Entity>);
+
+// `Default`s are required by the relationship but are not otherwise meaningful.
+#[derive(Debug, Default)]
 pub(crate) enum UpdateProperty {
+    #[default]
     BaseValue,
     TemporaryValue,
     TemporaryStack,
 }
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub(crate) enum UpdateEffect {
+    #[default]
     BarLength,
     Opacity,
     PulsingOpacity,
@@ -58,44 +128,182 @@ pub(crate) enum UpdateEffect {
     TextPercentage,
 }
 
-// These constants are each the initial value of their corresponding `Quantity`
+/// Values of the quantities at the start of each run.
+///
+/// Part of [`Settings`](crate::settings::Settings) so that modes such as a “hot start” can be
+/// played without code changes. Each value defaults to the `INITIAL` constant of its quantity.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct StartingQuantities {
+    pub coherence: f32,
+    pub fever: f32,
+    pub fervor: f32,
+}
+
+impl Default for StartingQuantities {
+    fn default() -> Self {
+        Self {
+            coherence: Coherence::INITIAL,
+            fever: Fever::INITIAL,
+            fervor: Fervor::INITIAL,
+        }
+    }
+}
+
+impl StartingQuantities {
+    /// Starting Fever for the “hot start” mode, in which the run begins already feverish.
+    pub const HOT_START_FEVER: f32 = 0.8;
+
+    /// The values limited to what can be meaningfully played: between 0 and 1, and short of
+    /// [`Fever::LOSE_THRESHOLD`] and [`Fervor::WIN_THRESHOLD`] so that the run does not end
+    /// as soon as it starts.
+    pub fn clamped(&self) -> Self {
+        Self {
+            coherence: self.coherence.clamp(0.0, 1.0),
+            fever: self.fever.clamp(0.0, Fever::LOSE_THRESHOLD - 0.05),
+            fervor: self.fervor.clamp(0.0, Fervor::WIN_THRESHOLD - 0.05),
+        }
+    }
+}
+
+// These constants are each the default initial value of their corresponding `Quantity`;
+// see `StartingQuantities`.
 impl Coherence {
     pub const INITIAL: f32 = 0.0;
 }
 impl Fever {
     pub const INITIAL: f32 = 0.5;
+
+    /// Effective value at which the game is lost.
+    pub const LOSE_THRESHOLD: f32 = FULL;
 }
 impl Fervor {
     pub const INITIAL: f32 = 0.0;
+
+    /// Base value at which the game is won.
+    pub const WIN_THRESHOLD: f32 = FULL;
 }
 
+/// Value at or above which a [`Quantity`] counts as full, and is displayed as 100%.
+///
+/// This is slightly less than 1 because [`Fervor`] approaches its maximum only gradually.
+/// The end conditions use this, and [`display_percent()`] never shows 100% below it,
+/// so that a bar reads 100% exactly when it has ended the game.
+pub(crate) const FULL: f32 = 0.999;
+
+/// Most that [`Fervor`]’s base value may be while the run cannot be won, such as while a boss
+/// remains, so that its bar does not read 100%.
+const FERVOR_CAP_UNTIL_WINNABLE: f32 = 0.99;
+
+/// Length of a bar at value 1.0, in UI pixels.
+pub(crate) const BAR_LENGTH: f32 = 459.0;
+/// Thickness of a bar’s fill, in UI pixels.
+pub(crate) const BAR_WIDTH: f32 = 16.0;
+/// Image mode for bar fill sprites whose length is at least one tile.
+/// Shorter fills are handled specially by [`update_quantity_display_system_2()`].
+pub(crate) const BAR_FILL_IMAGE_MODE: b::SpriteImageMode = b::SpriteImageMode::Tiled {
+    tile_x: true,
+    tile_y: true,
+    stretch_value: 1.0,
+};
+
+/// Minimum number of characters in the output of [`UpdateEffect::TextPercentage`], for layout.
+pub(crate) const PERCENTAGE_TEXT_CHARS: usize = 4;
+const GAIN_TEXT_COLOR: b::Color = b::Color::srgb(0.5, 1.0, 0.4);
+const LOSS_TEXT_COLOR: b::Color = b::Color::srgb(1.0, 0.35, 0.3);
+
+/// How close [`Coherence`] must be to [`Fever`] for the [`Fervor`] label to warn that
+/// [`fervor_is_active()`] is about to change.
+const FERVOR_FLIP_MARGIN: f32 = 0.05;
+
+/// Tint of the playfield frame while [`fervor_is_active()`].
+const FRAME_FERVOR_TINT: b::Srgba = b::Srgba::rgb(0.7, 1.0, 0.6);
+/// Tint the playfield frame flickers towards while [`Coherence`] is maxed by a surge.
+const FRAME_SURGE_TINT: b::Srgba = b::Srgba::rgb(1.0, 0.85, 0.3);
+/// Tint the playfield frame pulses towards when [`Fever`] is high.
+const FRAME_FEVER_TINT: b::Srgba = b::Srgba::rgb(1.0, 0.2, 0.15);
+/// [`Fever`] value at which the playfield frame starts warning.
+const FRAME_FEVER_WARNING_START: f32 = 0.7;
+
 // -------------------------------------------------------------------------------------------------
-// Convenient aliases for quantity queries so we don’t have to write so many `Without`s.
-
-pub(crate) type ReadQuantity<'w, 's, T> = b::Single<'w, 's, &'static Quantity, b::With<T>>;
-
-// We could eliminate this boilerplate too by giving each quantity an associated type which provides
-// its needed `Without`s, but that doesn’t seem worthwhile for now.
-// Also, Bevy is working on mutually exclusive components which will hopefully make this whole thing
-// unnecessary.
-pub(crate) type QCoherenceMut<'w, 's> = b::Single<
-    'w,
-    's,
-    &'static mut Quantity,
-    (b::With<Coherence>, b::Without<Fever>, b::Without<Fervor>),
->;
-pub(crate) type QFeverMut<'w, 's> = b::Single<
-    'w,
-    's,
-    &'static mut Quantity,
-    (b::With<Fever>, b::Without<Coherence>, b::Without<Fervor>),
->;
-pub(crate) type QFervorMut<'w, 's> = b::Single<
-    'w,
-    's,
-    &'static mut Quantity,
-    (b::With<Fervor>, b::Without<Coherence>, b::Without<Fever>),
->;
+// Access to the quantities without a separate query, and `Without` filters, for each one.
+
+/// Marker component which identifies one of the [`Quantity`] entities.
+pub(crate) trait QuantityMarker: b::Component {
+    const KIND: QuantityKind;
+}
+impl QuantityMarker for Coherence {
+    const KIND: QuantityKind = QuantityKind::Coherence;
+}
+impl QuantityMarker for Fever {
+    const KIND: QuantityKind = QuantityKind::Fever;
+}
+impl QuantityMarker for Fervor {
+    const KIND: QuantityKind = QuantityKind::Fervor;
+}
+
+/// Which entity is each [`Quantity`].
+/// The quantity entities are spawned once and exist forever.
+#[derive(Clone, Copy, Debug, b::Resource)]
+pub(crate) struct QuantityEntities {
+    pub coherence: b::Entity,
+    pub fever: b::Entity,
+    pub fervor: b::Entity,
+}
+
+/// Read access to all [`Quantity`]s.
+#[derive(SystemParam)]
+pub(crate) struct Quantities<'w, 's> {
+    entities: b::Res<'w, QuantityEntities>,
+    query: b::Query<'w, 's, &'static Quantity>,
+}
+
+/// Write access to all [`Quantity`]s.
+///
+/// Gameplay systems should not use this, but send [`QuantityDelta`]s.
+#[derive(SystemParam)]
+pub(crate) struct QuantitiesMut<'w, 's> {
+    entities: b::Res<'w, QuantityEntities>,
+    query: b::Query<'w, 's, &'static mut Quantity>,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl QuantityEntities {
+    pub fn get(&self, kind: QuantityKind) -> b::Entity {
+        match kind {
+            QuantityKind::Coherence => self.coherence,
+            QuantityKind::Fever => self.fever,
+            QuantityKind::Fervor => self.fervor,
+        }
+    }
+}
+
+impl Quantities<'_, '_> {
+    pub fn get<T: QuantityMarker>(&self) -> Result<&Quantity, b::BevyError> {
+        self.kind(T::KIND)
+    }
+
+    pub fn kind(&self, kind: QuantityKind) -> Result<&Quantity, b::BevyError> {
+        Ok(self.query.get(self.entities.get(kind))?)
+    }
+}
+
+impl QuantitiesMut<'_, '_> {
+    pub fn kind_mut(&mut self, kind: QuantityKind) -> Result<b::Mut<'_, Quantity>, b::BevyError> {
+        Ok(self.query.get_mut(self.entities.get(kind))?)
+    }
+
+    /// Returns [`Coherence`], [`Fever`], and [`Fervor`], in that order.
+    pub fn all_mut(&mut self) -> Result<[b::Mut<'_, Quantity>; 3], b::BevyError> {
+        let QuantityEntities {
+            coherence,
+            fever,
+            fervor,
+        } = *self.entities;
+        Ok(self.query.get_many_mut([coherence, fever, fervor])?)
+    }
+}
 
 // -------------------------------------------------------------------------------------------------
 
@@ -104,6 +312,7 @@ impl Quantity {
         Self {
             base: value,
             temporary_stack: 0.0,
+            maxed_remaining: 0.0,
         }
     }
 
@@ -133,6 +342,10 @@ impl Quantity {
         self.temporary_stack += delta;
     }
 
+    pub fn hold_maxed_for(&mut self, duration: f32) {
+        self.maxed_remaining = self.maxed_remaining.max(duration);
+    }
+
     /// Set the value and erase any temporary modifications
     pub fn reset_to(&mut self, value: f32) {
         self.set_base_only(value);
@@ -156,70 +369,194 @@ impl Quantity {
     }
 
     pub fn unclamped_effective_value(&self) -> f32 {
-        self.base + self.temporary_stack
+        if self.is_maxed() {
+            (self.base + self.temporary_stack).max(1.0)
+        } else {
+            self.base + self.temporary_stack
+        }
+    }
+
+    /// Whether the effective value is being held at its maximum by a [`QuantityHold`].
+    pub fn is_maxed(&self) -> bool {
+        self.maxed_remaining > 0.0
+    }
+
+    pub fn base(&self) -> f32 {
+        self.base
+    }
+
+    /// The whole percentage shown on this quantity’s bar; see [`FULL`].
+    pub fn display_percent(&self) -> i32 {
+        display_percent(self.base)
     }
 
     pub fn temporary_stack(&self) -> f32 {
         self.temporary_stack
     }
+
+    pub fn apply(&mut self, adjustment: Adjustment, amount: f32) {
+        match adjustment {
+            Adjustment::PermanentIncludingTemporary => {
+                self.adjust_permanent_including_temporary(amount)
+            }
+            Adjustment::PermanentClearingTemporary => {
+                self.adjust_permanent_clearing_temporary(amount)
+            }
+            Adjustment::PermanentKeepingTemporary => {
+                self.adjust_permanent_keeping_temporary(amount)
+            }
+            Adjustment::TemporaryCommittingPrevious => {
+                self.adjust_temporary_and_commit_previous_temporary(amount)
+            }
+            Adjustment::TemporaryStacking => self.adjust_temporary_stacking_with_previous(amount),
+        }
+    }
+}
+
+impl QuantityDelta {
+    pub fn new(quantity: QuantityKind, adjustment: Adjustment, amount: f32) -> Self {
+        Self {
+            quantity,
+            adjustment,
+            amount,
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
+/// Applies all [`QuantityDelta`]s and [`QuantityHold`]s sent since the last time this ran.
+pub(crate) fn apply_quantity_deltas_system(
+    mut deltas: b::MessageReader<QuantityDelta>,
+    mut holds: b::MessageReader<QuantityHold>,
+    mut quantities: QuantitiesMut,
+    difficulty: b::Res<Difficulty>,
+) -> b::Result {
+    for &delta in deltas.read() {
+        let QuantityDelta {
+            quantity,
+            adjustment,
+            amount,
+        } = difficulty.scale_delta(delta);
+        quantities.kind_mut(quantity)?.apply(adjustment, amount);
+    }
+    for &QuantityHold { quantity, duration } in holds.read() {
+        quantities.kind_mut(quantity)?.hold_maxed_for(duration);
+    }
+    Ok(())
+}
+
 pub(crate) fn quantity_behaviors_system(
     time: b::Res<b::Time>,
-    mut coherence: QCoherenceMut,
-    mut fever: QFeverMut,
-    mut fervor: QFervorMut,
+    mut quantities: QuantitiesMut,
     mut next_state: b::ResMut<b::NextState<GameState>>,
     mut next_wog_state: b::ResMut<b::NextState<WinOrGameOver>>,
+    bosses: b::Query<(), b::With<Boss>>,
+    game_mode: b::Res<GameMode>,
 ) -> b::Result {
+    let [mut coherence, mut fever, mut fervor] = quantities.all_mut()?;
+
+    step_quantity_behaviors(&mut coherence, &mut fever, &mut fervor, time.delta_secs());
+
+    // The run cannot be won while a boss remains, or ever in some modes.
+    let winnable = game_mode.can_win() && bosses.is_empty();
+    if !winnable {
+        fervor.base = fervor.base.min(FERVOR_CAP_UNTIL_WINNABLE);
+    }
+
     // Win and lose conditions.
+    // These are checked after this tick's changes, which are also what the displays will show
+    // at the end of this tick, so the game never ends a tick later than a bar reads 100%.
     // Side effects of these will be handled by OnEnter `crate::end_of_game_effects()`
-    if fever.effective_value() == 1.0 {
+    if fever.effective_value() >= Fever::LOSE_THRESHOLD {
         (*next_state).set_if_neq(GameState::WinOrGameOver);
         next_wog_state.set(WinOrGameOver::GameOver);
-    } else if fervor.base >= 0.999 {
+    } else if winnable && fervor.base >= Fervor::WIN_THRESHOLD {
         (*next_state).set_if_neq(GameState::WinOrGameOver);
         next_wog_state.set(WinOrGameOver::Win);
     }
 
+    Ok(())
+}
+
+/// Rounds a quantity value, or change of one, to a whole percentage for display.
+/// Only values which are [`FULL`] are shown as 100%.
+fn display_percent(value: f32) -> i32 {
+    let magnitude = if value.abs() >= FULL {
+        100
+    } else {
+        ((value.abs() * 100.0).round() as i32).min(99)
+    };
+    magnitude * value.signum() as i32
+}
+
+/// The gradual changes of the quantities over `dt` seconds, performed by
+/// [`quantity_behaviors_system`]. Separate from the system so that it can be tested.
+fn step_quantity_behaviors(
+    coherence: &mut Quantity,
+    fever: &mut Quantity,
+    fervor: &mut Quantity,
+    dt: f32,
+) {
+    for quantity in [&mut *coherence, &mut *fever, &mut *fervor] {
+        quantity.maxed_remaining = (quantity.maxed_remaining - dt).max(0.0);
+    }
+
     // Loss of coherence becomes permanent if not removed
     {
-        let coherence_change = coherence.temporary_stack * (2.0f32.powf(time.delta_secs()) - 1.0);
+        let coherence_change = coherence.temporary_stack * (2.0f32.powf(dt) - 1.0);
         coherence.adjust_permanent_keeping_temporary_absolutely(coherence_change);
     }
 
     // Excess fever goes away if not committed
-    fever.temporary_stack *= 0.3f32.powf(time.delta_secs());
+    fever.temporary_stack *= 0.3f32.powf(dt);
 
     // Fervor's permanent value moves towards its temporary value
     {
-        let change = fervor.temporary_stack * 0.3f32 * time.delta_secs();
+        let change = fervor.temporary_stack * 0.3f32 * dt;
         fervor.adjust_permanent_keeping_temporary_absolutely(change);
     }
 
     // Temporary fervor goes down linearly until it hits a most-negative value of -0.1 or the base
     // value, whichever is higher.
-    fervor.temporary_stack = (fervor.temporary_stack - 0.06f32 * time.delta_secs())
+    fervor.temporary_stack = (fervor.temporary_stack - 0.06f32 * dt)
         .max(-0.1)
         .max(-fervor.base);
-
-    Ok(())
 }
 
 /// Updates display in quantity-specific ways
+#[allow(
+    clippy::type_complexity,
+    reason = "the filters keep the sprite queries disjoint"
+)]
 pub(crate) fn update_quantity_display_system_1(
-    assets: Option<b::Res<crate::MyAssets>>,
-    fever: ReadQuantity<Fever>,
-    coherence: ReadQuantity<Coherence>,
-    // fervor: QFervor,
+    time: b::Res<b::Time>,
+    assets: Option<b::Res<crate::GameAssets>>,
+    quantities: Quantities,
     mut fervor_label_sprite: b::Single<&mut b::Sprite, b::With<crate::BarLabelSprite<Fervor>>>,
+    fervor_flip_marker: b::Single<
+        (&mut b::Sprite, &mut b::Visibility),
+        (
+            b::With<crate::FervorFlipMarker>,
+            b::Without<crate::BarLabelSprite<Fervor>>,
+        ),
+    >,
+    mut playfield_frame_sprite: b::Single<
+        &mut b::Sprite,
+        (
+            b::With<crate::PlayfieldFrame>,
+            b::Without<crate::BarLabelSprite<Fervor>>,
+            b::Without<crate::FervorFlipMarker>,
+        ),
+    >,
     cameras_to_color: b::Query<
         &mut b::Camera,
         b::Or<(b::With<PlayfieldCamera>, b::With<OuterCamera>)>,
     >,
 ) -> b::Result {
+    let fever = quantities.get::<Fever>()?;
+    let coherence = quantities.get::<Coherence>()?;
+
     let clear_color = bevy::camera::ClearColorConfig::Custom(b::Color::oklch(
         fever.effective_value() * 0.05,
         fever.effective_value(),
@@ -230,13 +567,47 @@ pub(crate) fn update_quantity_display_system_1(
     }
 
     if let Some(assets) = assets {
-        let image = if fervor_is_active(&fever, &coherence) {
-            &assets.text_bar_fervor_sprite
+        let image = assets.sprite(if fervor_is_active(fever, coherence) {
+            SpriteId::TextBarFervor
         } else {
-            &assets.text_bar_fervor_inactive_sprite
+            SpriteId::TextBarFervorInactive
+        });
+        if fervor_label_sprite.image != image {
+            fervor_label_sprite.image = image;
+        }
+    }
+
+    // Warn when fervor is about to become active or inactive.
+    {
+        let (mut marker_sprite, mut marker_visibility) = fervor_flip_marker.into_inner();
+        let near_flip =
+            (coherence.effective_value() - fever.effective_value()).abs() < FERVOR_FLIP_MARGIN;
+        marker_visibility.set_if_neq(if near_flip {
+            b::Visibility::Inherited
+        } else {
+            b::Visibility::Hidden
+        });
+        let pulse = 0.6 + (time.elapsed_secs_f64() * PI * 4.0).sin() as f32 * 0.4;
+        marker_sprite.color = b::Color::srgba(1.0, 1.0, 1.0, pulse);
+    }
+
+    // Tint the playfield frame so the player can notice status changes without looking at bars.
+    {
+        let base_tint = if coherence.is_maxed() {
+            let flicker = 0.5 + (time.elapsed_secs_f64() * PI * 8.0).sin() as f32 * 0.5;
+            FRAME_FERVOR_TINT.mix(&FRAME_SURGE_TINT, flicker)
+        } else if fervor_is_active(fever, coherence) {
+            FRAME_FERVOR_TINT
+        } else {
+            b::Srgba::WHITE
         };
-        if fervor_label_sprite.image != *image {
-            fervor_label_sprite.image = image.clone();
+        let fever_warning = ((fever.effective_value() - FRAME_FEVER_WARNING_START)
+            / (Fever::LOSE_THRESHOLD - FRAME_FEVER_WARNING_START))
+            .clamp(0.0, 1.0);
+        let pulse = 0.5 + (time.elapsed_secs_f64() * PI * 3.0).sin() as f32 * 0.5;
+        let tint = b::Color::from(base_tint.mix(&FRAME_FEVER_TINT, fever_warning * pulse));
+        if playfield_frame_sprite.color != tint {
+            playfield_frame_sprite.color = tint;
         }
     }
 
@@ -245,18 +616,29 @@ pub(crate) fn update_quantity_display_system_1(
 
 /// Updates sprites from quantities as specified by [`UpdateFromQuantity`] components.
 /// The main job of this system is to update the bars.
+#[allow(
+    clippy::type_complexity,
+    reason = "each display may have any of several components to update"
+)]
 pub(crate) fn update_quantity_display_system_2(
     time: b::Res<b::Time>,
+    images: b::Res<b::Assets<b::Image>>,
     quantities: b::Query<&Quantity>,
     sprites_to_update: b::Query<(
         Option<&mut b::Sprite>,
         Option<&mut b::Visibility>,
         Option<&mut b::Text2d>,
+        Option<&mut b::TextColor>,
         &UpdateFromQuantity,
     )>,
 ) -> b::Result {
-    for (sprite, visibility, text, ufq) in sprites_to_update {
-        let quantity: &Quantity = quantities.get(ufq.quantity_entity)?;
+    for (sprite, visibility, text, text_color, ufq) in sprites_to_update {
+        let Ok(quantity) = quantities.get(ufq.quantity_entity) else {
+            // Should not happen since the relationship is removed when the quantity entity is
+            // despawned, but if it does, don’t let one display stop the others.
+            b::warn_once!("{:?} is not a quantity entity", ufq.quantity_entity);
+            continue;
+        };
         let value = match ufq.property {
             UpdateProperty::BaseValue => quantity.base,
             UpdateProperty::TemporaryStack => quantity.temporary_stack,
@@ -265,11 +647,23 @@ pub(crate) fn update_quantity_display_system_2(
         };
         match ufq.effect {
             UpdateEffect::BarLength => {
-                let length_scale = 459.0;
-                let width = 16.0;
-                sprite
-                    .expect("need sprite component for BarLength")
-                    .custom_size = Some(vec2(length_scale * value, width));
+                let mut sprite = sprite.expect("need sprite component for BarLength");
+                let length = BAR_LENGTH * value;
+                let tile_size = images.get(&sprite.image).map(b::Image::size_f32);
+                match tile_size {
+                    // `SpriteImageMode::Tiled` squashes the entire tile into the sprite when the
+                    // sprite is shorter than one tile. Instead, crop the image to the part that
+                    // tiling would have shown.
+                    Some(tile_size) if length < tile_size.x => {
+                        sprite.image_mode = b::SpriteImageMode::Auto;
+                        sprite.rect = Some(b::Rect::new(0.0, 0.0, length, tile_size.y));
+                    }
+                    _ => {
+                        sprite.image_mode = BAR_FILL_IMAGE_MODE;
+                        sprite.rect = None;
+                    }
+                }
+                sprite.custom_size = Some(vec2(length, BAR_WIDTH));
             }
             UpdateEffect::Opacity => {
                 sprite.expect("need sprite component for Opacity").color =
@@ -292,13 +686,26 @@ pub(crate) fn update_quantity_display_system_2(
             UpdateEffect::TextPercentage => {
                 let text: &mut String =
                     &mut text.expect("need text component for TextPercentage").0;
+                let percent = display_percent(value);
+                // Widths here should match PERCENTAGE_TEXT_CHARS so that digits line up
+                // when right-aligned.
                 text.clear();
                 if let UpdateProperty::TemporaryStack = ufq.property {
-                    if value.abs() > 0.001 {
-                        write!(text, "{:+2}%", (value * 100.0).round()).unwrap();
+                    if percent != 0 {
+                        write!(text, "{percent:>+3}%").unwrap();
+                    }
+                    if let Some(mut text_color) = text_color {
+                        let color = if percent > 0 {
+                            GAIN_TEXT_COLOR
+                        } else {
+                            LOSS_TEXT_COLOR
+                        };
+                        if text_color.0 != color {
+                            text_color.0 = color;
+                        }
                     }
                 } else {
-                    write!(text, "{:2}%", (value * 100.0).round()).unwrap();
+                    write!(text, "{percent:>3}%").unwrap();
                 }
             }
         }
@@ -309,3 +716,121 @@ pub(crate) fn update_quantity_display_system_2(
 pub(crate) fn fervor_is_active(fever: &Quantity, coherence: &Quantity) -> bool {
     coherence.effective_value() > fever.effective_value()
 }
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Typical fixed timestep; see [`Settings::fixed_timestep_hz`](crate::settings::Settings).
+    const DT: f32 = 1.0 / 64.0;
+
+    fn quantity(base: f32, temporary_stack: f32) -> Quantity {
+        Quantity {
+            base,
+            temporary_stack,
+            maxed_remaining: 0.0,
+        }
+    }
+
+    /// Steps the behaviors for `seconds` of game time at [`DT`], and returns
+    /// `[coherence, fever, fervor]`.
+    fn run(mut quantities: [Quantity; 3], seconds: f32) -> [Quantity; 3] {
+        let [coherence, fever, fervor] = &mut quantities;
+        for _ in 0..(seconds / DT).round() as u32 {
+            step_quantity_behaviors(coherence, fever, fervor, DT);
+        }
+        quantities
+    }
+
+    #[track_caller]
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn coherence_temporary_loss_becomes_permanent() {
+        let [coherence, _, _] = run(
+            [quantity(0.5, -0.1), quantity(0.0, 0.0), quantity(0.0, 0.0)],
+            1.0,
+        );
+        assert_near(coherence.base, 0.44962);
+        assert_near(coherence.temporary_stack, -0.04962);
+        // The transfer does not change the effective value.
+        assert_near(coherence.effective_value(), 0.4);
+    }
+
+    #[test]
+    fn fever_temporary_dissipates() {
+        let [_, fever, _] = run(
+            [quantity(0.0, 0.0), quantity(0.5, 0.2), quantity(0.0, 0.0)],
+            1.0,
+        );
+        assert_near(fever.base, 0.5);
+        assert_near(fever.temporary_stack, 0.2 * 0.3);
+
+        // Dissipation is exponential, so it does not depend on the timestep.
+        let mut coarse_fever = quantity(0.5, 0.2);
+        for _ in 0..16 {
+            step_quantity_behaviors(
+                &mut quantity(0.0, 0.0),
+                &mut coarse_fever,
+                &mut quantity(0.0, 0.0),
+                1.0 / 16.0,
+            );
+        }
+        assert_near(coarse_fever.temporary_stack, fever.temporary_stack);
+    }
+
+    #[test]
+    fn fervor_drifts_towards_temporary_then_decays() {
+        let initial = [quantity(0.0, 0.0), quantity(0.0, 0.0), quantity(0.2, 0.1)];
+
+        let [_, _, fervor] = run(initial.clone(), 1.0);
+        assert_near(fervor.base, 0.21791);
+        assert_near(fervor.temporary_stack, 0.02209);
+
+        // Eventually the temporary stack is negative, limited by the base value.
+        let [_, _, fervor] = run(initial, 10.0);
+        assert_near(fervor.base, 0.03333);
+        assert_near(fervor.temporary_stack, -0.03333);
+    }
+
+    #[test]
+    fn fervor_temporary_floor() {
+        let [_, _, fervor] = run(
+            [quantity(0.0, 0.0), quantity(0.0, 0.0), quantity(0.8, -0.5)],
+            DT,
+        );
+        assert_near(fervor.temporary_stack, -0.1);
+    }
+
+    #[test]
+    fn percent_display_matches_end_conditions() {
+        assert_eq!(display_percent(Fervor::WIN_THRESHOLD), 100);
+        assert_eq!(display_percent(Fever::LOSE_THRESHOLD), 100);
+        assert_eq!(display_percent(1.0), 100);
+        assert_eq!(display_percent(0.9985), 99);
+        assert_eq!(display_percent(0.994), 99);
+        assert_eq!(display_percent(0.5), 50);
+        assert_eq!(display_percent(-0.1), -10);
+        assert_eq!(display_percent(0.0), 0);
+    }
+
+    #[test]
+    fn maxed_counts_down() {
+        let mut coherence = quantity(0.3, 0.0);
+        coherence.hold_maxed_for(0.5);
+        let [coherence, _, _] = run([coherence, quantity(0.0, 0.0), quantity(0.0, 0.0)], 0.25);
+        assert!(coherence.is_maxed());
+        assert_eq!(coherence.effective_value(), 1.0);
+
+        let [coherence, _, _] = run([coherence, quantity(0.0, 0.0), quantity(0.0, 0.0)], 0.25);
+        assert!(!coherence.is_maxed());
+        assert_near(coherence.effective_value(), 0.3);
+    }
+}
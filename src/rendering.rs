@@ -1,13 +1,24 @@
 use std::f32::consts::PI;
 
+use avian2d::prelude as p;
 use bevy::camera::visibility::RenderLayers;
+use bevy::color::Alpha as _;
+use bevy::ecs::change_detection::{DetectChanges as _, DetectChangesMut as _};
+use bevy::math::{Vec3Swizzles as _, Vec4, vec4};
 use bevy::prelude as b;
 use bevy::render::render_resource::{
-    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    AsBindGroup, Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
 };
+use bevy::shader::ShaderRef;
+use bevy::sprite_render::{Material2d, MeshMaterial2d};
 use bevy::utils::default;
 
-use crate::{PLAYFIELD_SIZE, SCREEN_SIZE};
+use crate::bullets_and_targets::{Bullet, CosmeticProjectile, ProjectileLedger};
+use crate::game_mode::GameMode;
+use crate::quantity::Quantities;
+use crate::run_stats::RunEvent;
+use crate::settings::Settings;
+use crate::{Fervor, Fever, Lifetime, PLAYFIELD_SIZE, SCREEN_SIZE};
 
 // -------------------------------------------------------------------------------------------------
 
@@ -21,6 +32,7 @@ pub(crate) const HIGH_RES_LAYERS: RenderLayers = RenderLayers::layer(2);
 
 /// Z position values for sprites for when disambiguation may be needed
 pub(crate) enum Zees {
+    Backdrop = -4,
     Starfield = -3,
     Pickup = -2,
     Bullets = -1,
@@ -49,7 +61,28 @@ impl Zees {
 /// Low-resolution texture that contains the pixel-perfect world.
 /// Canvas itself is rendered to the high-resolution world.
 #[derive(b::Component)]
-struct Canvas;
+pub(crate) struct Canvas;
+
+/// Material for the [`Canvas`] which distorts the playfield as [`Fever`] gets high,
+/// as a warning.
+#[derive(Clone, Debug, b::Asset, b::TypePath, AsBindGroup)]
+pub(crate) struct HeatHazeMaterial {
+    /// x is intensity from 0 to 1, y is time in seconds.
+    #[uniform(0)]
+    params: Vec4,
+    /// Min and max UV coordinates of the playfield within the canvas.
+    #[uniform(1)]
+    playfield_uv: Vec4,
+    #[texture(2)]
+    #[sampler(3)]
+    canvas: b::Handle<b::Image>,
+}
+
+impl Material2d for HeatHazeMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "heat-haze.wgsl".into()
+    }
+}
 
 /// Camera that renders the gameplay objects to the [`Canvas`].
 /// Has a restricted viewport to crop objects.
@@ -64,11 +97,34 @@ pub(crate) struct UiCamera;
 #[derive(b::Component)]
 pub(crate) struct OuterCamera;
 
+/// Line at the left (-1) or right (1) edge of the window, shown when the window is too narrow
+/// for the whole [`Canvas`] so that it is clear the side panels are cut off.
+#[derive(b::Component)]
+pub(crate) struct CropIndicator(f32);
+
+const CROP_INDICATOR_COLOR: b::Color = b::Color::srgba(1.0, 1.0, 1.0, 0.3);
+
+/// Position of the mouse cursor on the [`Canvas`], updated each frame by
+/// [`update_canvas_cursor_system`].
+///
+/// Each field is [`None`] when the cursor is not over the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, b::Resource)]
+pub(crate) struct CanvasCursor {
+    /// Position in the coordinates of entities on [`UI_LAYERS`]:
+    /// canvas pixels from the center of the screen, with y up.
+    pub ui: Option<b::Vec2>,
+    /// Position in the coordinates of entities on [`PLAYFIELD_LAYERS`], which differ from
+    /// [`Self::ui`] while the [`CameraZoom`] is zoomed in.
+    pub playfield: Option<b::Vec2>,
+}
+
 // -------------------------------------------------------------------------------------------------
 
 pub(crate) fn setup_camera_system(
     mut commands: b::Commands,
     mut images: b::ResMut<b::Assets<b::Image>>,
+    mut meshes: b::ResMut<b::Assets<b::Mesh>>,
+    mut materials: b::ResMut<b::Assets<HeatHazeMaterial>>,
 ) {
     // “Pixel perfect” setup per <https://github.com/bevyengine/bevy/blob/release-0.18.1/examples/2d/pixel_grid_snap.rs>
 
@@ -130,9 +186,20 @@ pub(crate) fn setup_camera_system(
         UI_LAYERS,
     ));
 
-    // This sprite renders the pixel camera image to the `OuterCamera`
+    // This mesh renders the pixel camera image to the `OuterCamera`
+    let playfield_uv_min = (SCREEN_SIZE - PLAYFIELD_SIZE).as_vec2() / 2.0 / SCREEN_SIZE.as_vec2();
     commands.spawn((
-        b::Sprite::from_image(pixel_camera_image_handle),
+        b::Mesh2d(meshes.add(b::Rectangle::from_size(SCREEN_SIZE.as_vec2()))),
+        MeshMaterial2d(materials.add(HeatHazeMaterial {
+            params: Vec4::ZERO,
+            playfield_uv: vec4(
+                playfield_uv_min.x,
+                playfield_uv_min.y,
+                1.0 - playfield_uv_min.x,
+                1.0 - playfield_uv_min.y,
+            ),
+            canvas: pixel_camera_image_handle,
+        })),
         Canvas,
         HIGH_RES_LAYERS,
     ));
@@ -148,6 +215,19 @@ pub(crate) fn setup_camera_system(
         OuterCamera,
         HIGH_RES_LAYERS,
     ));
+    for side in [-1.0, 1.0] {
+        commands.spawn((
+            CropIndicator(side),
+            b::Sprite {
+                color: CROP_INDICATOR_COLOR,
+                custom_size: Some(b::vec2(1.0, SCREEN_SIZE.y as f32)),
+                ..default()
+            },
+            b::Transform::from_xyz(0.0, 0.0, 1.0),
+            b::Visibility::Hidden,
+            HIGH_RES_LAYERS,
+        ));
+    }
 
     // Spatial audio listener (*not* attached to the player ship)
     commands.spawn((
@@ -157,24 +237,399 @@ pub(crate) fn setup_camera_system(
     ));
 }
 
-/// Scales camera projection to fit the window (integer multiples only).
+/// Updates [`CanvasCursor`] from the primary window’s cursor position.
+///
+/// This works because the [`OuterCamera`]’s world coordinates coincide with the canvas’s:
+/// the [`Canvas`] is centered on the origin and drawn at one unit per canvas pixel,
+/// whatever scale [`fit_canvas_to_window_system`] chose. The [`UiCamera`] and
+/// [`PlayfieldCamera`] are also centered on the origin, the latter zoomed and moved by
+/// [`CameraZoom`].
+pub(crate) fn update_canvas_cursor_system(
+    window: b::Single<&b::Window, b::With<bevy::window::PrimaryWindow>>,
+    camera: b::Single<(&b::Camera, &b::GlobalTransform), b::With<OuterCamera>>,
+    zoom: b::Res<CameraZoom>,
+    mut cursor: b::ResMut<CanvasCursor>,
+) {
+    let (camera, camera_transform) = *camera;
+    let ui = window
+        .cursor_position()
+        .and_then(|position| camera.viewport_to_world_2d(camera_transform, position).ok());
+    let new_cursor = CanvasCursor {
+        ui,
+        playfield: ui.map(|ui| ui * zoom.scale + zoom.offset),
+    };
+    if *cursor != new_cursor {
+        *cursor = new_cursor;
+    }
+}
+
+/// Scales camera projection to fit the window.
+///
+/// The scale is an integer multiple when possible. If the window is too small for even 1×,
+/// the canvas is shrunk by a fractional amount instead, so that the playfield is never cropped.
+/// With [`Settings::vertical_fit`], only the playfield’s width needs to fit, and the
+/// [`CropIndicator`]s show when the side panels are cut off.
 pub(crate) fn fit_canvas_to_window_system(
     mut resize_messages: b::MessageReader<bevy::window::WindowResized>,
-    windows: b::Query<&b::Window>,
+    settings: b::Res<Settings>,
+    window: b::Single<&b::Window, b::With<bevy::window::PrimaryWindow>>,
     mut projection: b::Single<&mut b::Projection, b::With<OuterCamera>>,
+    crop_indicators: b::Query<(&CropIndicator, &mut b::Transform, &mut b::Visibility)>,
 ) -> b::Result {
     let b::Projection::Orthographic(projection) = &mut **projection else {
         return Err(b::BevyError::from("projection not orthographic"));
     };
-    for window_resized in resize_messages.read() {
-        let window = windows.get(window_resized.window)?;
+    if resize_messages.read().count() == 0 && !settings.is_changed() {
+        return Ok(());
+    }
+
+    // compute scale factor in physical pixels
+    let size = window.physical_size().as_vec2();
+    if size.min_element() == 0.0 {
+        // minimized; keep the old scale rather than dividing by zero
+        return Ok(());
+    }
+    let fit_size = if settings.vertical_fit {
+        b::vec2(PLAYFIELD_SIZE.x as f32, SCREEN_SIZE.y as f32)
+    } else {
+        SCREEN_SIZE.as_vec2()
+    };
+    let fit = (size / fit_size).min_element();
+    let scale = if fit >= 1.0 { fit.floor() } else { fit };
+    projection.scale = window.scale_factor() / scale;
 
-        // compute scale factor in physical pixels
-        let size = window.physical_size();
-        let h_scale = size.x / SCREEN_SIZE.x;
-        let v_scale = size.y / SCREEN_SIZE.y;
+    // canvas pixels from the center to the edge of the window
+    let visible_half_width = size.x / scale / 2.0;
+    let cropped = visible_half_width < SCREEN_SIZE.x as f32 / 2.0;
+    for (&CropIndicator(side), mut transform, mut visibility) in crop_indicators {
+        transform.translation.x = side * (visible_half_width - 0.5);
+        *visibility = if cropped {
+            b::Visibility::Inherited
+        } else {
+            b::Visibility::Hidden
+        };
+    }
+    Ok(())
+}
 
-        projection.scale = window.scale_factor() / (h_scale.min(v_scale).max(1) as f32);
+/// Sets the intensity of the [`HeatHazeMaterial`] from [`Fever`].
+pub(crate) fn update_heat_haze_system(
+    time: b::Res<b::Time>,
+    quantities: Quantities,
+    settings: b::Res<Settings>,
+    canvas: b::Single<&MeshMaterial2d<HeatHazeMaterial>, b::With<Canvas>>,
+    mut materials: b::ResMut<b::Assets<HeatHazeMaterial>>,
+) -> b::Result {
+    let fever = quantities.get::<Fever>()?;
+
+    // No distortion at all until fever is fairly high, then ramp up toward the loss threshold.
+    let intensity = if settings.reduced_motion || settings.low_spec {
+        0.0
+    } else {
+        ((fever.effective_value() - 0.6) / (Fever::LOSE_THRESHOLD - 0.6))
+            .clamp(0.0, 1.0)
+            .powi(2)
+    };
+
+    // Avoid touching the asset (and thus re-uploading it) when nothing would change.
+    let Some(material) = materials.get(&canvas.0) else {
+        return Ok(());
+    };
+    if intensity == 0.0 && material.params.x == 0.0 {
+        return Ok(());
+    }
+
+    if let Some(material) = materials.get_mut(&canvas.0) {
+        material.params.x = intensity;
+        // wrap time to keep precision in the shader
+        material.params.y = time.elapsed_secs_wrapped();
+    }
+    Ok(())
+}
+
+/// Moves UI text to land exactly on canvas pixels.
+///
+/// Even with [`bevy::text::FontSmoothing::None`], text whose corner falls on a fractional pixel
+/// position (e.g. centered text with an odd width) is resampled and looks blurry.
+/// This runs after transform propagation and adjusts the [`b::GlobalTransform`] directly.
+///
+/// Propagation only rewrites the [`b::GlobalTransform`] when something moved, so the snap is
+/// always computed afresh from the [`b::Transform`], not from the previous frame’s snapped
+/// position. Text whose parent is also text is not snapped.
+#[allow(
+    clippy::type_complexity,
+    reason = "snapping needs each text's transform, parent, anchor, and layout"
+)]
+pub(crate) fn snap_ui_text_to_pixels_system(
+    texts: b::Query<
+        (
+            &mut b::GlobalTransform,
+            &b::Transform,
+            Option<&b::ChildOf>,
+            &RenderLayers,
+            &bevy::sprite::Anchor,
+            &bevy::text::TextLayoutInfo,
+        ),
+        b::With<b::Text2d>,
+    >,
+    parents: b::Query<&b::GlobalTransform, b::Without<b::Text2d>>,
+) {
+    for (mut global_transform, transform, child_of, layers, anchor, layout) in texts {
+        if !layers.intersects(&UI_LAYERS) {
+            continue;
+        }
+
+        let unsnapped = match child_of {
+            None => b::GlobalTransform::from(*transform),
+            Some(child_of) => match parents.get(child_of.parent()) {
+                Ok(parent) => parent.mul_transform(*transform),
+                Err(_) => continue,
+            },
+        };
+
+        // This is where `Text2d` will put the top left corner of the text.
+        let top_left_offset =
+            (bevy::sprite::Anchor::TOP_LEFT.as_vec() - anchor.as_vec()) * layout.size;
+        let mut affine = unsnapped.affine();
+        let corner = affine.transform_point3(top_left_offset.extend(0.0)).xy();
+        affine.translation += b::Vec3A::from((corner.round() - corner).extend(0.0));
+        global_transform.set_if_neq(b::GlobalTransform::from(affine));
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Bullet trails
+
+/// Bullets moving at least this fast leave a trail of fading copies of themselves,
+/// so that they do not seem to teleport.
+const TRAIL_MIN_SPEED: f32 = 600.0;
+
+/// Maximum number of [`TrailImage`]s one bullet leaves.
+const MAX_TRAIL_IMAGES: u8 = 6;
+
+/// How long each [`TrailImage`] lasts.
+const TRAIL_IMAGE_LIFETIME: f32 = 0.06;
+
+/// Counts the [`TrailImage`]s a bullet has left so far.
+#[derive(Debug, Default, b::Component)]
+pub(crate) struct Trail {
+    images: u8,
+}
+
+/// Fading copy of a bullet sprite, left behind by [`spawn_trail_images_system`].
+#[derive(Debug, b::Component)]
+pub(crate) struct TrailImage;
+
+/// Leaves a [`TrailImage`] at the current position of each fast, visible bullet,
+/// unless [`Settings::reduced_motion`] or [`Settings::low_spec`] is set
+/// or there are already too many projectiles.
+#[allow(
+    clippy::type_complexity,
+    reason = "trails copy the sprite and motion of each projectile"
+)]
+pub(crate) fn spawn_trail_images_system(
+    mut commands: b::Commands,
+    settings: b::Res<Settings>,
+    ledger: b::Res<ProjectileLedger>,
+    bullets: b::Query<
+        (
+            b::Entity,
+            &b::Transform,
+            &b::Sprite,
+            &b::Visibility,
+            &p::LinearVelocity,
+            Option<&mut Trail>,
+        ),
+        b::With<Bullet>,
+    >,
+) {
+    if settings.reduced_motion || settings.low_spec || ledger.is_full(&settings) {
+        return;
+    }
+
+    for (entity, transform, sprite, visibility, velocity, trail) in bullets {
+        if *visibility == b::Visibility::Hidden || velocity.length() < TRAIL_MIN_SPEED {
+            continue;
+        }
+        match trail {
+            None => {
+                commands.entity(entity).insert(Trail { images: 1 });
+            }
+            Some(mut trail) if trail.images < MAX_TRAIL_IMAGES => trail.images += 1,
+            Some(_) => continue,
+        }
+        commands.spawn((
+            TrailImage,
+            CosmeticProjectile,
+            b::Sprite {
+                image: sprite.image.clone(),
+                custom_size: sprite.custom_size,
+                ..default()
+            },
+            *transform,
+            Lifetime(TRAIL_IMAGE_LIFETIME),
+            PLAYFIELD_LAYERS,
+        ));
+    }
+}
+
+/// Fades [`TrailImage`]s out over their [`Lifetime`].
+pub(crate) fn fade_trail_images_system(
+    query: b::Query<(&mut b::Sprite, &Lifetime), b::With<TrailImage>>,
+) {
+    for (mut sprite, &Lifetime(remaining)) in query {
+        sprite
+            .color
+            .set_alpha(0.5 * (remaining / TRAIL_IMAGE_LIFETIME).clamp(0.0, 1.0));
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Camera zoom
+
+/// Zoom effects applied to the [`PlayfieldCamera`] by [`update_camera_zoom_system`].
+#[derive(Debug, b::Resource)]
+pub(crate) struct CameraZoom {
+    /// Brief zoom-in which decays to zero; see [`Self::punch()`].
+    punch: f32,
+    /// Gradual zoom-in as the player approaches a win.
+    push_in: f32,
+    /// Zoom-in towards [`Self::kill_cam_target`]; see [`Self::kill_cam()`].
+    kill_cam: f32,
+    /// Zoom [`Self::kill_cam`] is approaching, and where the camera should look meanwhile.
+    /// Cleared if not renewed within [`KILL_CAM_HOLD_TIME`].
+    kill_cam_target: Option<(f32, b::Vec2)>,
+    /// Time since [`Self::kill_cam_target`] was last set.
+    kill_cam_age: f32,
+    /// Point on which [`Self::kill_cam`] zooms in, kept after the target is cleared so that
+    /// the camera can pull back the way it came.
+    focus: b::Vec2,
+    /// Projection scale currently applied to the camera, after snapping.
+    scale: f32,
+    /// Camera position currently applied, after snapping and clamping.
+    offset: b::Vec2,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self {
+            punch: 0.0,
+            push_in: 0.0,
+            kill_cam: 0.0,
+            kill_cam_target: None,
+            kill_cam_age: 0.0,
+            focus: b::Vec2::ZERO,
+            scale: 1.0,
+            offset: b::Vec2::ZERO,
+        }
+    }
+}
+
+impl CameraZoom {
+    /// Zooms in briefly by at least the given fraction of the playfield.
+    pub fn punch(&mut self, amount: f32) {
+        self.punch = self.punch.max(amount);
+    }
+
+    /// Zooms in by the given fraction of the playfield, centered on `position` as far as the
+    /// playfield allows. Must be called every tick to keep the zoom; it eases back out
+    /// once calls stop.
+    pub fn kill_cam(&mut self, position: b::Vec2, amount: f32) {
+        self.kill_cam_target = Some((amount, position));
+        self.kill_cam_age = 0.0;
+    }
+}
+
+/// Game time after the last [`CameraZoom::kill_cam()`] call for which the kill-cam is kept.
+/// Longer than a tick, since the zoom is updated every frame and a frame may have no ticks.
+const KILL_CAM_HOLD_TIME: f32 = 0.1;
+
+/// Number of enemies which must be killed in a single tick to cause a zoom punch.
+const BIG_KILL_COUNT: usize = 3;
+
+/// Punches in the camera when many enemies are killed at once.
+pub(crate) fn punch_on_big_kills_system(
+    mut run_events: b::MessageReader<RunEvent>,
+    mut zoom: b::ResMut<CameraZoom>,
+) {
+    let kills = run_events
+        .read()
+        .filter(|event| matches!(event, RunEvent::EnemyKilled))
+        .count();
+    if kills >= BIG_KILL_COUNT {
+        zoom.punch(0.04);
+    }
+}
+
+/// Combines the zoom effects and applies them to the [`PlayfieldCamera`].
+///
+/// The zoom is snapped so that the visible width of the playfield is an even number of canvas
+/// pixels. Since the camera is centered, or moved off center only by whole pixels for a kill-cam,
+/// the edges of the view then stay on pixel boundaries, and the pixel grid of the canvas itself
+/// is never scaled.
+pub(crate) fn update_camera_zoom_system(
+    time: b::Res<b::Time>,
+    settings: b::Res<Settings>,
+    game_mode: b::Res<GameMode>,
+    quantities: Quantities,
+    mut zoom: b::ResMut<CameraZoom>,
+    camera: b::Single<(&mut b::Projection, &mut b::Transform), b::With<PlayfieldCamera>>,
+) -> b::Result {
+    let (mut projection, mut transform) = camera.into_inner();
+    let fervor = quantities.get::<Fervor>()?;
+
+    // Fervor at which the push-in starts.
+    const PUSH_IN_START: f32 = Fervor::WIN_THRESHOLD - 0.1;
+    const MAX_PUSH_IN: f32 = 0.06;
+
+    let dt = time.delta_secs();
+    zoom.punch = (zoom.punch - dt * 0.2).max(0.0);
+    // The push-in builds up to a win, so it would only mislead in a mode that cannot be won.
+    let push_in_target = if game_mode.can_win() {
+        ((fervor.base() - PUSH_IN_START) / (Fervor::WIN_THRESHOLD - PUSH_IN_START)).clamp(0.0, 1.0)
+            * MAX_PUSH_IN
+    } else {
+        0.0
+    };
+    zoom.push_in += (push_in_target - zoom.push_in) * (dt * 2.0).min(1.0);
+
+    zoom.kill_cam_age += dt;
+    if zoom.kill_cam_age > KILL_CAM_HOLD_TIME {
+        zoom.kill_cam_target = None;
+    }
+    let kill_cam_target = match zoom.kill_cam_target {
+        Some((amount, focus)) => {
+            zoom.focus = focus;
+            amount
+        }
+        None => 0.0,
+    };
+    zoom.kill_cam += (kill_cam_target - zoom.kill_cam) * (dt * 6.0).min(1.0);
+
+    let (amount, kill_cam) = if settings.reduced_motion {
+        (0.0, 0.0)
+    } else {
+        (zoom.punch + zoom.push_in + zoom.kill_cam, zoom.kill_cam)
+    };
+    let width = PLAYFIELD_SIZE.x as f32;
+    let scale = ((width * (1.0 - amount) / 2.0).round() * 2.0) / width;
+
+    // Move towards the focus only as far as keeps the view within the playfield.
+    let max_offset = PLAYFIELD_SIZE.as_vec2() * (1.0 - scale) / 2.0;
+    let offset = (zoom.focus * (kill_cam / amount.max(f32::EPSILON)))
+        .clamp(-max_offset, max_offset)
+        .round();
+
+    let b::Projection::Orthographic(projection) = &mut *projection else {
+        return Err(b::BevyError::from("projection not orthographic"));
+    };
+    if projection.scale != scale {
+        projection.scale = scale;
+    }
+    if transform.translation.xy() != offset {
+        transform.translation = offset.extend(transform.translation.z);
     }
+    zoom.scale = scale;
+    zoom.offset = offset;
     Ok(())
 }
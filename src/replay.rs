@@ -0,0 +1,387 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::prelude as b;
+use bevy_enhanced_input::prelude as bei;
+
+use crate::game_rng::{self, ChosenSeed, GameRng};
+use crate::persistence::{self, DataDirectory};
+use crate::settings::Settings;
+use crate::{Bomb, GameState, Gun, Move, Player, Shoot};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Records the player’s input during each run, and writes it as a [`Replay`] to
+/// [`LAST_REPLAY_FILE`] in the data directory when the run ends, so that it can be shared.
+///
+/// If [`ReplayPlugin::import`] is set, by the `--replay <file>` option, that replay is imported,
+/// and the next run uses its seed. Playing back the recorded input is not implemented yet.
+pub(crate) struct ReplayPlugin {
+    /// Replay file to import.
+    pub import: Option<PathBuf>,
+}
+
+impl b::Plugin for ReplayPlugin {
+    fn build(&self, app: &mut b::App) {
+        if let Some(path) = &self.import {
+            match Replay::read(path) {
+                Ok(replay) => {
+                    b::info!(
+                        "imported replay {}; the next run uses its seed {}",
+                        path.display(),
+                        game_rng::format_seed(replay.seed)
+                    );
+                    app.insert_resource(ChosenSeed(replay.seed));
+                }
+                Err(error) => b::error!("failed to import replay {}: {error}", path.display()),
+            }
+        }
+
+        app.init_resource::<ReplayRecording>()
+            .add_systems(
+                b::OnEnter(GameState::Playing),
+                (
+                    // restarting finishes the old run, if not already finished
+                    finish_run
+                        .before(crate::despawn_game)
+                        .run_if(b::resource_exists::<crate::RestartRun>),
+                    start_run.after(crate::start_new_game),
+                ),
+            )
+            .add_systems(
+                b::FixedUpdate,
+                record_system
+                    .after(crate::apply_movement)
+                    .run_if(crate::simulation_running),
+            )
+            .add_systems(b::OnEnter(GameState::WinOrGameOver), finish_run)
+            // in case the run was abandoned
+            .add_systems(b::OnEnter(GameState::Menu), finish_run);
+    }
+}
+
+/// File in the data directory to which the replay of the most recent run is written.
+const LAST_REPLAY_FILE: &str = "last-run.replay.json";
+
+/// Version of the [`Replay`] file format. Increment this whenever the format changes.
+const FORMAT_VERSION: u32 = 1;
+
+/// Version of the game, which is recorded in replays.
+const GAME_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Everything needed to play a run over again: its seed, settings, and the player’s input.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct Replay {
+    /// [`FORMAT_VERSION`] of the file.
+    pub format_version: u32,
+
+    /// Version of the game which recorded the replay.
+    ///
+    /// Gameplay tuning is in the game’s code and assets, and changes between versions without
+    /// any change to this format, so a replay only plays back the same on the same version.
+    pub game_version: String,
+
+    /// Seed of the run’s [`GameRng`].
+    pub seed: u64,
+
+    /// Settings at the start of the run. Some, such as the tick rate and the modifiers,
+    /// affect gameplay.
+    pub settings: Settings,
+
+    /// The player’s input on each fixed-update tick of the run, with runs of identical ticks
+    /// combined.
+    pub inputs: Vec<InputSpan>,
+}
+
+/// Input which was the same for [`InputSpan::ticks`] consecutive ticks.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct InputSpan {
+    pub ticks: u32,
+    pub input: TickInput,
+}
+
+/// The player’s input on one fixed-update tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct TickInput {
+    /// Value of the [`Move`] action.
+    pub movement: [f32; 2],
+    /// [`Gun::aim`] of the player’s gun, which the [`Aim`](crate::Aim) action or the mouse
+    /// may have changed since the last tick.
+    pub aim: f32,
+    /// Value of the [`Shoot`] action.
+    pub shoot: bool,
+    /// Value of the [`Bomb`] action.
+    pub bomb: bool,
+}
+
+/// Why a [`Replay`] could not be imported.
+#[derive(Debug)]
+pub(crate) enum ReplayError {
+    /// The file could not be read, or is not a replay.
+    Unreadable(String),
+    /// The replay was recorded by a different version of the game or in a different format.
+    IncompatibleVersion {
+        format_version: u32,
+        game_version: String,
+    },
+    /// The replay is in the current format, but contains values that no run could produce.
+    Invalid(String),
+}
+
+/// Replay being recorded for the run in progress, if any.
+#[derive(Debug, Default, b::Resource)]
+struct ReplayRecording {
+    run: Option<Replay>,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl Replay {
+    fn new(seed: u64, settings: Settings) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            game_version: GAME_VERSION.to_owned(),
+            seed,
+            settings,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Appends the input of one more tick.
+    fn push(&mut self, input: TickInput) {
+        match self.inputs.last_mut() {
+            Some(span) if span.input == input => span.ticks += 1,
+            _ => self.inputs.push(InputSpan { ticks: 1, input }),
+        }
+    }
+
+    /// Reads and validates a replay file.
+    pub(crate) fn read(path: &Path) -> Result<Self, ReplayError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| ReplayError::Unreadable(error.to_string()))?;
+        Self::from_json(&text)
+    }
+
+    /// Parses and validates a replay in the format written by [`ReplayPlugin`].
+    pub(crate) fn from_json(text: &str) -> Result<Self, ReplayError> {
+        // Check the version before anything else, since other versions may have other fields.
+        #[derive(serde::Deserialize)]
+        struct Header {
+            format_version: u32,
+            game_version: String,
+        }
+        let header: Header = serde_json::from_str(text)
+            .map_err(|error| ReplayError::Unreadable(error.to_string()))?;
+        if header.format_version != FORMAT_VERSION || header.game_version != GAME_VERSION {
+            return Err(ReplayError::IncompatibleVersion {
+                format_version: header.format_version,
+                game_version: header.game_version,
+            });
+        }
+
+        let replay: Self = serde_json::from_str(text)
+            .map_err(|error| ReplayError::Unreadable(error.to_string()))?;
+        replay.validate()?;
+        Ok(replay)
+    }
+
+    fn validate(&self) -> Result<(), ReplayError> {
+        let tick_rate = self.settings.fixed_timestep_hz;
+        if !(tick_rate.is_finite() && tick_rate > 0.0) {
+            return Err(ReplayError::Invalid(format!(
+                "tick rate must be positive, not {tick_rate}"
+            )));
+        }
+        for (index, &InputSpan { ticks, input }) in self.inputs.iter().enumerate() {
+            if ticks == 0 {
+                return Err(ReplayError::Invalid(format!(
+                    "input {index} lasts for zero ticks"
+                )));
+            }
+            if !input.movement.iter().all(|c| (-1.0..=1.0).contains(c)) {
+                return Err(ReplayError::Invalid(format!(
+                    "input {index} has movement {:?} outside -1 to 1",
+                    input.movement
+                )));
+            }
+            if !input.aim.is_finite() {
+                return Err(ReplayError::Invalid(format!(
+                    "input {index} has aim {}",
+                    input.aim
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Unreadable(reason) => write!(f, "not a readable replay: {reason}"),
+            ReplayError::IncompatibleVersion {
+                format_version,
+                game_version,
+            } => write!(
+                f,
+                "replay incompatible version: it was recorded by version {game_version} \
+                    in format {format_version}, but this is version {GAME_VERSION} \
+                    in format {FORMAT_VERSION}"
+            ),
+            ReplayError::Invalid(reason) => write!(f, "invalid replay: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn path_from_args(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == "--replay" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+fn start_run(
+    mut recording: b::ResMut<ReplayRecording>,
+    game_rng: b::Res<GameRng>,
+    settings: b::Res<Settings>,
+) {
+    // also entered when unpausing, which continues the same run
+    if recording.run.is_none() {
+        recording.run = Some(Replay::new(game_rng.seed(), settings.clone()));
+    }
+}
+
+fn record_system(
+    mut recording: b::ResMut<ReplayRecording>,
+    movement: b::Single<&bei::Action<Move>, b::With<bei::ActionOf<Player>>>,
+    shoot: b::Single<&bei::Action<Shoot>, b::With<bei::ActionOf<Player>>>,
+    bomb: b::Single<&bei::Action<Bomb>, b::With<bei::ActionOf<Player>>>,
+    gun: b::Single<&Gun, b::With<Player>>,
+) {
+    let Some(run) = &mut recording.run else {
+        return;
+    };
+    let movement: b::Vec2 = ***movement;
+    run.push(TickInput {
+        movement: movement.to_array(),
+        aim: gun.aim,
+        shoot: ***shoot,
+        bomb: ***bomb,
+    });
+}
+
+fn finish_run(mut recording: b::ResMut<ReplayRecording>, data_directory: b::Res<DataDirectory>) {
+    let Some(run) = recording.run.take() else {
+        return;
+    };
+    persistence::save(&data_directory, LAST_REPLAY_FILE, &run);
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay_with_inputs(inputs: &[TickInput]) -> Replay {
+        let mut replay = Replay::new(0x1234, Settings::default());
+        for &input in inputs {
+            replay.push(input);
+        }
+        replay
+    }
+
+    const SHOOTING: TickInput = TickInput {
+        movement: [0.0, 1.0],
+        aim: 0.0,
+        shoot: true,
+        bomb: false,
+    };
+
+    #[test]
+    fn push_combines_identical_ticks() {
+        let replay = replay_with_inputs(&[SHOOTING, SHOOTING, TickInput::default(), SHOOTING]);
+        assert_eq!(
+            replay.inputs,
+            vec![
+                InputSpan {
+                    ticks: 2,
+                    input: SHOOTING
+                },
+                InputSpan {
+                    ticks: 1,
+                    input: TickInput::default()
+                },
+                InputSpan {
+                    ticks: 1,
+                    input: SHOOTING
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = replay_with_inputs(&[SHOOTING, TickInput::default()]);
+        let text = serde_json::to_string(&replay).unwrap();
+        assert_eq!(Replay::from_json(&text).unwrap(), replay);
+    }
+
+    #[test]
+    fn other_game_version_is_incompatible() {
+        let mut replay = replay_with_inputs(&[SHOOTING]);
+        replay.game_version = "0.0.0-other".to_owned();
+        let text = serde_json::to_string(&replay).unwrap();
+        assert!(matches!(
+            Replay::from_json(&text),
+            Err(ReplayError::IncompatibleVersion { game_version, .. })
+                if game_version == "0.0.0-other"
+        ));
+    }
+
+    #[test]
+    fn future_format_is_incompatible_not_unreadable() {
+        let text = format!(
+            r#"{{"format_version": {}, "game_version": "{GAME_VERSION}", "frames": []}}"#,
+            FORMAT_VERSION + 1
+        );
+        assert!(matches!(
+            Replay::from_json(&text),
+            Err(ReplayError::IncompatibleVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn not_a_replay_is_unreadable() {
+        assert!(matches!(
+            Replay::from_json("{}"),
+            Err(ReplayError::Unreadable(_))
+        ));
+    }
+
+    #[test]
+    fn impossible_input_is_invalid() {
+        let mut replay = replay_with_inputs(&[SHOOTING]);
+        replay.inputs[0].ticks = 0;
+        let text = serde_json::to_string(&replay).unwrap();
+        assert!(matches!(
+            Replay::from_json(&text),
+            Err(ReplayError::Invalid(_))
+        ));
+
+        let mut replay = replay_with_inputs(&[SHOOTING]);
+        replay.inputs[0].input.movement = [0.0, 2.0];
+        let text = serde_json::to_string(&replay).unwrap();
+        assert!(matches!(
+            Replay::from_json(&text),
+            Err(ReplayError::Invalid(_))
+        ));
+    }
+}
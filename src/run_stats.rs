@@ -0,0 +1,169 @@
+use std::fmt::Write as _;
+
+use bevy::ecs::change_detection::DetectChangesMut as _;
+use bevy::ecs::reflect::ReflectResource;
+use bevy::math::{vec2, vec3};
+use bevy::prelude as b;
+
+use crate::cutscene::Cutscene;
+use crate::pickup::PickupSpawnType;
+use crate::quantity::{Fervor, Quantities};
+use crate::settings::{Settings, update_hud_font_size};
+use crate::{GameAssets, GameState, PLAYFIELD_RECT, Team, UI_LAYERS, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Statistics about the current run. Reset when a new game starts.
+#[derive(Debug, Default, b::Resource, b::Reflect)]
+#[reflect(Resource)]
+pub(crate) struct RunStats {
+    /// Game time spent in [`GameState::Playing`](crate::GameState::Playing).
+    pub elapsed: f32,
+    pub shots_fired: u32,
+    pub kills: u32,
+    pub damage_taken: u32,
+    /// Value of [`Self::elapsed`] when [`Fervor`] first reached each of [`SPLIT_THRESHOLDS`].
+    pub splits: [Option<f32>; SPLIT_THRESHOLDS.len()],
+}
+
+/// [`Fervor`] base values at which [`RunStats::splits`] are taken.
+/// The last one is winning.
+pub(crate) const SPLIT_THRESHOLDS: [f32; 4] = [0.25, 0.5, 0.75, Fervor::WIN_THRESHOLD];
+
+/// Message sent by gameplay systems when something happens that is worth counting.
+/// Changes to quantities are not included here, because [`QuantityDelta`] covers them.
+///
+/// [`QuantityDelta`]: crate::quantity::QuantityDelta
+#[derive(Clone, Copy, Debug, b::Message)]
+pub(crate) enum RunEvent {
+    /// A gun on the given team fired.
+    ShotFired(Team),
+    /// An enemy was killed by the player.
+    EnemyKilled,
+    /// The player was hurt.
+    PlayerDamaged,
+    /// The player collected a pickup.
+    PickupCollected(PickupSpawnType),
+    /// Fever reached its maximum and the player lost a life instead of the run.
+    LifeLost,
+}
+
+/// Text showing [`RunStats::elapsed`] and [`RunStats::splits`].
+/// Shown during play if [`Settings::speedrun_timer`] is set, and always once the run is over.
+#[derive(Debug, b::Component)]
+pub(crate) struct RunTimerText;
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn reset_run_stats(mut stats: b::ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+pub(crate) fn run_clock_system(time: b::Res<b::Time>, mut stats: b::ResMut<RunStats>) {
+    stats.elapsed += time.delta_secs();
+}
+
+pub(crate) fn count_run_events_system(
+    mut events: b::MessageReader<RunEvent>,
+    mut stats: b::ResMut<RunStats>,
+) {
+    for event in events.read() {
+        match *event {
+            RunEvent::ShotFired(Team::Player) => stats.shots_fired += 1,
+            RunEvent::ShotFired(Team::Enemy) => {}
+            RunEvent::EnemyKilled => stats.kills += 1,
+            RunEvent::PlayerDamaged => stats.damage_taken += 1,
+            RunEvent::PickupCollected(_) | RunEvent::LifeLost => {}
+        }
+    }
+}
+
+pub(crate) fn record_splits_system(
+    quantities: Quantities,
+    mut stats: b::ResMut<RunStats>,
+) -> b::Result {
+    let fervor = quantities.get::<Fervor>()?;
+
+    let RunStats {
+        elapsed, splits, ..
+    } = &mut *stats;
+    for (split, threshold) in splits.iter_mut().zip(SPLIT_THRESHOLDS) {
+        if split.is_none() && fervor.base() >= threshold {
+            *split = Some(*elapsed);
+        }
+    }
+    Ok(())
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn run_timer_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        RunTimerText,
+        b::Text2d::new(""),
+        assets.small_mono_font(),
+        b::TextLayout::new_with_justify(b::Justify::Left),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        bevy::sprite::Anchor::TOP_LEFT,
+        b::Transform::from_translation(vec3(
+            PLAYFIELD_RECT.min.x + 4.0,
+            PLAYFIELD_RECT.max.y - 4.0,
+            Zees::UiFront2.z(),
+        )),
+        b::Visibility::Hidden,
+        UI_LAYERS,
+    )
+}
+
+pub(crate) fn update_run_timer_text_system(
+    stats: b::Res<RunStats>,
+    assets: b::Res<GameAssets>,
+    settings: b::Res<Settings>,
+    state: b::Res<b::State<GameState>>,
+    cutscene: Option<b::Res<Cutscene>>,
+    text: b::Single<(&mut b::Text2d, &mut b::TextFont, &mut b::Visibility), b::With<RunTimerText>>,
+) {
+    let (mut text, font, mut visibility) = text.into_inner();
+
+    update_hud_font_size(&settings, &assets, font);
+
+    let visible = match *state.get() {
+        GameState::Playing | GameState::Paused => settings.speedrun_timer,
+        // results
+        GameState::WinOrGameOver => cutscene.is_none(),
+        GameState::AssetLoading | GameState::Menu | GameState::HighScores => false,
+    };
+    visibility.set_if_neq(if visible {
+        b::Visibility::Inherited
+    } else {
+        b::Visibility::Hidden
+    });
+    if !visible {
+        return;
+    }
+
+    let mut new_text = format_run_time(stats.elapsed);
+    for (split, threshold) in stats.splits.iter().zip(SPLIT_THRESHOLDS) {
+        if let Some(split) = split {
+            let percent = threshold * 100.0;
+            write!(new_text, "\n{percent:3.0}% {}", format_run_time(*split)).unwrap();
+        }
+    }
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
+
+/// Formats a duration in seconds as minutes, seconds, and hundredths, e.g. `2:05.37`.
+pub(crate) fn format_run_time(seconds: f32) -> String {
+    let hundredths = (seconds * 100.0) as u32;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
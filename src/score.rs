@@ -0,0 +1,281 @@
+use bevy::color::Alpha as _;
+use bevy::ecs::change_detection::DetectChangesMut as _;
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::math::{Vec2, vec2, vec3};
+use bevy::prelude as b;
+
+use crate::cutscene::Cutscene;
+use crate::quantity::Quantity;
+use crate::settings::{Settings, update_hud_font_size};
+use crate::{GameAssets, GameState, Lifetime, PLAYFIELD_LAYERS, PLAYFIELD_RECT, UI_LAYERS, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Points scored in the current run, by destroying enemies and completing
+/// [`Objectives`](crate::objectives::Objectives).
+#[derive(Debug, Default, b::Resource)]
+pub(crate) struct Score(pub u64);
+
+/// Points for destroying this enemy, before multipliers; see [`kill_points()`].
+#[derive(Debug, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct ScoreValue(pub u32);
+
+/// Text showing the [`Score`] during play and on the results screen.
+#[derive(Debug, b::Component)]
+pub(crate) struct ScoreText;
+
+/// Consecutive kills by the player, each within [`COMBO_WINDOW`] of the one before,
+/// which multiply the points and Fervor gained for each kill.
+#[derive(Debug, Default, b::Resource)]
+pub(crate) struct Combo {
+    kills: u32,
+    /// Game time remaining before the combo ends, unless another kill extends it.
+    remaining: f32,
+}
+
+/// Text showing the [`Combo`] multiplier, which fades as the combo runs out.
+#[derive(Debug, b::Component)]
+pub(crate) struct ComboText;
+
+/// Extra multiple of the points for a kill at full [`Fervor`](crate::Fervor).
+const FERVOR_MULTIPLIER: f32 = 3.0;
+
+/// Time for which the points for a kill are shown where the enemy was.
+const POPUP_LIFETIME: f32 = 0.6;
+
+/// Time after a kill within which another kill continues the [`Combo`].
+/// Much longer than the announcer’s streak window, so that it rewards sustained aggression
+/// rather than only multi-kills.
+const COMBO_WINDOW: f32 = 2.0;
+/// Increase in the [`Combo::multiplier()`] per kill after the first.
+const COMBO_STEP: f32 = 0.1;
+const COMBO_MAX_MULTIPLIER: f32 = 3.0;
+
+// -------------------------------------------------------------------------------------------------
+
+impl Combo {
+    /// Counts a kill, continuing the combo or starting a new one.
+    pub fn add_kill(&mut self) {
+        self.kills += 1;
+        self.remaining = COMBO_WINDOW;
+    }
+
+    /// Multiplier for points and Fervor from a kill which is part of this combo.
+    pub fn multiplier(&self) -> f32 {
+        (1.0 + COMBO_STEP * self.kills.saturating_sub(1) as f32).min(COMBO_MAX_MULTIPLIER)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Points for the player destroying an enemy worth `value`.
+///
+/// Kills count for more with higher [`Coherence`](crate::Coherence) (up to double),
+/// [`Fervor`](crate::Fervor) (up to [`FERVOR_MULTIPLIER`] more),
+/// and a longer [`Combo`] (up to [`COMBO_MAX_MULTIPLIER`] times).
+pub(crate) fn kill_points(
+    value: u32,
+    coherence: &Quantity,
+    fervor: &Quantity,
+    combo: &Combo,
+) -> u32 {
+    let multiplier = (1.0 + coherence.effective_value())
+        * (1.0 + FERVOR_MULTIPLIER * fervor.effective_value())
+        * combo.multiplier();
+    (value as f32 * multiplier).round() as u32
+}
+
+/// Briefly shows the points scored for a kill at the position of the kill.
+pub(crate) fn points_popup_bundle(
+    assets: &GameAssets,
+    position: Vec2,
+    points: u32,
+) -> impl b::Bundle {
+    (
+        b::Text2d::new(format!("{points}")),
+        assets.small_mono_font(),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        b::Transform::from_translation(position.extend(Zees::AbovePlayer.z())),
+        Lifetime(POPUP_LIFETIME),
+        PLAYFIELD_LAYERS,
+    )
+}
+
+pub(crate) fn reset_score(mut score: b::ResMut<Score>, mut combo: b::ResMut<Combo>) {
+    *score = Score::default();
+    *combo = Combo::default();
+}
+
+/// Ends the [`Combo`] once [`COMBO_WINDOW`] has passed without a kill.
+pub(crate) fn combo_decay_system(time: b::Res<b::Time>, mut combo: b::ResMut<Combo>) {
+    if combo.kills == 0 {
+        return;
+    }
+    combo.remaining -= time.delta_secs();
+    if combo.remaining <= 0.0 {
+        *combo = Combo::default();
+    }
+}
+
+pub(crate) fn score_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        ScoreText,
+        b::Text2d::new(""),
+        assets.small_mono_font(),
+        b::TextLayout::new_with_justify(b::Justify::Center),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        bevy::sprite::Anchor::TOP_CENTER,
+        b::Transform::from_translation(vec3(
+            PLAYFIELD_RECT.center().x,
+            PLAYFIELD_RECT.max.y - 4.0,
+            Zees::UiFront2.z(),
+        )),
+        b::Visibility::Hidden,
+        UI_LAYERS,
+    )
+}
+
+pub(crate) fn update_score_text_system(
+    score: b::Res<Score>,
+    assets: b::Res<GameAssets>,
+    settings: b::Res<Settings>,
+    state: b::Res<b::State<GameState>>,
+    cutscene: Option<b::Res<Cutscene>>,
+    text: b::Single<(&mut b::Text2d, &mut b::TextFont, &mut b::Visibility), b::With<ScoreText>>,
+) {
+    let (mut text, font, mut visibility) = text.into_inner();
+
+    update_hud_font_size(&settings, &assets, font);
+
+    let visible = match *state.get() {
+        GameState::Playing | GameState::Paused => true,
+        // results
+        GameState::WinOrGameOver => cutscene.is_none(),
+        GameState::AssetLoading | GameState::Menu | GameState::HighScores => false,
+    };
+    visibility.set_if_neq(if visible {
+        b::Visibility::Inherited
+    } else {
+        b::Visibility::Hidden
+    });
+
+    let new_text = format!("{:08}", score.0);
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
+
+pub(crate) fn combo_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        ComboText,
+        b::Text2d::new(""),
+        assets.small_mono_font(),
+        b::TextLayout::new_with_justify(b::Justify::Center),
+        b::TextShadow {
+            offset: vec2(1.0, 1.0),
+            color: b::Color::BLACK,
+        },
+        bevy::sprite::Anchor::TOP_CENTER,
+        b::Transform::from_translation(vec3(
+            PLAYFIELD_RECT.center().x,
+            PLAYFIELD_RECT.max.y - 14.0,
+            Zees::UiFront2.z(),
+        )),
+        b::Visibility::Hidden,
+        UI_LAYERS,
+    )
+}
+
+/// Shows the [`Combo`] multiplier below the score while a combo is going,
+/// fading out as the time to continue it runs out.
+pub(crate) fn update_combo_text_system(
+    combo: b::Res<Combo>,
+    state: b::Res<b::State<GameState>>,
+    text: b::Single<
+        (
+            &mut b::Text2d,
+            &mut b::TextColor,
+            &mut b::TextShadow,
+            &mut b::Visibility,
+        ),
+        b::With<ComboText>,
+    >,
+) {
+    let (mut text, mut color, mut shadow, mut visibility) = text.into_inner();
+
+    let visible = matches!(state.get(), GameState::Playing | GameState::Paused) && combo.kills >= 2;
+    visibility.set_if_neq(if visible {
+        b::Visibility::Inherited
+    } else {
+        b::Visibility::Hidden
+    });
+    if !visible {
+        return;
+    }
+
+    let new_text = format!("{} combo x{:.1}", combo.kills, combo.multiplier());
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+    let alpha = (combo.remaining / COMBO_WINDOW).clamp(0.0, 1.0);
+    color.set_if_neq(b::TextColor(b::Color::WHITE.with_alpha(alpha)));
+    shadow.color = b::Color::BLACK.with_alpha(alpha);
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combo_of(kills: u32) -> Combo {
+        Combo {
+            kills,
+            remaining: COMBO_WINDOW,
+        }
+    }
+
+    #[test]
+    fn kill_points_unmultiplied() {
+        let zero = Quantity::new(0.0);
+        assert_eq!(kill_points(100, &zero, &zero, &Combo::default()), 100);
+        // a combo starts with its first kill
+        assert_eq!(kill_points(100, &zero, &zero, &combo_of(1)), 100);
+    }
+
+    #[test]
+    fn kill_points_multipliers_combine() {
+        let zero = Quantity::new(0.0);
+        let full = Quantity::new(1.0);
+        assert_eq!(kill_points(100, &full, &zero, &Combo::default()), 200);
+        assert_eq!(kill_points(100, &zero, &full, &Combo::default()), 400);
+        assert_eq!(kill_points(100, &zero, &zero, &combo_of(3)), 120);
+        assert_eq!(kill_points(100, &full, &full, &combo_of(3)), 960);
+    }
+
+    #[test]
+    fn kill_points_combo_is_capped() {
+        let zero = Quantity::new(0.0);
+        assert_eq!(kill_points(100, &zero, &zero, &combo_of(1000)), 300);
+    }
+
+    #[test]
+    fn kill_points_rounds() {
+        let zero = Quantity::new(0.0);
+        assert_eq!(
+            kill_points(1, &Quantity::new(0.5), &zero, &Combo::default()),
+            2
+        );
+        assert_eq!(
+            kill_points(1, &Quantity::new(0.4), &zero, &Combo::default()),
+            1
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::math::vec3;
+#[cfg(not(target_family = "wasm"))]
+use bevy::platform::time::Instant;
+use bevy::prelude as b;
+use bevy::window::PresentMode;
+use bevy_enhanced_input::prelude as bei;
+
+use crate::game_speed::GameSpeed;
+use crate::persistence::{self, DataDirectory, FlushPersistentData};
+use crate::quantity::StartingQuantities;
+use crate::{
+    GameAssets, GameState, PLAYFIELD_RECT, UI_LAYERS, VisibleInState, VolumeDown, VolumeUp, Zees,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Player-adjustable settings which are not part of gameplay.
+///
+/// Settings are loaded from [`SETTINGS_FILE`] and then may be overridden for the current session
+/// from the command line:
+///
+/// * `--tick-rate <hz>` sets [`Settings::fixed_timestep_hz`].
+/// * `--no-vsync` clears [`Settings::vsync`].
+/// * `--frame-limit <fps>` sets [`Settings::frame_rate_limit`].
+/// * `--practice-speed <factor>` sets [`Settings::practice_speed`].
+/// * `--reduced-motion` sets [`Settings::reduced_motion`].
+/// * `--twin-stick` sets [`Settings::twin_stick`].
+/// * `--low-spec` sets [`Settings::low_spec`].
+/// * `--speedrun-timer` sets [`Settings::speedrun_timer`].
+/// * `--wrap-playfield` sets [`Settings::wrap_playfield`].
+/// * `--fever-pressure` sets [`Settings::fever_pressure`].
+/// * `--streamer-mode` sets [`Settings::streamer_mode`].
+/// * `--vertical-fit` sets [`Settings::vertical_fit`].
+/// * `--presence-title` sets [`Settings::presence_in_title`].
+/// * `--projectile-cap <count>` sets [`Settings::projectile_render_cap`].
+/// * `--hot-start` sets the starting Fever of [`Settings::starting_quantities`] to
+///   [`StartingQuantities::HOT_START_FEVER`].
+#[derive(Clone, Debug, PartialEq, b::Resource, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct Settings {
+    /// Rate at which [`b::FixedUpdate`] gameplay systems run.
+    ///
+    /// Gameplay is intended to be balanced the same regardless of this value;
+    /// higher values give smoother motion and more precise collisions at higher CPU cost.
+    pub fixed_timestep_hz: f64,
+
+    /// Whether to wait for the display’s vertical sync when presenting frames.
+    pub vsync: bool,
+
+    /// If set, frames are not rendered more often than this many per second.
+    /// Not supported on the web, where the browser paces frames.
+    pub frame_rate_limit: Option<f64>,
+
+    /// Speed of gameplay relative to normal, for practicing; see [`GameSpeed::practice`].
+    pub practice_speed: f32,
+
+    /// Whether to avoid purely decorative motion effects such as distortion.
+    pub reduced_motion: bool,
+
+    /// Whether the player aims with the right stick or mouse, independently of movement,
+    /// rather than always firing straight ahead. Takes effect on the next run.
+    pub twin_stick: bool,
+
+    /// Whether to reduce graphical effects for the sake of weak GPUs: no debris, sparkles,
+    /// bullet trails, or heat distortion; a sparser starfield; and a cap on live bullets.
+    pub low_spec: bool,
+
+    /// Whether to show the run clock and Fervor splits during play, not just at the end.
+    pub speedrun_timer: bool,
+
+    /// Experimental modifier: whether the player ship wraps around the left and right edges of
+    /// the playfield instead of stopping at them. Bullets do not wrap.
+    /// Takes effect on the next run.
+    pub wrap_playfield: bool,
+
+    /// Experimental hard-mode modifier: whether enemy bullets which narrowly miss the player ship
+    /// add a little temporary Fever, more so the higher Fever already is.
+    pub fever_pressure: bool,
+
+    /// Whether to arrange the display for streaming or recording: the run’s seed is not shown,
+    /// so viewers cannot replay or look up the run, and the score and run clock are enlarged
+    /// to stay legible after video compression.
+    pub streamer_mode: bool,
+
+    /// Whether to scale the display to fit the window’s height and only the playfield’s width,
+    /// cropping the side panels if necessary, rather than fitting the whole screen.
+    /// Gives a larger playfield in narrow or portrait windows.
+    pub vertical_fit: bool,
+
+    /// Whether the window title shows what the player is doing — the state of the run in
+    /// progress and the session’s wins and losses — for stream overlays and capture tools.
+    pub presence_in_title: bool,
+
+    /// Most bullets and bullet-like effects to draw at once. Beyond this, the oldest debris,
+    /// sparks, and bullet trails fade out early; bullets themselves are always drawn.
+    pub projectile_render_cap: usize,
+
+    /// Values of Coherence, Fever, and Fervor at the start of each run.
+    /// Changing these from the defaults makes for a different mode of play.
+    pub starting_quantities: StartingQuantities,
+
+    /// Loudness of all sound, from 0 (silent) to 1 (full).
+    /// Adjusted in game by the [`VolumeUp`] and [`VolumeDown`] actions.
+    pub volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fixed_timestep_hz: 64.0,
+            vsync: true,
+            frame_rate_limit: None,
+            practice_speed: 1.0,
+            reduced_motion: false,
+            twin_stick: false,
+            low_spec: false,
+            speedrun_timer: false,
+            wrap_playfield: false,
+            fever_pressure: false,
+            streamer_mode: false,
+            vertical_fit: false,
+            presence_in_title: false,
+            projectile_render_cap: 1500,
+            starting_quantities: StartingQuantities::default(),
+            volume: 1.0,
+        }
+    }
+}
+
+/// The settings as they are stored on disk, without command-line overrides.
+/// A future settings menu should change both this and [`Settings`].
+#[derive(Clone, Debug, b::Resource)]
+pub(crate) struct StoredSettings(pub Settings);
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Font size of the score, run clock, and lives when [`Settings::streamer_mode`] is set;
+/// a whole multiple of the normal size so that the pixel fonts stay crisp.
+const STREAMER_FONT_SIZE: f32 = 16.0;
+
+/// Sets the font size of a HUD text which is enlarged in [`Settings::streamer_mode`],
+/// without triggering change detection if it is already right.
+pub(crate) fn update_hud_font_size(
+    settings: &Settings,
+    assets: &GameAssets,
+    mut font: b::Mut<'_, b::TextFont>,
+) {
+    let font_size = if settings.streamer_mode {
+        STREAMER_FONT_SIZE
+    } else {
+        assets.small_mono_font().font_size
+    };
+    if font.font_size != font_size {
+        font.font_size = font_size;
+    }
+}
+
+/// Amount by which each press of [`VolumeUp`] or [`VolumeDown`] changes [`Settings::volume`].
+const VOLUME_STEP: f32 = 0.1;
+
+/// Text showing [`Settings::volume`] while paused.
+#[derive(Debug, b::Component)]
+pub(crate) struct VolumeText;
+
+pub(crate) struct SettingsPlugin {
+    /// Command-line arguments, to override the stored settings for this session.
+    pub args: Vec<String>,
+}
+
+impl b::Plugin for SettingsPlugin {
+    fn build(&self, app: &mut b::App) {
+        let stored = persistence::load::<Settings>(app.world().resource(), SETTINGS_FILE)
+            .unwrap_or_default();
+        app.insert_resource(stored.clone().with_args(self.args.iter().cloned()))
+            .insert_resource(StoredSettings(stored))
+            .add_observer(save_settings_observer)
+            .add_observer(volume_up_observer)
+            .add_observer(volume_down_observer)
+            .add_systems(
+                b::Update,
+                (
+                    apply_settings_system.run_if(b::resource_changed::<Settings>),
+                    update_volume_text_system.run_if(b::in_state(GameState::Paused)),
+                ),
+            );
+
+        #[cfg(not(target_family = "wasm"))]
+        app.add_systems(b::Last, frame_limiter_system);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl Settings {
+    fn with_args(mut self, mut args: impl Iterator<Item = String>) -> Self {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--tick-rate" => {
+                    if let Some(hz) = parse_positive(args.next(), &arg) {
+                        self.fixed_timestep_hz = hz;
+                    }
+                }
+                "--no-vsync" => self.vsync = false,
+                "--reduced-motion" => self.reduced_motion = true,
+                "--twin-stick" => self.twin_stick = true,
+                "--low-spec" => self.low_spec = true,
+                "--speedrun-timer" => self.speedrun_timer = true,
+                "--wrap-playfield" => self.wrap_playfield = true,
+                "--fever-pressure" => self.fever_pressure = true,
+                "--streamer-mode" => self.streamer_mode = true,
+                "--vertical-fit" => self.vertical_fit = true,
+                "--presence-title" => self.presence_in_title = true,
+                "--projectile-cap" => {
+                    if let Some(count) = parse_positive(args.next(), &arg) {
+                        self.projectile_render_cap = count as usize;
+                    }
+                }
+                "--hot-start" => {
+                    self.starting_quantities.fever = StartingQuantities::HOT_START_FEVER;
+                }
+                "--frame-limit" => {
+                    if let Some(fps) = parse_positive(args.next(), &arg) {
+                        self.frame_rate_limit = Some(fps);
+                    }
+                }
+                "--practice-speed" => {
+                    if let Some(factor) = parse_positive(args.next(), &arg) {
+                        self.practice_speed = factor as f32;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+fn parse_positive(value: Option<String>, option_name: &str) -> Option<f64> {
+    match value.as_deref().map(str::parse::<f64>) {
+        Some(Ok(number)) if number > 0.0 && number.is_finite() => Some(number),
+        _ => {
+            b::warn!("ignoring {option_name}: expected a positive number, got {value:?}");
+            None
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+fn save_settings_observer(
+    _event: b::On<FlushPersistentData>,
+    data_directory: b::Res<DataDirectory>,
+    stored: b::Res<StoredSettings>,
+) {
+    persistence::save(&data_directory, SETTINGS_FILE, &stored.0);
+}
+
+fn volume_up_observer(
+    _event: b::On<bei::Start<VolumeUp>>,
+    settings: b::ResMut<Settings>,
+    stored: b::ResMut<StoredSettings>,
+) {
+    change_volume(settings, stored, VOLUME_STEP);
+}
+
+fn volume_down_observer(
+    _event: b::On<bei::Start<VolumeDown>>,
+    settings: b::ResMut<Settings>,
+    stored: b::ResMut<StoredSettings>,
+) {
+    change_volume(settings, stored, -VOLUME_STEP);
+}
+
+/// Changes the volume both for now and for future sessions.
+fn change_volume(
+    mut settings: b::ResMut<Settings>,
+    mut stored: b::ResMut<StoredSettings>,
+    delta: f32,
+) {
+    // Round so that repeated steps land exactly on multiples of the step.
+    let volume = ((settings.volume + delta) / VOLUME_STEP).round() * VOLUME_STEP;
+    let volume = volume.clamp(0.0, 1.0);
+    settings.volume = volume;
+    stored.0.volume = volume;
+}
+
+fn apply_settings_system(
+    settings: b::Res<Settings>,
+    mut fixed_time: b::ResMut<b::Time<b::Fixed>>,
+    window: Option<b::Single<&mut b::Window, b::With<bevy::window::PrimaryWindow>>>,
+    mut game_speed: b::ResMut<GameSpeed>,
+    mut global_volume: b::ResMut<bevy::audio::GlobalVolume>,
+) {
+    fixed_time.set_timestep_hz(settings.fixed_timestep_hz);
+    game_speed.practice = settings.practice_speed;
+    // Sounds already playing keep their volume, but they are all short.
+    global_volume.volume = bevy::audio::Volume::Linear(settings.volume);
+
+    if let Some(mut window) = window {
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+}
+
+pub(crate) fn volume_text_bundle(assets: &GameAssets) -> impl b::Bundle {
+    (
+        VolumeText,
+        VisibleInState(GameState::Paused),
+        b::Text2d::new(""),
+        assets.small_prop_font(),
+        b::TextLayout::new_with_justify(b::Justify::Center),
+        bevy::sprite::Anchor::BOTTOM_CENTER,
+        b::Transform::from_translation(vec3(0.0, PLAYFIELD_RECT.min.y + 8.0, Zees::UiMiddle.z())),
+        UI_LAYERS,
+    )
+}
+
+fn update_volume_text_system(
+    settings: b::Res<Settings>,
+    mut text: b::Single<&mut b::Text2d, b::With<VolumeText>>,
+) {
+    let new_text = format!("Volume {:.0}%  (- / +)", settings.volume * 100.0);
+    if text.0 != new_text {
+        text.0 = new_text;
+    }
+}
+
+/// Sleeps at the end of the frame if needed to obey [`Settings::frame_rate_limit`].
+#[cfg(not(target_family = "wasm"))]
+fn frame_limiter_system(
+    settings: b::Res<Settings>,
+    mut previous_frame_end: b::Local<Option<Instant>>,
+) {
+    if let Some(limit) = settings.frame_rate_limit
+        && let Some(previous) = *previous_frame_end
+    {
+        let target = previous + std::time::Duration::from_secs_f64(limit.recip());
+        let now = Instant::now();
+        if target > now {
+            std::thread::sleep(target - now);
+        }
+    }
+
+    *previous_frame_end = Some(Instant::now());
+}
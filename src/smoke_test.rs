@@ -0,0 +1,401 @@
+use std::time::{Duration, Instant};
+
+use avian2d::prelude as p;
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::ecs::system::RunSystemOnce as _;
+use bevy::math::{Vec2, Vec3Swizzles as _};
+use bevy::prelude as b;
+use bevy::utils::default;
+
+use crate::boss::Boss;
+use crate::bullets_and_targets::{Bullet, fire_gun_system};
+use crate::enemy::EnemyShipAi;
+use crate::game_rng::ChosenSeed;
+use crate::lives::Lives;
+use crate::pickup::{Carried, PickupSpawnType, after_drop_bundle};
+use crate::prefab::Prefabs;
+use crate::quantity::{Quantity, StartingQuantities};
+use crate::run_stats::RunStats;
+use crate::score::Score;
+use crate::settings::Settings;
+use crate::{
+    DespawnNextTick, GameConfig, GameState, Gun, PLAYFIELD_RECT, Player, RecombobulatorPlugin, Team,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+// Tests which run the whole game, without a window or GPU.
+//
+// These are slow compared to other tests, but they are the only tests which cover how the
+// systems work together.
+
+/// Seed for the run, so that failures can be reproduced by playing it.
+const SEED: u64 = 0x5eed_5eed;
+
+/// Real time allowed for assets to load before giving up.
+const LOADING_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fixed-update ticks after which the run must have ended. Under the forced Fever of
+/// [`smoke_settings()`] it should end long before this.
+const MAX_TICKS: u32 = 64 * 180;
+
+/// More entities than this means something is failing to despawn.
+const MAX_ENTITIES: u32 = 5000;
+
+/// Number of fixed-update ticks that have run, counted by [`count_fixed_ticks()`].
+#[derive(Debug, Default, b::Resource)]
+struct FixedTicks(u32);
+
+/// Everything about a run which must not change once it has ended.
+#[derive(Debug, PartialEq)]
+struct RunRecord {
+    /// Base and temporary values of each quantity.
+    quantities: Vec<(b::Entity, f32, f32)>,
+    score: u64,
+    lives: u8,
+    elapsed: f32,
+    shots_fired: u32,
+    kills: u32,
+    damage_taken: u32,
+}
+
+/// Marks an entity spawned by a test for [`mark_probes_system`] to act on.
+#[derive(Debug, b::Component)]
+struct Probe;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Plays a short run with a bot for a player, and checks that nothing goes wrong along the way.
+#[test]
+fn bot_run_reaches_end_of_game() {
+    let mut app = headless_app(smoke_settings());
+    add_bot(&mut app);
+    load_to_menu(&mut app);
+    start_run(&mut app);
+    play_until_end(&mut app);
+
+    // Let the end-of-game effects play out.
+    for _ in 0..64 {
+        app.update();
+        check_invariants(&mut app);
+    }
+}
+
+/// After the run ends, bullets keep flying for show, but nothing changes the outcome or record
+/// of the run, and enemies stay still and hold their fire.
+#[test]
+fn run_is_frozen_after_it_ends() {
+    let mut app = headless_app(smoke_settings());
+    add_bot(&mut app);
+    load_to_menu(&mut app);
+    start_run(&mut app);
+    play_until_end(&mut app);
+
+    let record = RunRecord::capture(app.world_mut());
+    let mut enemy_bullets = count_enemy_bullets(app.world_mut());
+    for tick in 0..128 {
+        app.update();
+        check_invariants(&mut app);
+        let world = app.world_mut();
+
+        assert_eq!(
+            RunRecord::capture(world),
+            record,
+            "run changed {tick} ticks after it ended"
+        );
+
+        for (velocity, gun) in world
+            .query_filtered::<(&p::LinearVelocity, Option<&Gun>), b::Or<(
+                b::With<EnemyShipAi>,
+                b::With<Boss>,
+            )>>()
+            .iter(world)
+        {
+            assert_eq!(velocity.0, Vec2::ZERO, "enemy moved after the run ended");
+            assert!(
+                !gun.is_some_and(|gun| gun.trigger),
+                "enemy is firing after the run ended"
+            );
+        }
+
+        let new_enemy_bullets = count_enemy_bullets(world);
+        assert!(
+            new_enemy_bullets <= enemy_bullets,
+            "enemy bullets were fired after the run ended"
+        );
+        enemy_bullets = new_enemy_bullets;
+    }
+}
+
+/// An entity marked with [`DespawnNextTick`] during a tick, as bullets are when they hit,
+/// exists for the rest of that tick and is gone after the next one.
+#[test]
+fn despawn_next_tick_waits_for_next_tick() {
+    let mut app = headless_app(Settings::default());
+    load_to_menu(&mut app);
+    start_run(&mut app);
+    // as late in the tick as bullets are marked
+    app.add_systems(
+        b::FixedUpdate,
+        mark_probes_system.after(crate::bullets_and_targets::bullet_hit_system),
+    );
+
+    let probe = app.world_mut().spawn(Probe).id();
+    fixed_tick(&mut app);
+    assert!(
+        app.world().get::<DespawnNextTick>(probe).is_some(),
+        "probe was not marked"
+    );
+    fixed_tick(&mut app);
+    assert!(
+        app.world().get_entity(probe).is_err(),
+        "marked entity was not despawned on the next tick"
+    );
+}
+
+/// A [`Carried`] pickup touching the player is not collected, but is once it is dropped.
+#[test]
+fn carried_pickup_is_not_collected() {
+    let mut app = headless_app(Settings::default());
+    load_to_menu(&mut app);
+    start_run(&mut app);
+
+    let pickup = app
+        .world_mut()
+        .run_system_once::<_, b::Result<b::Entity>, _>(spawn_pickup_on_player)
+        .unwrap()
+        .unwrap();
+    for _ in 0..8 {
+        fixed_tick(&mut app);
+    }
+    assert!(
+        app.world().get_entity(pickup).is_ok(),
+        "carried pickup was collected"
+    );
+
+    // Check that the pickup was in reach, by dropping it.
+    app.world_mut().entity_mut(pickup).remove::<Carried>();
+    for _ in 0..8 {
+        fixed_tick(&mut app);
+    }
+    assert!(
+        app.world().get_entity(pickup).is_err(),
+        "dropped pickup was not collected"
+    );
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// The game with no window, no renderer, and time advancing by exactly one fixed-update tick
+/// per [`b::App::update()`].
+fn headless_app(settings: Settings) -> b::App {
+    let mut app = b::App::new();
+    let config = GameConfig::default().headless();
+    app.add_plugins(
+        config
+            .default_plugins()
+            // only one global logger may exist, and other tests may also want one
+            .disable::<bevy::log::LogPlugin>(),
+    )
+    .add_plugins(RecombobulatorPlugin::new(config));
+    app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+        Duration::from_secs_f64(settings.fixed_timestep_hz.recip()),
+    ))
+    .insert_resource(settings)
+    .init_resource::<FixedTicks>()
+    .add_systems(b::FixedFirst, count_fixed_ticks);
+
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Makes [`bot_system`] play the game.
+fn add_bot(app: &mut b::App) {
+    app.add_systems(
+        b::FixedUpdate,
+        bot_system
+            .after(crate::apply_movement)
+            .before(fire_gun_system)
+            .run_if(b::in_state(GameState::Playing)),
+    );
+}
+
+/// Loads assets and reaches the menu.
+fn load_to_menu(app: &mut b::App) {
+    let loading_start = Instant::now();
+    while *app.world().resource::<b::State<GameState>>().get() == GameState::AssetLoading {
+        assert!(
+            loading_start.elapsed() < LOADING_TIMEOUT,
+            "assets did not finish loading"
+        );
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(
+        *app.world().resource::<b::State<GameState>>().get(),
+        GameState::Menu
+    );
+}
+
+/// Starts a run with [`SEED`] from the menu.
+fn start_run(app: &mut b::App) {
+    app.insert_resource(ChosenSeed(SEED));
+    app.world_mut()
+        .resource_mut::<b::NextState<GameState>>()
+        .set(GameState::Playing);
+    app.update();
+    assert_eq!(
+        *app.world().resource::<b::State<GameState>>().get(),
+        GameState::Playing
+    );
+}
+
+/// Runs the game until the run is won or lost, checking invariants along the way.
+fn play_until_end(app: &mut b::App) {
+    let mut ticks = 0;
+    loop {
+        app.update();
+        ticks += 1;
+        check_invariants(app);
+
+        let state = app.world().resource::<b::State<GameState>>().get();
+        if *state == GameState::WinOrGameOver {
+            break;
+        }
+        assert_eq!(
+            *state,
+            GameState::Playing,
+            "unexpected state after {ticks} ticks"
+        );
+        assert!(
+            ticks < MAX_TICKS,
+            "run did not end within {MAX_TICKS} ticks"
+        );
+    }
+}
+
+/// Updates the app until exactly one more fixed-update tick has run.
+fn fixed_tick(app: &mut b::App) {
+    let before = app.world().resource::<FixedTicks>().0;
+    for _ in 0..4 {
+        app.update();
+        let after = app.world().resource::<FixedTicks>().0;
+        if after > before {
+            assert_eq!(after, before + 1, "more than one fixed-update tick ran");
+            return;
+        }
+    }
+    panic!("no fixed-update tick ran");
+}
+
+/// Settings which force Fever up, so that the bot, which does not dodge, loses quickly.
+fn smoke_settings() -> Settings {
+    Settings {
+        starting_quantities: StartingQuantities {
+            fever: StartingQuantities::HOT_START_FEVER,
+            ..default()
+        },
+        fever_pressure: true,
+        reduced_motion: true,
+        ..default()
+    }
+}
+
+/// Plays the game badly: sweeps from side to side near the bottom of the playfield, firing in
+/// bursts.
+///
+/// Moves the ship directly, rather than through input actions, since there are no input devices.
+fn bot_system(
+    time: b::Res<b::Time>,
+    mut elapsed: b::Local<f32>,
+    player: b::Single<(&mut b::Transform, &mut Gun), b::With<Player>>,
+) {
+    *elapsed += time.delta_secs();
+    let (mut transform, mut gun) = player.into_inner();
+
+    transform.translation.x =
+        PLAYFIELD_RECT.center().x + (PLAYFIELD_RECT.width() / 2.0 - 20.0) * (*elapsed * 0.7).sin();
+    transform.translation.y = PLAYFIELD_RECT.min.y + 40.0 + 20.0 * (*elapsed * 1.3).sin();
+
+    // two seconds firing, one second not
+    gun.trigger = elapsed.rem_euclid(3.0) < 2.0;
+}
+
+impl RunRecord {
+    fn capture(world: &mut b::World) -> Self {
+        let mut quantities: Vec<(b::Entity, f32, f32)> = world
+            .query::<(b::Entity, &Quantity)>()
+            .iter(world)
+            .map(|(entity, quantity)| (entity, quantity.base(), quantity.temporary_stack()))
+            .collect();
+        quantities.sort_by_key(|&(entity, ..)| entity);
+        let stats = world.resource::<RunStats>();
+        Self {
+            quantities,
+            score: world.resource::<Score>().0,
+            lives: world.resource::<Lives>().0,
+            elapsed: stats.elapsed,
+            shots_fired: stats.shots_fired,
+            kills: stats.kills,
+            damage_taken: stats.damage_taken,
+        }
+    }
+}
+
+fn count_enemy_bullets(world: &mut b::World) -> usize {
+    world
+        .query_filtered::<&Team, b::With<Bullet>>()
+        .iter(world)
+        .filter(|&&team| team == Team::Enemy)
+        .count()
+}
+
+/// Spawns a [`Carried`] pickup which has the physics of a dropped one, but no motion, on top of
+/// the player.
+fn spawn_pickup_on_player(
+    mut commands: b::Commands,
+    prefabs: Prefabs,
+    player: b::Single<&b::Transform, b::With<Player>>,
+) -> b::Result<b::Entity> {
+    let mut pickup = commands.spawn((
+        PickupSpawnType::Cool.pickup_bundle(prefabs.pickups()?, player.translation.xy()),
+        after_drop_bundle(Vec2::ZERO, &mut rand::rng()),
+    ));
+    pickup.insert((p::LinearVelocity(Vec2::ZERO), p::AngularVelocity(0.0)));
+    Ok(pickup.id())
+}
+
+fn count_fixed_ticks(mut ticks: b::ResMut<FixedTicks>) {
+    ticks.0 += 1;
+}
+
+fn mark_probes_system(
+    mut commands: b::Commands,
+    probes: b::Query<b::Entity, (b::With<Probe>, b::Without<DespawnNextTick>)>,
+) {
+    for probe in probes {
+        commands.entity(probe).insert(DespawnNextTick);
+    }
+}
+
+/// Panics if the game is in a state it should never be in.
+///
+/// Errors returned by systems, including violations found by `InvariantsPlugin`, also fail
+/// the test, since the game keeps Bevy’s panicking error handler unless asked to show errors.
+fn check_invariants(app: &mut b::App) {
+    let world = app.world_mut();
+
+    for quantity in world.query::<&Quantity>().iter(world) {
+        assert!(
+            (0.0..=1.0).contains(&quantity.base()) && quantity.temporary_stack().is_finite(),
+            "quantity out of range: {quantity:?}"
+        );
+    }
+
+    let entity_count = world.entities().count_spawned();
+    assert!(
+        entity_count <= MAX_ENTITIES,
+        "{entity_count} entities exist; something is not being despawned"
+    );
+}
@@ -0,0 +1,49 @@
+use bevy::math::Vec3;
+use bevy::prelude as b;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Moves a sound entity to follow the entity which emitted the sound, so that spatial audio
+/// tracks moving emitters.
+///
+/// If the emitter is despawned, the sound stays where it last was.
+#[derive(Debug, b::Component)]
+pub(crate) struct FollowEmitter(pub b::Entity);
+
+pub(crate) fn follow_emitters_system(
+    mut commands: b::Commands,
+    sounds: b::Query<(b::Entity, &FollowEmitter, &mut b::Transform)>,
+    emitters: b::Query<&b::Transform, b::Without<FollowEmitter>>,
+) {
+    for (sound_entity, &FollowEmitter(emitter), mut transform) in sounds {
+        match emitters.get(emitter) {
+            Ok(emitter_transform) => {
+                transform.translation = emitter_transform.translation;
+            }
+            Err(_) => {
+                commands.entity(sound_entity).remove::<FollowEmitter>();
+            }
+        }
+    }
+}
+
+/// A sound which plays once at `translation`, following `emitter` while it exists.
+pub(crate) fn emitted_sound_bundle(
+    sound: b::Handle<b::AudioSource>,
+    volume: bevy::audio::Volume,
+    speed: f32,
+    translation: Vec3,
+    emitter: b::Entity,
+) -> impl b::Bundle {
+    (
+        b::AudioPlayer::new(sound),
+        b::PlaybackSettings {
+            spatial: true,
+            volume,
+            speed,
+            ..b::PlaybackSettings::DESPAWN
+        },
+        b::Transform::from_translation(translation),
+        FollowEmitter(emitter),
+    )
+}
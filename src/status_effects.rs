@@ -0,0 +1,246 @@
+use bevy::color::Mix as _;
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::prelude as b;
+
+use crate::Team;
+use crate::bullets_and_targets::{Attackable, Hurt, HurtFlash};
+
+// -------------------------------------------------------------------------------------------------
+
+/// A kind of lingering effect which a bullet can inflict on an [`Attackable`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, b::Reflect, serde::Deserialize)]
+pub(crate) enum StatusKind {
+    /// Fast, short damage. Reinflicting it adds a stack, up to [`BURN_MAX_STACKS`], each of which
+    /// deals its own damage per tick, and restarts the duration.
+    Burn,
+    /// Slow, long damage. Reinflicting it does not add stacks, but extends the duration,
+    /// up to [`CORRODE_MAX_DURATION`].
+    Corrode,
+}
+
+/// The status effects currently affecting an [`Attackable`].
+///
+/// Removed by [`status_tint_system`] once all of the effects have worn off.
+#[derive(Debug, Default, b::Component, b::Reflect)]
+#[reflect(Component)]
+pub(crate) struct StatusEffects {
+    pub effects: Vec<ActiveStatus>,
+
+    /// Color of the sprite before [`status_tint_system`] tinted it, to restore afterward.
+    untinted_color: Option<b::Color>,
+}
+
+/// One kind of status effect in [`StatusEffects`].
+#[derive(Clone, Debug, b::Reflect)]
+pub(crate) struct ActiveStatus {
+    pub kind: StatusKind,
+    /// Who inflicted the effect, to attribute the kill.
+    pub inflicted_by: Team,
+    /// Damage dealt each time the effect ticks.
+    pub stacks: u8,
+    /// Time until the effect wears off.
+    pub remaining: f32,
+    /// Time until the effect next deals damage.
+    pub until_tick: f32,
+}
+
+const BURN_DURATION: f32 = 1.5;
+const BURN_TICK_INTERVAL: f32 = 0.25;
+const BURN_MAX_STACKS: u8 = 3;
+const BURN_TINT: b::Color = b::Color::srgb(1.0, 0.5, 0.2);
+
+const CORRODE_DURATION: f32 = 4.0;
+const CORRODE_MAX_DURATION: f32 = 8.0;
+const CORRODE_TICK_INTERVAL: f32 = 0.5;
+const CORRODE_TINT: b::Color = b::Color::srgb(0.5, 1.0, 0.3);
+
+/// Fraction of the way from the sprite’s own color to the tint of the strongest effect.
+const TINT_STRENGTH: f32 = 0.6;
+
+// -------------------------------------------------------------------------------------------------
+
+impl StatusKind {
+    fn tick_interval(self) -> f32 {
+        match self {
+            StatusKind::Burn => BURN_TICK_INTERVAL,
+            StatusKind::Corrode => CORRODE_TICK_INTERVAL,
+        }
+    }
+
+    fn tint(self) -> b::Color {
+        match self {
+            StatusKind::Burn => BURN_TINT,
+            StatusKind::Corrode => CORRODE_TINT,
+        }
+    }
+}
+
+impl StatusEffects {
+    /// Adds an effect of the given kind, or strengthens the existing one according to the
+    /// stacking rules of [`StatusKind`].
+    pub fn inflict(&mut self, kind: StatusKind, inflicted_by: Team) {
+        match self.effects.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => {
+                effect.inflicted_by = inflicted_by;
+                match kind {
+                    StatusKind::Burn => {
+                        effect.stacks = (effect.stacks + 1).min(BURN_MAX_STACKS);
+                        effect.remaining = BURN_DURATION;
+                    }
+                    StatusKind::Corrode => {
+                        effect.remaining =
+                            (effect.remaining + CORRODE_DURATION).min(CORRODE_MAX_DURATION);
+                    }
+                }
+            }
+            None => self.effects.push(ActiveStatus {
+                kind,
+                inflicted_by,
+                stacks: 1,
+                remaining: match kind {
+                    StatusKind::Burn => BURN_DURATION,
+                    StatusKind::Corrode => CORRODE_DURATION,
+                },
+                until_tick: kind.tick_interval(),
+            }),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Counts down [`StatusEffects`] and deals their damage.
+pub(crate) fn status_effects_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time>,
+    query: b::Query<(b::Entity, &mut StatusEffects, &mut Attackable)>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut statuses, mut attackable) in query {
+        if attackable.health == 0 {
+            // already dying
+            continue;
+        }
+
+        let mut damage: u8 = 0;
+        let mut inflicted_by = None;
+        for effect in &mut statuses.effects {
+            effect.remaining -= dt;
+            effect.until_tick -= dt;
+            // Add rather than set, so the tick rate of the effect does not depend on the
+            // fixed timestep.
+            while effect.until_tick <= 0.0 {
+                effect.until_tick += effect.kind.tick_interval();
+                damage = damage.saturating_add(effect.stacks);
+                inflicted_by = Some(effect.inflicted_by);
+            }
+        }
+        statuses.effects.retain(|effect| effect.remaining > 0.0);
+
+        if damage > 0 {
+            attackable.health = attackable.health.saturating_sub(damage);
+            attackable.last_hit_by = inflicted_by;
+            commands.trigger(Hurt {
+                target: entity,
+                damage,
+            });
+        }
+    }
+}
+
+/// Tints the sprites of entities with [`StatusEffects`] by the color of their effects,
+/// and restores them once the effects have worn off.
+///
+/// Entities whose [`HurtFlash`] is showing are left alone until it ends,
+/// so that the flash restores the tinted color rather than the tint replacing the flash.
+pub(crate) fn status_tint_system(
+    mut commands: b::Commands,
+    query: b::Query<(b::Entity, &mut StatusEffects, &mut b::Sprite), b::Without<HurtFlash>>,
+) {
+    for (entity, mut statuses, mut sprite) in query {
+        let untinted_color = *statuses.untinted_color.get_or_insert(sprite.color);
+
+        // The effect with the most stacks determines the tint; ties go to the first inflicted.
+        let strongest = statuses
+            .effects
+            .iter()
+            .rev()
+            .max_by_key(|effect| effect.stacks);
+        let color = match strongest {
+            Some(effect) => untinted_color.mix(&effect.kind.tint(), TINT_STRENGTH),
+            None => {
+                commands.entity(entity).remove::<StatusEffects>();
+                untinted_color
+            }
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn only_effect(statuses: &StatusEffects) -> &ActiveStatus {
+        assert_eq!(statuses.effects.len(), 1, "{:?}", statuses.effects);
+        &statuses.effects[0]
+    }
+
+    #[test]
+    fn burn_stacks_up_to_max_and_restarts() {
+        let mut statuses = StatusEffects::default();
+        statuses.inflict(StatusKind::Burn, Team::Player);
+        statuses.effects[0].remaining = 0.5;
+
+        statuses.inflict(StatusKind::Burn, Team::Player);
+        let effect = only_effect(&statuses);
+        assert_eq!(effect.stacks, 2);
+        assert_eq!(effect.remaining, BURN_DURATION);
+
+        for _ in 0..BURN_MAX_STACKS {
+            statuses.inflict(StatusKind::Burn, Team::Player);
+        }
+        assert_eq!(only_effect(&statuses).stacks, BURN_MAX_STACKS);
+    }
+
+    #[test]
+    fn corrode_extends_up_to_max() {
+        let mut statuses = StatusEffects::default();
+        statuses.inflict(StatusKind::Corrode, Team::Player);
+        statuses.effects[0].remaining = 1.0;
+
+        statuses.inflict(StatusKind::Corrode, Team::Player);
+        let effect = only_effect(&statuses);
+        assert_eq!(effect.stacks, 1);
+        assert_eq!(effect.remaining, 1.0 + CORRODE_DURATION);
+
+        statuses.inflict(StatusKind::Corrode, Team::Player);
+        assert_eq!(only_effect(&statuses).remaining, CORRODE_MAX_DURATION);
+    }
+
+    #[test]
+    fn kinds_are_separate_and_latest_inflicter_counts() {
+        let mut statuses = StatusEffects::default();
+        statuses.inflict(StatusKind::Burn, Team::Enemy);
+        statuses.inflict(StatusKind::Corrode, Team::Player);
+        statuses.inflict(StatusKind::Burn, Team::Player);
+
+        let kinds: Vec<(StatusKind, Team, u8)> = statuses
+            .effects
+            .iter()
+            .map(|effect| (effect.kind, effect.inflicted_by, effect.stacks))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (StatusKind::Burn, Team::Player, 2),
+                (StatusKind::Corrode, Team::Player, 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::path::PathBuf;
+
+use bevy::ecs::schedule::IntoScheduleConfigs as _;
+use bevy::prelude as b;
+
+use crate::persistence;
+use crate::pickup::PickupSpawnType;
+use crate::quantity::{Adjustment, Quantities, QuantityDelta, QuantityHold, QuantityKind};
+use crate::run_stats::{RunEvent, RunStats};
+use crate::{Coherence, Fervor, Fever, GameState, Team, WinOrGameOver};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Writes a JSON timeline of each run into a directory, for offline balance analysis.
+///
+/// Only enabled if the game is started with the `--telemetry <directory>` option.
+pub(crate) struct TelemetryPlugin {
+    /// Where to write the timelines, or [`None`] to not record them.
+    pub directory: Option<PathBuf>,
+}
+
+impl b::Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut b::App) {
+        let Some(directory) = self.directory.clone() else {
+            return;
+        };
+        b::info!("writing run telemetry to {}", directory.display());
+
+        app.insert_resource(Telemetry {
+            directory,
+            run: None,
+        })
+        .add_systems(
+            b::OnTransition {
+                exited: GameState::Menu,
+                entered: GameState::Playing,
+            },
+            start_run,
+        )
+        .add_systems(
+            b::FixedUpdate,
+            record_system
+                .after(crate::quantity::apply_quantity_deltas_system)
+                .after(crate::run_stats::run_clock_system)
+                .run_if(b::in_state(GameState::Playing)),
+        )
+        .add_systems(b::OnEnter(GameState::WinOrGameOver), finish_run)
+        // restarting finishes the old run, if not already finished, and starts a new one
+        .add_systems(
+            b::OnEnter(GameState::Playing),
+            (finish_run, start_run)
+                .chain()
+                .before(crate::despawn_game)
+                .run_if(b::resource_exists::<crate::RestartRun>),
+        )
+        // in case the run was abandoned
+        .add_systems(b::OnEnter(GameState::Menu), finish_run);
+    }
+}
+
+/// Seconds of game time between samples of quantity values.
+const SAMPLE_INTERVAL: f32 = 1.0;
+
+#[derive(Debug, b::Resource)]
+struct Telemetry {
+    directory: PathBuf,
+    /// Data for the run in progress, if any.
+    run: Option<RunTimeline>,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+struct RunTimeline {
+    game_version: &'static str,
+    outcome: Option<WinOrGameOver>,
+    duration_secs: f32,
+    shots_fired: u32,
+    kills: u32,
+    damage_taken: u32,
+    quantity_samples: Vec<QuantitySample>,
+    events: Vec<TimelineEvent>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct QuantitySample {
+    time: f32,
+    coherence: f32,
+    fever: f32,
+    fervor: f32,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct TimelineEvent {
+    time: f32,
+    #[serde(flatten)]
+    kind: TimelineEventKind,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TimelineEventKind {
+    ShotFired {
+        team: Team,
+    },
+    EnemyKilled,
+    PlayerDamaged,
+    PickupCollected {
+        kind: PickupSpawnType,
+    },
+    LifeLost,
+    QuantityDelta {
+        quantity: QuantityKind,
+        adjustment: Adjustment,
+        amount: f32,
+    },
+    QuantityHold {
+        quantity: QuantityKind,
+        duration: f32,
+    },
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn directory_from_args(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == "--telemetry" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+fn start_run(mut telemetry: b::ResMut<Telemetry>) {
+    telemetry.run = Some(RunTimeline {
+        game_version: env!("CARGO_PKG_VERSION"),
+        ..RunTimeline::default()
+    });
+}
+
+fn record_system(
+    mut telemetry: b::ResMut<Telemetry>,
+    stats: b::Res<RunStats>,
+    mut run_events: b::MessageReader<RunEvent>,
+    mut quantity_deltas: b::MessageReader<QuantityDelta>,
+    mut quantity_holds: b::MessageReader<QuantityHold>,
+    quantities: Quantities,
+) -> b::Result {
+    let coherence = quantities.get::<Coherence>()?;
+    let fever = quantities.get::<Fever>()?;
+    let fervor = quantities.get::<Fervor>()?;
+
+    let Some(run) = &mut telemetry.run else {
+        return Ok(());
+    };
+    let time = stats.elapsed;
+
+    let next_sample_time = run.quantity_samples.len() as f32 * SAMPLE_INTERVAL;
+    if time >= next_sample_time {
+        run.quantity_samples.push(QuantitySample {
+            time,
+            coherence: coherence.effective_value(),
+            fever: fever.effective_value(),
+            fervor: fervor.effective_value(),
+        });
+    }
+
+    for event in run_events.read() {
+        let kind = match *event {
+            RunEvent::ShotFired(team) => TimelineEventKind::ShotFired { team },
+            RunEvent::EnemyKilled => TimelineEventKind::EnemyKilled,
+            RunEvent::PlayerDamaged => TimelineEventKind::PlayerDamaged,
+            RunEvent::PickupCollected(kind) => TimelineEventKind::PickupCollected { kind },
+            RunEvent::LifeLost => TimelineEventKind::LifeLost,
+        };
+        run.events.push(TimelineEvent { time, kind });
+    }
+    for delta in quantity_deltas.read() {
+        run.events.push(TimelineEvent {
+            time,
+            kind: TimelineEventKind::QuantityDelta {
+                quantity: delta.quantity,
+                adjustment: delta.adjustment,
+                amount: delta.amount,
+            },
+        });
+    }
+    for hold in quantity_holds.read() {
+        run.events.push(TimelineEvent {
+            time,
+            kind: TimelineEventKind::QuantityHold {
+                quantity: hold.quantity,
+                duration: hold.duration,
+            },
+        });
+    }
+    Ok(())
+}
+
+fn finish_run(
+    mut telemetry: b::ResMut<Telemetry>,
+    stats: b::Res<RunStats>,
+    wog_state: Option<b::Res<b::State<WinOrGameOver>>>,
+) {
+    let Some(mut run) = telemetry.run.take() else {
+        return;
+    };
+    run.outcome = wog_state.map(|state| state.get().clone());
+    run.duration_secs = stats.elapsed;
+    run.shots_fired = stats.shots_fired;
+    run.kills = stats.kills;
+    run.damage_taken = stats.damage_taken;
+
+    let path = persistence::timestamped_path(&telemetry.directory, "run-", ".json");
+
+    let result = std::fs::create_dir_all(&telemetry.directory)
+        .and_then(|()| std::fs::File::create(&path))
+        .and_then(|file| {
+            serde_json::to_writer_pretty(std::io::BufWriter::new(file), &run)
+                .map_err(std::io::Error::from)
+        });
+    match result {
+        Ok(()) => b::info!("wrote run telemetry to {}", path.display()),
+        Err(error) => b::error!(
+            "failed to write run telemetry to {}: {error}",
+            path.display()
+        ),
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::ecs::change_detection::DetectChanges as _;
+use bevy::prelude as b;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Animated text. The entity’s [`b::Text2d`] is left empty, and the text is displayed by one
+/// [`b::TextSpan`] child per character, so that characters can be colored individually.
+///
+/// Every change of text is revealed one character at a time, then the [`TextEffectStyle`]
+/// applies. (Unrevealed characters are left out rather than made transparent,
+/// because [`b::TextShadow`] would still show them.)
+#[derive(Debug, Default, b::Component)]
+#[require(b::Text2d)]
+pub(crate) struct TextEffect {
+    text: &'static str,
+    style: TextEffectStyle,
+    /// Seconds since `text` or `style` last changed.
+    age: f32,
+    /// The text which the child spans were created for, if they have been.
+    spans_text: Option<&'static str>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum TextEffectStyle {
+    #[default]
+    Plain,
+    /// Color pulses between white and red, as a warning.
+    Pulse,
+    /// A wave of changing color moves across the characters.
+    Rainbow,
+}
+
+/// Characters per second revealed by the typewriter effect.
+const TYPEWRITER_RATE: f32 = 30.0;
+
+// -------------------------------------------------------------------------------------------------
+
+impl TextEffect {
+    /// Changes the displayed text and style, restarting the animation if they are different.
+    pub fn set(&mut self, text: &'static str, style: TextEffectStyle) {
+        if self.text != text || self.style != style {
+            self.text = text;
+            self.style = style;
+            self.age = 0.0;
+        }
+    }
+
+    /// Returns the color of the character at the given index, or [`None`] if it has not been
+    /// revealed yet.
+    fn color_of(&self, char_index: usize) -> Option<b::Color> {
+        if char_index as f32 >= self.age * TYPEWRITER_RATE {
+            return None;
+        }
+        let age = self.age;
+        Some(match self.style {
+            TextEffectStyle::Plain => b::Color::WHITE,
+            TextEffectStyle::Pulse => {
+                let redness = 0.5 + 0.5 * (age * 6.0).sin();
+                b::Color::srgb(1.0, 1.0 - redness * 0.7, 1.0 - redness * 0.7)
+            }
+            TextEffectStyle::Rainbow => {
+                let hue = (char_index as f32 * 40.0 + age * 240.0).rem_euclid(360.0);
+                b::Color::hsl(hue, 0.9, 0.75)
+            }
+        })
+    }
+}
+
+/// Animates [`TextEffect`]s.
+///
+/// Uses real time so that text is unaffected by pausing and [`crate::GameSpeed`].
+pub(crate) fn text_effect_system(
+    mut commands: b::Commands,
+    time: b::Res<b::Time<b::Real>>,
+    effects: b::Query<(
+        b::Entity,
+        &mut TextEffect,
+        b::Ref<b::TextFont>,
+        Option<&b::Children>,
+    )>,
+    mut spans: b::Query<(&mut b::TextSpan, &mut b::TextColor, &mut b::TextFont)>,
+) {
+    for (entity, mut effect, font, children) in effects {
+        effect.age += time.delta_secs();
+
+        if effect.spans_text != Some(effect.text) {
+            effect.spans_text = Some(effect.text);
+            commands
+                .entity(entity)
+                .despawn_related::<b::Children>()
+                .with_children(|parent| {
+                    for _ in effect.text.chars() {
+                        parent.spawn((b::TextSpan::new(""), font.clone()));
+                    }
+                });
+            continue;
+        }
+
+        for ((i, ch), &child) in effect
+            .text
+            .chars()
+            .enumerate()
+            .zip(children.into_iter().flatten())
+        {
+            let Ok((mut span, mut color, mut span_font)) = spans.get_mut(child) else {
+                continue;
+            };
+            match effect.color_of(i) {
+                Some(new_color) => {
+                    if span.is_empty() {
+                        span.0 = ch.to_string();
+                    }
+                    if color.0 != new_color {
+                        color.0 = new_color;
+                    }
+                }
+                None => {
+                    if !span.is_empty() {
+                        span.0.clear();
+                    }
+                }
+            }
+            if font.is_changed() {
+                *span_font = font.clone();
+            }
+        }
+    }
+}
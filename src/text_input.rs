@@ -0,0 +1,76 @@
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude as b;
+
+// -------------------------------------------------------------------------------------------------
+
+/// A single-line text field, edited by typing while it exists.
+/// Its [`b::Text`] shows the entered text followed by a cursor.
+///
+/// There is no focus handling; every `TextInput` receives the keys typed,
+/// which is fine as long as there is only one at a time.
+#[derive(Debug, b::Component)]
+#[require(b::Text)]
+pub(crate) struct TextInput {
+    pub value: String,
+    pub max_chars: usize,
+    /// Typed characters for which this returns false are ignored.
+    pub accept: fn(char) -> bool,
+}
+
+/// Event triggered when Enter is pressed in a [`TextInput`].
+#[derive(Debug, b::Event)]
+pub(crate) struct SubmitText {
+    pub input: b::Entity,
+    pub value: String,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl TextInput {
+    pub fn new(max_chars: usize, accept: fn(char) -> bool) -> Self {
+        Self {
+            value: String::new(),
+            max_chars,
+            accept,
+        }
+    }
+}
+
+pub(crate) fn text_input_system(
+    mut commands: b::Commands,
+    mut keys: b::MessageReader<KeyboardInput>,
+    inputs: b::Query<(b::Entity, &mut TextInput, &mut b::Text)>,
+) {
+    let keys: Vec<&KeyboardInput> = keys
+        .read()
+        .filter(|key| key.state == ButtonState::Pressed)
+        .collect();
+
+    for (entity, mut input, mut text) in inputs {
+        for key in &keys {
+            match &key.logical_key {
+                Key::Backspace => {
+                    input.value.pop();
+                }
+                Key::Enter => commands.trigger(SubmitText {
+                    input: entity,
+                    value: input.value.clone(),
+                }),
+                Key::Character(chars) => {
+                    for ch in chars.chars() {
+                        if (input.accept)(ch) && input.value.chars().count() < input.max_chars {
+                            input.value.push(ch);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let new_text = format!("{}_", input.value);
+        if text.0 != new_text {
+            text.0 = new_text;
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::ecs::system::NonSendMarker;
+use bevy::prelude as b;
+use bevy::render::render_resource::TextureFormat;
+use bevy::window::PrimaryWindow;
+
+use crate::quantity::{Fervor, Quantities};
+use crate::score::Score;
+use crate::settings::Settings;
+use crate::{GAME_NAME, GameAssets, GameState, SpriteId, WinOrGameOver};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Runs won and lost since the game was started, shown in the window title when
+/// [`Settings::presence_in_title`] is set.
+#[derive(Debug, Default, b::Resource)]
+pub(crate) struct SessionRecord {
+    wins: u32,
+    losses: u32,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+pub(crate) fn count_session_record(
+    mut record: b::ResMut<SessionRecord>,
+    wog_state: Option<b::Res<b::State<WinOrGameOver>>>,
+) {
+    match wog_state.as_deref().map(b::State::get) {
+        Some(WinOrGameOver::Win) => record.wins += 1,
+        Some(WinOrGameOver::GameOver) | None => record.losses += 1,
+    }
+}
+
+/// Whether [`update_window_title_system`] must run every frame, to keep the title current.
+pub(crate) fn presence_in_title(settings: b::Res<Settings>) -> bool {
+    settings.presence_in_title
+}
+
+/// Sets the window title to the game name plus what state the game is in.
+///
+/// If [`Settings::presence_in_title`] is set, the title also describes the run in progress and
+/// the [`SessionRecord`], for stream overlays and window-capture tools to show.
+pub(crate) fn update_window_title_system(
+    state: b::Res<b::State<GameState>>,
+    wog_state: Option<b::Res<b::State<WinOrGameOver>>>,
+    settings: b::Res<Settings>,
+    record: b::Res<SessionRecord>,
+    score: b::Res<Score>,
+    quantities: Quantities,
+    mut window: b::Single<&mut b::Window, b::With<PrimaryWindow>>,
+) -> b::Result {
+    let state_name = match *state.get() {
+        GameState::AssetLoading => Some("Loading"),
+        GameState::Menu | GameState::Playing => None,
+        GameState::Paused => Some("Paused"),
+        GameState::HighScores => Some("High Scores"),
+        GameState::WinOrGameOver => match wog_state.as_deref().map(b::State::get) {
+            Some(WinOrGameOver::Win) => Some("Win"),
+            Some(WinOrGameOver::GameOver) | None => Some("Game Over"),
+        },
+    };
+    let title = if settings.presence_in_title {
+        let fervor_percent = quantities.get::<Fervor>()?.display_percent();
+        let mut presence = match *state.get() {
+            GameState::Menu => "In menu".to_owned(),
+            GameState::Playing | GameState::Paused => format!(
+                "{}, {fervor_percent}% Fervor, {} points",
+                state_name.unwrap_or("Playing"),
+                score.0
+            ),
+            GameState::WinOrGameOver => {
+                format!("{}, {} points", state_name.unwrap_or_default(), score.0)
+            }
+            GameState::AssetLoading | GameState::HighScores => {
+                state_name.unwrap_or_default().to_owned()
+            }
+        };
+        if record.wins + record.losses > 0 {
+            presence = format!("{presence} ({}W {}L)", record.wins, record.losses);
+        }
+        format!("{GAME_NAME} — {presence}")
+    } else {
+        match state_name {
+            Some(state_name) => format!("{GAME_NAME} — {state_name}"),
+            None => GAME_NAME.to_owned(),
+        }
+    };
+    if window.title != title {
+        window.title = title;
+    }
+    Ok(())
+}
+
+/// Sets the window icon to the player ship sprite.
+///
+/// Bevy has no API for window icons, so this talks to `winit` directly,
+/// which must happen on the main thread.
+pub(crate) fn set_window_icon_system(
+    _main_thread: NonSendMarker,
+    assets: b::Res<GameAssets>,
+    images: b::Res<b::Assets<b::Image>>,
+) -> b::Result {
+    let image = images
+        .get(&assets.sprite(SpriteId::PlayerShip))
+        .ok_or_else(|| b::BevyError::from("asset not loaded"))?;
+    let format = image.texture_descriptor.format;
+    if !matches!(
+        format,
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb
+    ) {
+        return Err(b::BevyError::from(format!(
+            "icon image has unsupported format {format:?}"
+        )));
+    }
+    let data = image
+        .data
+        .clone()
+        .ok_or_else(|| b::BevyError::from("icon image data not available"))?;
+    let icon = winit::window::Icon::from_rgba(data, image.width(), image.height())?;
+
+    bevy::winit::WINIT_WINDOWS.with_borrow(|winit_windows| {
+        for window in winit_windows.windows.values() {
+            window.set_window_icon(Some(icon.clone()));
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use avian2d::prelude as p;
+use bevy::math::vec3;
+use bevy::prelude as b;
+
+use crate::{GameAssets, PLAYER_COLLIDER_RADIUS, PLAYFIELD_LAYERS, PLAYFIELD_RECT, SpriteId, Zees};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Copy of the player ship one playfield width away from it, used when
+/// [`Settings::wrap_playfield`](crate::settings::Settings::wrap_playfield) is set, so that a ship
+/// partway across one edge also appears, and can be hit, at the other edge.
+///
+/// Bullets which hit the ghost hurt the ship, and pickups it touches are collected.
+#[derive(Debug, b::Component)]
+#[require(p::CollidingEntities)]
+pub(crate) struct WrapGhost {
+    pub of: b::Entity,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Wraps an x coordinate into the horizontal extent of the playfield.
+pub(crate) fn wrap_x(x: f32) -> f32 {
+    PLAYFIELD_RECT.min.x + (x - PLAYFIELD_RECT.min.x).rem_euclid(PLAYFIELD_RECT.width())
+}
+
+pub(crate) fn wrap_ghost_bundle(assets: &GameAssets, ship: b::Entity) -> impl b::Bundle {
+    (
+        WrapGhost { of: ship },
+        b::Sprite::from_image(assets.sprite(SpriteId::PlayerShip)),
+        b::Transform::from_xyz(0.0, 0.0, Zees::Player.z()),
+        PLAYFIELD_LAYERS,
+        b::Visibility::Visible,
+        p::Collider::circle(PLAYER_COLLIDER_RADIUS),
+    )
+}
+
+/// Moves each [`WrapGhost`] to the far side of the playfield from its ship,
+/// beyond whichever edge the ship is nearer to.
+pub(crate) fn update_wrap_ghosts_system(
+    ships: b::Query<&b::Transform, b::Without<WrapGhost>>,
+    ghosts: b::Query<(&WrapGhost, &mut b::Transform)>,
+) {
+    for (ghost, mut ghost_transform) in ghosts {
+        let Ok(ship_transform) = ships.get(ghost.of) else {
+            continue;
+        };
+        let offset = if ship_transform.translation.x < PLAYFIELD_RECT.center().x {
+            PLAYFIELD_RECT.width()
+        } else {
+            -PLAYFIELD_RECT.width()
+        };
+        *ghost_transform =
+            ship_transform.with_translation(ship_transform.translation + vec3(offset, 0.0, 0.0));
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn wrap_x_inside_is_unchanged() {
+        assert_near(wrap_x(PLAYFIELD_RECT.center().x), PLAYFIELD_RECT.center().x);
+        assert_near(wrap_x(PLAYFIELD_RECT.min.x), PLAYFIELD_RECT.min.x);
+    }
+
+    #[test]
+    fn wrap_x_outside() {
+        let b::Rect { min, max } = PLAYFIELD_RECT;
+        let width = PLAYFIELD_RECT.width();
+        assert_near(wrap_x(max.x + 3.0), min.x + 3.0);
+        assert_near(wrap_x(min.x - 3.0), max.x - 3.0);
+        // the right edge is the same place as the left edge
+        assert_near(wrap_x(max.x), min.x);
+        // more than once around
+        assert_near(wrap_x(min.x + 3.0 + width * 2.0), min.x + 3.0);
+        assert_near(wrap_x(min.x + 3.0 - width * 2.0), min.x + 3.0);
+    }
+}